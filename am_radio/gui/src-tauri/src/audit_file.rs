@@ -0,0 +1,169 @@
+#![allow(dead_code)]
+// audit_file.rs
+// Opt-in on-disk audit log: one JSON entry per line, rotated to "<file>.1" at a size limit
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::model::AuditEntry;
+
+/// Where audit entries are appended, and the size at which the file is rotated
+#[derive(Clone, Debug, Serialize)]
+pub struct AuditPersistence {
+    pub path: PathBuf,
+    pub max_bytes: u64,
+}
+
+impl AuditPersistence {
+    pub fn new(path: PathBuf, max_bytes: u64) -> Result<Self, String> {
+        if max_bytes < Config::MIN_AUDIT_FILE_BYTES {
+            return Err(format!("Audit file limit {} bytes too small (min {})",
+                max_bytes, Config::MIN_AUDIT_FILE_BYTES));
+        }
+        Ok(Self { path, max_bytes })
+    }
+}
+
+/// The previous file after rotation ("audit.jsonl" -> "audit.jsonl.1")
+pub fn rotated_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".1");
+    PathBuf::from(name)
+}
+
+/// Append one entry, first rotating the file if the line would push it past the limit
+/// (only one rotated file is kept)
+pub fn append(persistence: &AuditPersistence, entry: &AuditEntry) -> Result<(), String> {
+    let path = &persistence.path;
+    let mut line = serde_json::to_string(entry)
+        .map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+    line.push('\n');
+
+    let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    if size > 0 && size + line.len() as u64 > persistence.max_bytes {
+        std::fs::rename(path, rotated_path(path))
+            .map_err(|e| format!("Failed to rotate {}: {}", path.display(), e))?;
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    file.write_all(line.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// The last `limit` entries, oldest first, reaching into the rotated file if the current
+/// one is short. Unreadable lines (e.g. a torn final write) are skipped.
+pub fn read_tail(path: &Path, limit: usize) -> Vec<AuditEntry> {
+    let mut entries = read_entries(path);
+    if entries.len() < limit {
+        let mut older = read_entries(&rotated_path(path));
+        older.append(&mut entries);
+        entries = older;
+    }
+    let skip = entries.len().saturating_sub(limit);
+    entries.split_off(skip)
+}
+
+/// Entries as CSV with a "timestamp,level,operator,message" header
+pub fn to_csv(entries: &[AuditEntry]) -> String {
+    let mut csv = String::from("timestamp,level,operator,message\n");
+    for entry in entries {
+        csv.push_str(&format!("{},{},{},{}\n",
+            entry.timestamp,
+            csv_field(&entry.level),
+            csv_field(entry.operator.as_deref().unwrap_or("")),
+            csv_field(&entry.message)));
+    }
+    csv
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn read_entries(path: &Path) -> Vec<AuditEntry> {
+    std::fs::read_to_string(path)
+        .map(|text| text.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("amradio_audit_{}_{}.jsonl", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(rotated_path(&path));
+        path
+    }
+
+    fn entry(n: u64) -> AuditEntry {
+        AuditEntry { timestamp: 1_700_000_000 + n, level: "INFO".to_string(), message: format!("entry {}", n), operator: None }
+    }
+
+    #[test]
+    fn test_append_and_reload() {
+        let path = temp_path("append");
+        let persistence = AuditPersistence::new(path.clone(), 1_000_000).unwrap();
+        for n in 0..5 {
+            append(&persistence, &entry(n)).unwrap();
+        }
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text.lines().count(), 5);
+
+        let tail = read_tail(&path, 3);
+        let messages: Vec<&str> = tail.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, ["entry 2", "entry 3", "entry 4"]);
+        assert_eq!(tail[2].timestamp, 1_700_000_004);
+
+        // Torn last line is ignored
+        std::fs::write(&path, format!("{}{{\"timestamp\":", text)).unwrap();
+        assert_eq!(read_tail(&path, 100).len(), 5);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_rotation_at_size_limit() {
+        let path = temp_path("rotate");
+        let line_len = serde_json::to_string(&entry(0)).unwrap().len() as u64 + 1;
+        let persistence = AuditPersistence { path: path.clone(), max_bytes: line_len * 3 };
+
+        for n in 0..3 {
+            append(&persistence, &entry(n)).unwrap();
+        }
+        assert!(!rotated_path(&path).exists());
+
+        // Fourth line would exceed the limit - the full file moves aside
+        append(&persistence, &entry(3)).unwrap();
+        assert_eq!(std::fs::read_to_string(rotated_path(&path)).unwrap().lines().count(), 3);
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+        assert!(std::fs::metadata(&path).unwrap().len() <= persistence.max_bytes);
+
+        // Reload spans both files, oldest first
+        let tail = read_tail(&path, 3);
+        let messages: Vec<&str> = tail.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, ["entry 1", "entry 2", "entry 3"]);
+
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(rotated_path(&path));
+    }
+
+    #[test]
+    fn test_limit_validated() {
+        assert!(AuditPersistence::new(PathBuf::from("audit.jsonl"), 10).is_err());
+        assert!(read_tail(&temp_path("missing"), 10).is_empty());
+    }
+}
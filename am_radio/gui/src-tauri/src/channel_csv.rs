@@ -0,0 +1,161 @@
+#![allow(dead_code)]
+// channel_csv.rs
+// Channel plans imported from spreadsheets: "id,frequency,enabled,amplitude,phase" rows
+
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::model::Channel;
+
+/// Outcome for one data row of an import
+#[derive(Clone, Debug, Serialize)]
+pub struct CsvRowResult {
+    pub line: usize,          // 1-based line in the file
+    pub channel: Option<u8>,
+    pub ok: bool,
+    pub message: String,
+}
+
+/// Per-row report; nothing is applied unless every row is valid
+#[derive(Clone, Debug, Serialize)]
+pub struct CsvImportReport {
+    pub applied: bool,
+    pub rows: Vec<CsvRowResult>,
+}
+
+/// Column names of the optional header row, in order
+const HEADER: [&str; 5] = ["id", "frequency", "enabled", "amplitude", "phase"];
+
+/// Parse every data row, keeping its line number. Blank lines and a leading
+/// header row naming the expected columns are skipped; anything else is a row.
+pub fn parse(text: &str) -> Vec<(usize, Result<Channel, String>)> {
+    let mut rows = Vec::new();
+    let mut seen = Vec::new();
+    let mut first = true;
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if first && is_header(&fields) {
+            first = false;
+            continue;
+        }
+        first = false;
+
+        let row = parse_row(&fields).and_then(|channel| {
+            if seen.contains(&channel.id) {
+                return Err(format!("Duplicate channel {}", channel.id));
+            }
+            seen.push(channel.id);
+            Ok(channel)
+        });
+        rows.push((index + 1, row));
+    }
+
+    rows
+}
+
+fn is_header(fields: &[&str]) -> bool {
+    fields.len() == HEADER.len()
+        && fields.iter().zip(HEADER).all(|(field, name)| field.eq_ignore_ascii_case(name))
+}
+
+fn parse_row(fields: &[&str]) -> Result<Channel, String> {
+    if fields.len() != 5 {
+        return Err(format!("Expected 5 fields, got {}", fields.len()));
+    }
+
+    let id: u8 = fields[0].parse().map_err(|_| format!("Invalid channel id: '{}'", fields[0]))?;
+    if !(1..=Config::NUM_CHANNELS).contains(&id) {
+        return Err(format!("Channel {} out of range (1-{})", id, Config::NUM_CHANNELS));
+    }
+
+    let frequency: u32 = fields[1].parse().map_err(|_| format!("Invalid frequency: '{}'", fields[1]))?;
+    if !(Config::MIN_FREQUENCY..=Config::MAX_FREQUENCY).contains(&frequency) {
+        return Err(format!("Frequency {} out of range ({}-{})",
+            frequency, Config::MIN_FREQUENCY, Config::MAX_FREQUENCY));
+    }
+
+    let enabled = match fields[2].to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => true,
+        "0" | "false" | "no" | "off" => false,
+        other => return Err(format!("Invalid enabled flag: '{}'", other)),
+    };
+
+    let amplitude: f32 = fields[3].parse().map_err(|_| format!("Invalid amplitude: '{}'", fields[3]))?;
+    if !(0.0..=1.0).contains(&amplitude) {
+        return Err(format!("Amplitude {} out of range (0.0-1.0)", amplitude));
+    }
+
+    let phase: f32 = fields[4].parse().map_err(|_| format!("Invalid phase: '{}'", fields[4]))?;
+    if !(0.0..360.0).contains(&phase) {
+        return Err(format!("Phase {} out of range (0-360)", phase));
+    }
+
+    Ok(Channel { id, enabled, frequency, amplitude, phase, pilot: false })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_well_formed() {
+        let rows = parse("1,540000,1,0.5,0\n2,640000,0,1.0,90.5\n");
+        assert_eq!(rows.len(), 2);
+
+        let (line, first) = &rows[0];
+        let first = first.as_ref().unwrap();
+        assert_eq!(*line, 1);
+        assert_eq!((first.id, first.frequency, first.enabled), (1, 540_000, true));
+        assert_eq!(first.amplitude, 0.5);
+
+        let second = rows[1].1.as_ref().unwrap();
+        assert!(!second.enabled);
+        assert_eq!(second.phase, 90.5);
+    }
+
+    #[test]
+    fn test_parse_bad_frequency_row() {
+        let rows = parse("1,540000,1,0.5,0\n2,99000,1,0.5,0\n3,740000,1,0.5,0\n");
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0].1.is_ok());
+        assert_eq!(rows[1].0, 2);
+        assert!(rows[1].1.as_ref().unwrap_err().contains("99000"));
+        assert!(rows[2].1.is_ok());
+    }
+
+    #[test]
+    fn test_parse_header_and_whitespace() {
+        let text = "id, frequency, enabled, amplitude, phase\n\n  4 , 1000000 , true , 0.25 , 180  \n";
+        let rows = parse(text);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].0, 3);
+
+        let channel = rows[0].1.as_ref().unwrap();
+        assert_eq!((channel.id, channel.frequency, channel.enabled), (4, 1_000_000, true));
+        assert_eq!(channel.phase, 180.0);
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_rows() {
+        let rows = parse("13,540000,1,0.5,0\n1,540000,maybe,0.5,0\n1,540000,1,1.5,0\n1,540000,1,0.5\n1,540000,1,0.5,0\n1,640000,1,0.5,0\n");
+        let errors: Vec<bool> = rows.iter().map(|(_, r)| r.is_err()).collect();
+        assert_eq!(errors, vec![true, true, true, true, false, true]);
+        assert!(rows[5].1.as_ref().unwrap_err().contains("Duplicate"));
+    }
+
+    #[test]
+    fn test_parse_malformed_first_row_is_reported() {
+        // Not the header - a data row with a bad id must not vanish silently
+        let rows = parse("x1,540000,1,0.5,0\n2,640000,1,0.5,0\n");
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].0, 1);
+        assert!(rows[0].1.as_ref().unwrap_err().contains("x1"));
+        assert!(rows[1].1.is_ok());
+    }
+}
@@ -2,13 +2,27 @@
 // Tauri commands - API between JS and Rust
 // Updated with state machine support
 
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tauri::State;
 use tokio::sync::RwLock;
 use serde::{Deserialize, Serialize};
 
-use crate::model::NetworkManager;
-use crate::state_machine::{BroadcastState, SourceMode};
+use crate::channel_csv::CsvImportReport;
+use crate::config::{ChannelPresets, Config};
+use crate::console;
+use crate::event_bus::{EventBusHealth, EventType};
+use crate::model::{
+    format_endpoint, AmplitudeBudget, AuditEntry, AuditSummary, BramInfo, BurnInCycle, Channel, ChannelBounds, ChannelChange, ChannelCapability,
+    ConnectTiming, ConnectionTarget, DeviceState, DiagnosticReport, EffectiveConfig, FrequencyUnit, LatencySample,
+    LicenseWindow, LinkBench, NetworkManager, PlanBandwidth, PostResult, ProtocolCheck, ReliabilityStats,
+    SequenceStep, SessionStats, StepResult, TemperatureReading, TemperatureUnit, WatchdogMargin,
+};
+use crate::retry::RetryConfig;
+use crate::scene;
+use crate::settings::Endpoint;
+use crate::stations;
+use crate::state_machine::{BroadcastState, SelftestResult, SourceMode, StopReason};
 
 pub type AppState = Arc<RwLock<NetworkManager>>;
 
@@ -21,8 +35,14 @@ pub struct StateResponse {
     pub watchdog: String,
     pub source: String,
     pub channels: Vec<ChannelResponse>,
-    pub fpga_temperature: Option<f32>,
+    pub fpga_temperature: Option<f32>,  // Celsius
+    pub temp_c: Option<f32>,
+    pub temp_f: Option<f32>,
+    pub temperature_unit: TemperatureUnit,
     pub error_count: u32,
+    pub muted: bool,
+    pub frequency_unit: FrequencyUnit,  // Unit of every channel frequency in this response
+    pub status_age_secs: Option<u64>,   // None until the first status arrives
 
     // State machine helpers for UI
     pub can_arm: bool,
@@ -36,14 +56,47 @@ pub struct StateResponse {
 pub struct ChannelResponse {
     pub id: u8,
     pub enabled: bool,
-    pub frequency: u32,
+    pub frequency: f64,
     pub amplitude: f32,
+    pub pilot: bool,
+}
+
+impl ChannelResponse {
+    fn with_unit(c: &Channel, unit: FrequencyUnit) -> Self {
+        Self {
+            id: c.id,
+            enabled: c.enabled,
+            frequency: unit.hz_to_unit(c.frequency),
+            amplitude: c.amplitude,
+            pilot: c.pilot,
+        }
+    }
+}
+
+impl From<&Channel> for ChannelResponse {
+    fn from(c: &Channel) -> Self {
+        Self::with_unit(c, FrequencyUnit::Hz)
+    }
+}
+
+#[derive(Serialize)]
+pub struct ChannelsGroupedResponse {
+    pub enabled: Vec<ChannelResponse>,
+    pub disabled: Vec<ChannelResponse>,
+}
+
+#[derive(Serialize)]
+pub struct ActiveChannelsResponse {
+    pub has_active: bool,  // Start button gate
+    pub count: usize,
 }
 
 #[derive(Deserialize)]
 pub struct ChannelUpdate {
     pub enabled: Option<bool>,
-    pub frequency: Option<u32>,
+    pub frequency: Option<f64>,
+    pub unit: Option<FrequencyUnit>,  // Unit of `frequency` (default Hz)
+    pub amplitude: Option<f32>,  // Explicit 0 keeps an enabled channel silent
 }
 
 // CONNECTION
@@ -52,22 +105,156 @@ pub struct ChannelUpdate {
 pub async fn connect(
     ip: String,
     port: u16,
+    run_post: Option<bool>,
     state: State<'_, AppState>,
 ) -> Result<String, String> {
     let manager = state.read().await;
     manager.connect(&ip, port).await?;
-    Ok(format!("Connected to {}:{}", ip, port))
+
+    // Optional power-on self test (channels are left disabled afterwards)
+    if run_post.unwrap_or(false) {
+        let results = manager.power_on_self_test().await?;
+        let failed: Vec<String> = results.iter().filter(|r| !r.passed).map(|r| format!("CH{}", r.channel)).collect();
+        if !failed.is_empty() {
+            return Ok(format!("Connected to {} (self test failed: {})", format_endpoint(&ip, port), failed.join(", ")));
+        }
+        return Ok(format!("Connected to {} (self test passed)", format_endpoint(&ip, port)));
+    }
+    Ok(format!("Connected to {}", format_endpoint(&ip, port)))
+}
+
+/// Connect to the first reachable endpoint (primary first); the list is reused on reconnect
+#[tauri::command]
+pub async fn connect_with_failover(
+    endpoints: Vec<Endpoint>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let manager = state.read().await;
+    let active = manager.connect_with_failover(&endpoints).await?;
+    Ok(format!("Connected to {}", format_endpoint(&active.ip, active.port)))
+}
+
+/// Endpoint currently in use (changes after a failover)
+#[tauri::command]
+pub async fn get_active_endpoint(state: State<'_, AppState>) -> Result<Option<Endpoint>, String> {
+    let manager = state.read().await;
+    Ok(manager.active_endpoint().await)
+}
+
+/// Target as typed, the endpoint in use and the address it resolved to (None while not connected)
+#[tauri::command]
+pub async fn get_connection_target(state: State<'_, AppState>) -> Result<Option<ConnectionTarget>, String> {
+    let manager = state.read().await;
+    Ok(manager.connection_target().await)
+}
+
+/// Start connecting in the background and return at once (watch Connecting/ConnectSuccess/ConnectFailed events)
+#[tauri::command]
+pub async fn begin_connect(
+    ip: String,
+    port: u16,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.begin_connect(&ip, port).await?;
+    Ok(format!("Connecting to {}:{}...", ip, port))
+}
+
+/// Controlled power-off sequence: confirmed output stop, all channels off, disconnect
+#[tauri::command]
+pub async fn safe_shutdown(state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.safe_shutdown().await?;
+    Ok("Safe shutdown complete".to_string())
 }
 
+/// Disconnect; `stop_on_disconnect: false` leaves output running for a controller handoff
 #[tauri::command]
-pub async fn disconnect(state: State<'_, AppState>) -> Result<String, String> {
+pub async fn disconnect(
+    stop_on_disconnect: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
     let manager = state.read().await;
-    manager.disconnect().await?;
+    manager.disconnect(stop_on_disconnect.unwrap_or(true)).await?;
     Ok("Disconnected".to_string())
 }
 
+/// Reassign the device's static IP (drops the link, then reconnects to the new address)
+#[tauri::command]
+pub async fn set_device_ip(ip: String, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_device_ip(&ip).await?;
+    Ok(format!("Device IP changed, reconnected to {}", ip))
+}
+
+/// Last-used endpoint (falls back to the default ip/port)
+#[tauri::command]
+pub async fn get_last_endpoint(state: State<'_, AppState>) -> Result<Endpoint, String> {
+    let manager = state.read().await;
+    Ok(manager.last_endpoint())
+}
+
+/// Remember an endpoint as the default for the next app start
+#[tauri::command]
+pub async fn save_last_endpoint(
+    ip: String,
+    port: u16,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.remember_endpoint(&ip, port).await?;
+    Ok(format!("Saved {}:{}", ip, port))
+}
+
+/// Per-phase timing of the last successful connect (None before the first)
+#[tauri::command]
+pub async fn get_last_connect_timing(state: State<'_, AppState>) -> Result<Option<ConnectTiming>, String> {
+    let manager = state.read().await;
+    Ok(manager.last_connect_timing().await)
+}
+
+/// Reconnect attempts/successes for this session, with the success rate
+#[tauri::command]
+pub async fn get_reliability_stats(state: State<'_, AppState>) -> Result<ReliabilityStats, String> {
+    let manager = state.read().await;
+    Ok(manager.reliability_stats().await)
+}
+
+/// Get the retry/backoff settings used by connect
+#[tauri::command]
+pub async fn get_retry_config(state: State<'_, AppState>) -> Result<RetryConfig, String> {
+    let manager = state.read().await;
+    Ok(manager.get_retry_config().await)
+}
+
+/// Set the retry/backoff settings used by subsequent connects
+#[tauri::command]
+pub async fn set_retry_config(
+    max_attempts: u32,
+    initial_delay_ms: u64,
+    max_delay_ms: u64,
+    multiplier: f64,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_retry_config(RetryConfig {
+        max_attempts,
+        initial_delay_ms,
+        max_delay_ms,
+        multiplier,
+    }).await?;
+    Ok("Retry config updated".to_string())
+}
+
 //BROADCAST STATE MACHINE
 
+/// Everything that would stop a broadcast right now (empty list = good to go)
+#[tauri::command]
+pub async fn preflight_check(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let manager = state.read().await;
+    Ok(manager.preflight().await)
+}
+
 /// Arm the system for broadcast
 #[tauri::command]
 pub async fn arm(state: State<'_, AppState>) -> Result<String, String> {
@@ -84,6 +271,29 @@ pub async fn start_broadcast(state: State<'_, AppState>) -> Result<String, Strin
     Ok("Broadcast started".to_string())
 }
 
+/// Stop output automatically at a unix time (StopCountdown events in the last seconds)
+#[tauri::command]
+pub async fn schedule_stop_at(unix_timestamp: u64, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.schedule_stop_at(unix_timestamp).await?;
+    Ok(format!("Broadcast will stop at {}", unix_timestamp))
+}
+
+/// Drop the scheduled stop (false if none was set)
+#[tauri::command]
+pub async fn cancel_scheduled_stop(state: State<'_, AppState>) -> Result<bool, String> {
+    let manager = state.read().await;
+    Ok(manager.cancel_scheduled_stop().await)
+}
+
+/// Restart with the pre-trip channel set once the watchdog has cleared
+#[tauri::command]
+pub async fn resume_after_watchdog(state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.resume_after_watchdog().await?;
+    Ok("Broadcast resumed".to_string())
+}
+
 /// Stop broadcasting
 #[tauri::command]
 pub async fn stop_broadcast(state: State<'_, AppState>) -> Result<String, String> {
@@ -94,9 +304,12 @@ pub async fn stop_broadcast(state: State<'_, AppState>) -> Result<String, String
 
 /// Start emergency broadcast (bypasses arm requirement)
 #[tauri::command]
-pub async fn start_emergency(state: State<'_, AppState>) -> Result<String, String> {
+pub async fn start_emergency(
+    override_license: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
     let manager = state.read().await;
-    manager.start_emergency().await?;
+    manager.start_emergency(override_license.unwrap_or(false)).await?;
     Ok("Emergency broadcast started".to_string())
 }
 
@@ -108,12 +321,152 @@ pub async fn stop_emergency(state: State<'_, AppState>) -> Result<String, String
     Ok("Emergency broadcast stopped".to_string())
 }
 
+/// Run a scripted arm/start/wait/stop/... sequence and report each step
+#[tauri::command]
+pub async fn run_sequence(
+    steps: Vec<SequenceStep>,
+    state: State<'_, AppState>,
+) -> Result<Vec<StepResult>, String> {
+    let manager = state.read().await;
+    Ok(manager.run_sequence(steps).await)
+}
+
+/// Set the minimum time between output on/off changes (emergency stop is exempt)
+#[tauri::command]
+pub async fn set_min_output_interval(ms: u64, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_min_output_interval(ms).await;
+    Ok(format!("Minimum output interval set to {} ms", ms))
+}
+
+/// Set the licensed broadcast hours ("HH:MM" local time); omit both to clear
+#[tauri::command]
+pub async fn set_license_window(
+    start: Option<String>,
+    end: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let manager = state.read().await;
+    match (start, end) {
+        (Some(start), Some(end)) => {
+            let window = LicenseWindow::parse(&start, &end)?;
+            manager.set_license_window(Some(window)).await;
+            Ok(format!("License window {} - {}", start.trim(), end.trim()))
+        }
+        (None, None) => {
+            manager.set_license_window(None).await;
+            Ok("License window cleared".to_string())
+        }
+        _ => Err("Both start and end are required".to_string()),
+    }
+}
+
+/// Preview the combined amplitude of enabled channels against the budget
+#[tauri::command]
+pub async fn check_amplitude_budget(state: State<'_, AppState>) -> Result<AmplitudeBudget, String> {
+    let manager = state.read().await;
+    Ok(manager.amplitude_budget().await)
+}
+
+/// Set the amplitude given to a silent channel when it is enabled
+#[tauri::command]
+pub async fn set_default_amplitude(amplitude: f32, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_default_amplitude(amplitude).await?;
+    Ok(format!("Default amplitude set to {}", amplitude))
+}
+
+/// All live tunables as currently applied (runtime values, not compile-time defaults)
+#[tauri::command]
+pub async fn get_effective_config(state: State<'_, AppState>) -> Result<EffectiveConfig, String> {
+    let manager = state.read().await;
+    Ok(manager.effective_config().await)
+}
+
+/// Set the background poll (watchdog feed) interval
+#[tauri::command]
+pub async fn set_poll_interval(ms: u64, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_poll_interval(ms).await?;
+    Ok(format!("Poll interval set to {} ms", ms))
+}
+
+/// Tell the backend whether the window is visible (hidden = keepalive-only polling)
+#[tauri::command]
+pub async fn set_ui_visible(visible: bool, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_ui_visible(visible).await;
+    Ok(format!("Polling {}", if visible { "at full rate" } else { "keepalive only" }))
+}
+
+/// Set the watchdog reset string sent every poll (e.g. "WDT:KICK" on older firmware)
+#[tauri::command]
+pub async fn set_watchdog_command(command: String, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_watchdog_command(&command).await?;
+    Ok(format!("Watchdog command set to {}", command.trim()))
+}
+
+/// Hold off status-poll overwrites of channels changed in the last `window_ms` (or turn off)
+#[tauri::command]
+pub async fn set_reconcile_guard(enabled: bool, window_ms: Option<u64>, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    let window_ms = window_ms.unwrap_or(Config::DEFAULT_RECONCILE_WINDOW_MS);
+    manager.set_reconcile_guard(enabled, window_ms).await?;
+    if enabled {
+        Ok(format!("Reconcile guard on ({} ms window)", window_ms))
+    } else {
+        Ok("Reconcile guard off".to_string())
+    }
+}
+
+/// Set the over-temperature trip (`high`) and clear (`low`) thresholds in deg C
+#[tauri::command]
+pub async fn set_temperature_thresholds(high: f32, low: f32, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_temperature_thresholds(high, low).await?;
+    Ok(format!("Temperature thresholds set to {:.1}/{:.1} C", high, low))
+}
+
+/// Limit a channel to a frequency sub-range (Hz); omit both to clear, omit one for the band edge
+#[tauri::command]
+pub async fn set_channel_bounds(
+    ch: u8,
+    min: Option<u32>,
+    max: Option<u32>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let manager = state.read().await;
+    if min.is_none() && max.is_none() {
+        manager.set_channel_bounds(ch, None).await?;
+        return Ok(format!("CH{} limits cleared", ch));
+    }
+
+    let bounds = ChannelBounds {
+        min: min.unwrap_or(Config::MIN_FREQUENCY),
+        max: max.unwrap_or(Config::MAX_FREQUENCY),
+    };
+    manager.set_channel_bounds(ch, Some(bounds)).await?;
+    Ok(format!("CH{} limited to {}-{} Hz", ch, bounds.min, bounds.max))
+}
+
+/// Set the maximum combined amplitude allowed at broadcast start
+#[tauri::command]
+pub async fn set_max_total_amplitude(max_total: f32, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_max_total_amplitude(max_total).await?;
+    Ok(format!("Amplitude budget set to {:.2}", max_total))
+}
+
 // CHANNEL CONTROL
 
+/// Update one channel (`verify` reads the settings back from the device)
 #[tauri::command]
 pub async fn update_channel(
     channel_id: u8,
     update: ChannelUpdate,
+    verify: Option<bool>,
+    auto_offset: Option<bool>,
     state: State<'_, AppState>,
 ) -> Result<String, String> {
     let manager = state.read().await;
@@ -126,12 +479,70 @@ pub async fn update_channel(
         .ok_or_else(|| format!("Channel {} not found", channel_id))?;
 
     let enabled = update.enabled.unwrap_or(current.enabled);
-    let frequency = update.frequency.unwrap_or(current.frequency);
+    let frequency = match update.frequency {
+        Some(value) => update.unit.unwrap_or_default().unit_to_hz(value)?,
+        None => current.frequency,
+    };
 
-    manager.set_channel(channel_id, frequency, enabled).await?;
+    let verify = verify.unwrap_or(false);
+    if auto_offset.unwrap_or(false) {
+        let applied = manager.set_channel_auto_offset(channel_id, frequency, enabled, update.amplitude, verify).await?;
+        if applied != frequency {
+            return Ok(format!("Channel {} updated (offset from {} Hz to {} Hz)", channel_id, frequency, applied));
+        }
+    } else {
+        manager.set_channel(channel_id, frequency, enabled, update.amplitude, verify).await?;
+    }
     Ok(format!("Channel {} updated", channel_id))
 }
 
+/// Drop every channel to zero amplitude (previous values kept for unmute)
+#[tauri::command]
+pub async fn mute_all(state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.mute_all().await?;
+    Ok("All channels muted".to_string())
+}
+
+#[tauri::command]
+pub async fn unmute_all(state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.unmute_all().await?;
+    Ok("All channels unmuted".to_string())
+}
+
+/// Carrier-only pilot on a channel (output on at the pilot level); `on: false` ends it
+#[tauri::command]
+pub async fn set_pilot(ch: u8, on: bool, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_pilot(ch, on).await?;
+    Ok(if on { format!("CH{} is a pilot carrier", ch) } else { format!("CH{} pilot ended", ch) })
+}
+
+/// Amplitude used by later set_pilot calls
+#[tauri::command]
+pub async fn set_pilot_amplitude(amplitude: f32, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_pilot_amplitude(amplitude).await?;
+    Ok(format!("Pilot amplitude set to {}", amplitude))
+}
+
+/// Read back the whole channel plan from the device; returns (channel, mismatch) pairs
+#[tauri::command]
+pub async fn verify_channel_plan(state: State<'_, AppState>) -> Result<Vec<(u8, String)>, String> {
+    let manager = state.read().await;
+    manager.verify_plan().await
+}
+
+/// Channel ids `enable_preset_channels` would use for `count` channels
+#[tauri::command]
+pub async fn preset_channels_for(count: u8) -> Result<Vec<u8>, String> {
+    if !(1..=Config::NUM_CHANNELS).contains(&count) {
+        return Err(format!("Preset count {} out of range (1-{})", count, Config::NUM_CHANNELS));
+    }
+    Ok(ChannelPresets::for_count(count))
+}
+
 #[tauri::command]
 pub async fn enable_preset_channels(
     count: u8,
@@ -142,56 +553,732 @@ pub async fn enable_preset_channels(
     Ok(format!("Enabled {} channels", count))
 }
 
-// SOURCE CONTROL
+/// Shift the enabled channel set by `step` slots (wrapping), keeping per-channel frequencies
+#[tauri::command]
+pub async fn rotate_channel_plan(step: u8, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.rotate_plan(step).await?;
+    Ok(format!("Channel plan rotated by {}", step))
+}
 
+/// Step one channel through test frequencies (antenna tuning), then restore it
 #[tauri::command]
-pub async fn set_source(source: String, state: State<'_, AppState>) -> Result<String, String> {
+pub async fn test_tone_sequence(
+    ch: u8,
+    freqs: Vec<u32>,
+    dwell_ms: u64,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
     let manager = state.read().await;
+    let steps = freqs.len();
+    manager.test_tone_sequence(ch, freqs, dwell_ms).await?;
+    Ok(format!("CH{} test tone sequence complete ({} frequencies)", ch, steps))
+}
 
-    let mode = match source.to_uppercase().as_str() {
-        "BRAM" => SourceMode::Bram,
-        "ADC" => SourceMode::Adc,
-        _ => return Err(format!("Invalid source: {}", source)),
-    };
+/// Abort a running test tone sequence (the channel is restored)
+#[tauri::command]
+pub async fn abort_test_tone(state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.abort_test_tone().await;
+    Ok("Test tone sequence abort requested".to_string())
+}
 
-    manager.set_source(mode).await?;
-    Ok(format!("Source set to {}", source))
+/// Cycle output on/off with the current plan (manufacturing burn-in); one result per cycle
+#[tauri::command]
+pub async fn burn_in(
+    on_ms: u64,
+    off_ms: u64,
+    cycles: u32,
+    state: State<'_, AppState>,
+) -> Result<Vec<BurnInCycle>, String> {
+    let manager = state.read().await;
+    manager.burn_in(on_ms, off_ms, cycles).await
 }
 
-// STATE QUERY
+/// Abort a running burn-in (output is switched off)
+#[tauri::command]
+pub async fn abort_burn_in(state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.abort_burn_in().await;
+    Ok("Burn-in abort requested".to_string())
+}
 
+/// Walk a channel's frequency to `target` in `step_hz` steps, `step_ms` apart
 #[tauri::command]
-pub async fn get_state(state: State<'_, AppState>) -> Result<StateResponse, String> {
+pub async fn ramp_frequency(
+    ch: u8,
+    target: u32,
+    step_hz: u32,
+    step_ms: u64,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
     let manager = state.read().await;
-    let device_state = manager.get_state().await;
+    let reached = manager.ramp_frequency(ch, target, step_hz, step_ms).await?;
+    Ok(format!("CH{} ramped to {} Hz", ch, reached))
+}
 
-    let broadcast = &device_state.broadcast;
+/// Abort a running frequency ramp (the channel stays where it got to)
+#[tauri::command]
+pub async fn abort_ramp(state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.abort_ramp().await;
+    Ok("Frequency ramp abort requested".to_string())
+}
 
-    Ok(StateResponse {
-        connection: device_state.connection.display().to_string(),
-        broadcast: broadcast.display().to_string(),
-        watchdog: device_state.watchdog.display().to_string(),
-        source: device_state.source.as_str().to_string(),
+/// Switch to a new channel plan, sending only the channels that differ (no dead air)
+#[tauri::command]
+pub async fn swap_plan(channels: Vec<Channel>, state: State<'_, AppState>) -> Result<Vec<u8>, String> {
+    let manager = state.read().await;
+    manager.swap_plan(channels).await
+}
 
-        channels: device_state
-            .channels
-            .iter()
-            .map(|c| ChannelResponse {
-                id: c.id,
-                enabled: c.enabled,
-                frequency: c.frequency,
-                amplitude: c.amplitude,
-            })
-            .collect(),
+/// Stage a channel edit without sending it (returns the number queued)
+#[tauri::command]
+pub async fn queue_channel_change(change: ChannelChange, state: State<'_, AppState>) -> Result<usize, String> {
+    let manager = state.read().await;
+    manager.queue_channel_change(change).await
+}
 
-        fpga_temperature: device_state.fpga_temperature,
-        error_count: device_state.error_count,
+/// Staged channel edits not yet sent
+#[tauri::command]
+pub async fn get_pending_changes(state: State<'_, AppState>) -> Result<Vec<ChannelChange>, String> {
+    let manager = state.read().await;
+    Ok(manager.pending_changes().await)
+}
 
-        // State machine helpers
-        can_arm: matches!(broadcast, BroadcastState::Idle),
-        can_broadcast: matches!(broadcast, BroadcastState::Armed),
-        can_stop: broadcast.is_broadcasting(),
-        is_emergency: matches!(broadcast, BroadcastState::Emergency),
-        is_transitioning: broadcast.is_transitioning(),
-    })
+/// Send every staged edit in one plan swap
+#[tauri::command]
+pub async fn flush_pending_changes(state: State<'_, AppState>) -> Result<Vec<u8>, String> {
+    let manager = state.read().await;
+    manager.flush_pending_changes().await
+}
+
+/// Drop every staged edit (returns how many were discarded)
+#[tauri::command]
+pub async fn clear_pending_changes(state: State<'_, AppState>) -> Result<usize, String> {
+    let manager = state.read().await;
+    Ok(manager.clear_pending_changes().await)
+}
+
+/// Enabled channels and frequencies as a short code for sharing (e.g. over chat)
+#[tauri::command]
+pub async fn export_plan_code(state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    Ok(manager.export_plan_code().await)
+}
+
+/// Apply a plan code from export_plan_code (channels not in the code are disabled)
+#[tauri::command]
+pub async fn import_plan_code(code: String, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    let plan = manager.import_plan_code(&code).await?;
+    Ok(format!("Plan applied: {} channels enabled", plan.len()))
+}
+
+/// Import a channel plan from CSV rows "id,frequency,enabled,amplitude,phase"
+#[tauri::command]
+pub async fn import_channels_csv(path: String, state: State<'_, AppState>) -> Result<CsvImportReport, String> {
+    let manager = state.read().await;
+    manager.import_channels_csv(Path::new(&path)).await
+}
+
+// STATIONS
+
+/// Load the call-sign table used by tune_by_station ({"CALLSIGN": frequency_hz, ...})
+#[tauri::command]
+pub async fn load_stations(path: String, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    let table = stations::load(Path::new(&path))?;
+    let count = table.len();
+    manager.set_stations(table).await;
+    Ok(format!("Loaded {} stations", count))
+}
+
+/// Known stations as (call sign, frequency Hz), sorted by frequency
+#[tauri::command]
+pub async fn list_stations(state: State<'_, AppState>) -> Result<Vec<(String, u32)>, String> {
+    let manager = state.read().await;
+    Ok(manager.list_stations().await)
+}
+
+/// Tune a channel to a station from the call-sign table
+#[tauri::command]
+pub async fn tune_by_station(
+    channel_id: u8,
+    call_sign: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let manager = state.read().await;
+    let freq = manager.tune_by_station(channel_id, &call_sign).await?;
+    Ok(format!("Channel {} tuned to {} ({} Hz)", channel_id, call_sign.trim(), freq))
+}
+
+// SCENES
+
+/// Save the current channel plan and source to a scene file
+#[tauri::command]
+pub async fn save_scene(
+    name: String,
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let manager = state.read().await;
+    let scene = manager.capture_scene(&name).await;
+    scene::save(Path::new(&path), &scene)?;
+    Ok(format!("Scene '{}' saved", name))
+}
+
+/// Load a scene file and apply it to the device
+#[tauri::command]
+pub async fn load_scene(path: String, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    let scene = scene::load(Path::new(&path))?;
+    manager.apply_scene(&scene).await?;
+    Ok(format!("Scene '{}' applied", scene.name))
+}
+
+/// List what loading a scene file would change, without applying it
+#[tauri::command]
+pub async fn preview_scene(path: String, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let manager = state.read().await;
+    let scene = scene::load(Path::new(&path))?;
+    Ok(manager.diff_scene(&scene).await)
+}
+
+// SOURCE CONTROL
+
+#[tauri::command]
+pub async fn set_source(source: String, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+
+    let mode = match source.to_uppercase().as_str() {
+        "BRAM" => SourceMode::Bram,
+        "ADC" => SourceMode::Adc,
+        _ => return Err(format!("Invalid source: {}", source)),
+    };
+
+    manager.set_source(mode).await?;
+    Ok(format!("Source set to {}", source))
+}
+
+/// Switch source mid-broadcast, optionally ramping amplitude around the change
+#[tauri::command]
+pub async fn switch_source_live(
+    source: String,
+    ramp: bool,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let manager = state.read().await;
+
+    let mode = match source.to_uppercase().as_str() {
+        "BRAM" => SourceMode::Bram,
+        "ADC" => SourceMode::Adc,
+        _ => return Err(format!("Invalid source: {}", source)),
+    };
+
+    manager.switch_source_live(mode, ramp).await?;
+    Ok(format!("Source switched to {}", source))
+}
+
+/// List physical audio inputs available in ADC mode
+#[tauri::command]
+pub async fn list_audio_inputs(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let manager = state.read().await;
+    manager.list_audio_inputs().await
+}
+
+/// Read the output load (ohm) from the device
+#[tauri::command]
+pub async fn get_output_load(state: State<'_, AppState>) -> Result<u32, String> {
+    let manager = state.read().await;
+    manager.query_output_load().await
+}
+
+/// Set the output load: 50 ohm or high-Z (1000000)
+#[tauri::command]
+pub async fn set_output_load(ohms: u32, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_output_load(ohms).await?;
+    Ok(format!("Output load set to {} ohm", ohms))
+}
+
+/// Select the physical audio input (ADC mode only)
+#[tauri::command]
+pub async fn set_audio_input(input: String, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_audio_input(&input).await?;
+    Ok(format!("Audio input set to {}", input))
+}
+
+/// Get duration/size metadata for a stored BRAM message
+#[tauri::command]
+pub async fn get_bram_message_info(index: u8, state: State<'_, AppState>) -> Result<BramInfo, String> {
+    let manager = state.read().await;
+    manager.bram_message_info(index).await
+}
+
+/// Preload a BRAM message, wait until it's buffered, then switch to it (output untouched)
+#[tauri::command]
+pub async fn switch_to_bram_message(index: u8, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.switch_to_bram_message(index).await?;
+    Ok(format!("Playing BRAM message {}", index))
+}
+
+// STATE QUERY
+
+/// Check *IDN? starts with the expected identity (guards against the wrong transmitter)
+#[tauri::command]
+pub async fn verify_device_fingerprint(
+    expected_idn: String,
+    disconnect_on_mismatch: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.verify_device_fingerprint(&expected_idn, disconnect_on_mismatch.unwrap_or(true)).await
+}
+
+/// Compare the firmware's protocol version with the one this client expects
+#[tauri::command]
+pub async fn check_protocol_version(state: State<'_, AppState>) -> Result<ProtocolCheck, String> {
+    let manager = state.read().await;
+    Ok(manager.check_protocol_version().await)
+}
+
+/// Number of control clients connected to the device (warns in the audit log if > 1)
+#[tauri::command]
+pub async fn query_active_clients(state: State<'_, AppState>) -> Result<u32, String> {
+    let manager = state.read().await;
+    manager.query_active_clients().await
+}
+
+/// Query the device clock and return its skew from the host in seconds (positive = device ahead)
+#[tauri::command]
+pub async fn get_clock_skew(state: State<'_, AppState>) -> Result<i64, String> {
+    let manager = state.read().await;
+    manager.query_clock_skew().await
+}
+
+/// Read antenna VSWR on a channel (stops output if above the limit while broadcasting)
+#[tauri::command]
+pub async fn get_vswr(ch: u8, state: State<'_, AppState>) -> Result<f32, String> {
+    let manager = state.read().await;
+    manager.read_vswr(ch).await
+}
+
+/// Set the VSWR above which broadcast is stopped
+#[tauri::command]
+pub async fn set_max_vswr(max_vswr: f32, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_max_vswr(max_vswr).await?;
+    Ok(format!("VSWR limit set to {:.2}", max_vswr))
+}
+
+/// Totals since connect: on-air time, start/stop cycles, emergencies, peak temperature and channels
+#[tauri::command]
+pub async fn get_session_stats(state: State<'_, AppState>) -> Result<SessionStats, String> {
+    let manager = state.read().await;
+    Ok(manager.session_stats().await)
+}
+
+/// Why the last broadcast went back to idle (manual, watchdog, over-temp, ...)
+#[tauri::command]
+pub async fn get_last_stop_reason(state: State<'_, AppState>) -> Result<Option<StopReason>, String> {
+    let manager = state.read().await;
+    Ok(manager.last_stop_reason().await)
+}
+
+/// Device serial number parsed from *IDN? at connect
+#[tauri::command]
+pub async fn get_serial(state: State<'_, AppState>) -> Result<Option<String>, String> {
+    let manager = state.read().await;
+    Ok(manager.serial().await)
+}
+
+#[tauri::command]
+pub async fn get_state(
+    unit: Option<FrequencyUnit>,
+    state: State<'_, AppState>,
+) -> Result<StateResponse, String> {
+    let manager = state.read().await;
+    let device_state = manager.get_state().await;
+    let unit = match unit {
+        Some(unit) => unit,
+        None => manager.frequency_unit().await,
+    };
+
+    let broadcast = &device_state.broadcast;
+    let displayed = if manager.show_transitions().await { *broadcast } else { broadcast.settled() };
+    let temperature = manager.temperature().await;
+
+    Ok(StateResponse {
+        connection: device_state.connection.display().to_string(),
+        broadcast: displayed.display().to_string(),
+        watchdog: device_state.watchdog.display().to_string(),
+        source: device_state.source.as_str().to_string(),
+
+        channels: device_state.channels.iter().map(|c| ChannelResponse::with_unit(c, unit)).collect(),
+
+        fpga_temperature: device_state.fpga_temperature,
+        temp_c: temperature.map(|t| t.temp_c),
+        temp_f: temperature.map(|t| t.temp_f),
+        temperature_unit: manager.temperature_unit().await,
+        error_count: device_state.error_count,
+        muted: device_state.muted,
+        frequency_unit: unit,
+        status_age_secs: manager.status_age_secs().await,
+
+        // State machine helpers
+        can_arm: matches!(broadcast, BroadcastState::Idle),
+        can_broadcast: matches!(broadcast, BroadcastState::Armed),
+        can_stop: broadcast.is_broadcasting(),
+        is_emergency: matches!(broadcast, BroadcastState::Emergency),
+        is_transitioning: broadcast.is_transitioning(),
+    })
+}
+
+/// Broadcast state for display (collapsed to the settled target when transitions are hidden)
+#[tauri::command]
+pub async fn get_broadcast_state(state: State<'_, AppState>) -> Result<BroadcastState, String> {
+    let manager = state.read().await;
+    Ok(manager.displayed_broadcast().await)
+}
+
+/// Whether the UI sees ARMING.../STARTING.../STOPPING... or only settled states
+#[tauri::command]
+pub async fn set_show_transitions(show: bool, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_show_transitions(show).await;
+    Ok(if show { "Showing transitional states" } else { "Showing settled states only" }.to_string())
+}
+
+/// Seconds since the last successful status poll (None if never received)
+#[tauri::command]
+pub async fn get_status_age(state: State<'_, AppState>) -> Result<Option<u64>, String> {
+    let manager = state.read().await;
+    Ok(manager.status_age_secs().await)
+}
+
+/// Complete device state as serialized by the backend (get_state is the trimmed UI view)
+#[tauri::command]
+pub async fn get_full_state(state: State<'_, AppState>) -> Result<DeviceState, String> {
+    let manager = state.read().await;
+    Ok(manager.get_state().await)
+}
+
+/// One-line status summary for headless monitors and status bars
+#[tauri::command]
+pub async fn get_status_line(state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    Ok(manager.status_line().await)
+}
+
+/// Whether any channel is enabled, and how many (cached state, no device query)
+#[tauri::command]
+pub async fn has_active_channels(state: State<'_, AppState>) -> Result<ActiveChannelsResponse, String> {
+    let manager = state.read().await;
+    Ok(ActiveChannelsResponse {
+        has_active: manager.has_active_channels().await,
+        count: manager.active_channel_count().await,
+    })
+}
+
+/// Channels split into enabled/disabled groups, each sorted by id
+#[tauri::command]
+pub async fn get_channels_grouped(
+    unit: Option<FrequencyUnit>,
+    state: State<'_, AppState>,
+) -> Result<ChannelsGroupedResponse, String> {
+    let manager = state.read().await;
+    let (enabled, disabled) = manager.channels_grouped().await;
+    let unit = match unit {
+        Some(unit) => unit,
+        None => manager.frequency_unit().await,
+    };
+
+    Ok(ChannelsGroupedResponse {
+        enabled: enabled.iter().map(|c| ChannelResponse::with_unit(c, unit)).collect(),
+        disabled: disabled.iter().map(|c| ChannelResponse::with_unit(c, unit)).collect(),
+    })
+}
+
+/// Default unit ("hz" / "khz") for frequencies in query responses; storage stays in Hz
+#[tauri::command]
+pub async fn set_frequency_unit(unit: FrequencyUnit, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_frequency_unit(unit).await;
+    Ok(format!("Frequencies reported in {:?}", unit))
+}
+
+/// FPGA temperature in both C and F (None until the device has reported one)
+#[tauri::command]
+pub async fn get_temperature(state: State<'_, AppState>) -> Result<Option<TemperatureReading>, String> {
+    let manager = state.read().await;
+    Ok(manager.temperature().await)
+}
+
+/// Preferred temperature unit ("celsius" / "fahrenheit") for display; storage stays in C
+#[tauri::command]
+pub async fn set_temperature_unit(unit: TemperatureUnit, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_temperature_unit(unit).await;
+    Ok(format!("Temperatures shown in {:?}", unit))
+}
+
+/// Occupied bandwidth of the enabled channel plan
+#[tauri::command]
+pub async fn get_plan_bandwidth(state: State<'_, AppState>) -> Result<PlanBandwidth, String> {
+    let manager = state.read().await;
+    Ok(manager.plan_bandwidth().await)
+}
+
+/// Channels tuned away from their preset, as (channel, current Hz, preset Hz)
+#[tauri::command]
+pub async fn get_preset_deviations(state: State<'_, AppState>) -> Result<Vec<(u8, u32, u32)>, String> {
+    let manager = state.read().await;
+    Ok(manager.preset_deviations().await)
+}
+
+/// Nearest enabled channel (id, gap Hz) within interference distance of a candidate frequency
+#[tauri::command]
+pub async fn nearest_collision(
+    channel: u8,
+    frequency: u32,
+    state: State<'_, AppState>,
+) -> Result<Option<(u8, u32)>, String> {
+    let manager = state.read().await;
+    Ok(manager.nearest_collision(channel, frequency).await)
+}
+
+// DIAGNOSTICS
+
+/// Run a connectivity diagnostic (each sub-check reported independently)
+#[tauri::command]
+pub async fn run_diagnostics(state: State<'_, AppState>) -> Result<DiagnosticReport, String> {
+    let manager = state.read().await;
+    Ok(manager.run_diagnostics().await)
+}
+
+/// Reset the error count and watchdog trip history after investigating
+#[tauri::command]
+pub async fn acknowledge_errors(state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.acknowledge_errors().await;
+    Ok("Errors acknowledged".to_string())
+}
+
+/// Add an operator note to the audit log (NOTE level)
+#[tauri::command]
+pub async fn add_audit_note(note: String, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.add_note(&note).await?;
+    Ok("Note added".to_string())
+}
+
+/// Audit log entries, optionally only one level (INFO/WARNING/ERROR/NOTE)
+#[tauri::command]
+pub async fn get_audit_log(level: Option<String>, state: State<'_, AppState>) -> Result<Vec<AuditEntry>, String> {
+    let manager = state.read().await;
+    Ok(match level {
+        Some(level) => manager.get_audit_log_level(&level).await,
+        None => manager.get_audit_log().await,
+    })
+}
+
+/// Entry counts per level and the time of the latest error
+#[tauri::command]
+pub async fn audit_summary(state: State<'_, AppState>) -> Result<AuditSummary, String> {
+    let manager = state.read().await;
+    Ok(manager.audit_summary().await)
+}
+
+/// Name the operator; every later audit entry carries it (returns the cleaned name)
+#[tauri::command]
+pub async fn set_operator(name: String, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_operator(&name).await
+}
+
+/// Write the in-memory audit log as CSV (returns the number of entries)
+#[tauri::command]
+pub async fn export_audit_csv(path: String, state: State<'_, AppState>) -> Result<usize, String> {
+    let manager = state.read().await;
+    manager.export_audit_csv(Path::new(&path)).await
+}
+
+/// Also write audit entries to a rotating JSONL file; `preload` reloads its recent entries
+/// (omit `path` to go back to memory only)
+#[tauri::command]
+pub async fn set_audit_persistence(
+    path: Option<String>,
+    max_bytes: Option<u64>,
+    preload: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let manager = state.read().await;
+    let enabled = path.is_some();
+    let loaded = manager.set_audit_persistence(
+        path.map(PathBuf::from),
+        max_bytes.unwrap_or(Config::DEFAULT_AUDIT_FILE_BYTES),
+        preload.unwrap_or(false),
+    ).await?;
+    if enabled {
+        Ok(format!("Audit log persistence enabled ({} entries reloaded)", loaded))
+    } else {
+        Ok("Audit log persistence disabled".to_string())
+    }
+}
+
+/// Append every SCPI command/response to a file until stopped
+#[tauri::command]
+pub async fn start_scpi_file_trace(path: String, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.start_scpi_file_trace(Path::new(&path)).await?;
+    Ok(format!("Tracing SCPI to {}", path))
+}
+
+#[tauri::command]
+pub async fn stop_scpi_file_trace(state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.stop_scpi_file_trace().await?;
+    Ok("SCPI trace stopped".to_string())
+}
+
+/// Per-channel capabilities reported by the device at connect (empty if unsupported)
+#[tauri::command]
+pub async fn get_channel_capabilities(state: State<'_, AppState>) -> Result<Vec<ChannelCapability>, String> {
+    let manager = state.read().await;
+    Ok(manager.get_channel_capabilities().await)
+}
+
+/// Recent command latencies (oldest first) for the UI sparkline
+#[tauri::command]
+pub async fn get_latency_trend(
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<LatencySample>, String> {
+    let manager = state.read().await;
+    Ok(manager.latency_trend(limit.unwrap_or(Config::MAX_LATENCY_SAMPLES)).await)
+}
+
+/// Time rapid *IDN? round-trips (min/max/avg latency, requests/sec)
+#[tauri::command]
+pub async fn bench_link(
+    iterations: u32,
+    force: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<LinkBench, String> {
+    let manager = state.read().await;
+    manager.bench_link(iterations, force.unwrap_or(false)).await
+}
+
+/// Walk the broadcast state machine transitions and report pass/fail per scenario
+#[tauri::command]
+pub async fn run_state_machine_selftest(state: State<'_, AppState>) -> Result<Vec<SelftestResult>, String> {
+    let manager = state.read().await;
+    Ok(manager.run_state_machine_selftest().await)
+}
+
+/// Whether the background poll loop (watchdog feed) is still ticking
+#[tauri::command]
+pub async fn poll_task_healthy(state: State<'_, AppState>) -> Result<bool, String> {
+    let manager = state.read().await;
+    Ok(manager.poll_task_healthy().await)
+}
+
+/// Worst watchdog feed interval over the last minute, against the timeout
+#[tauri::command]
+pub async fn get_watchdog_margin(state: State<'_, AppState>) -> Result<WatchdogMargin, String> {
+    let manager = state.read().await;
+    Ok(manager.watchdog_margin().await)
+}
+
+/// Times (unix ms, oldest first) of recent watchdog trips
+#[tauri::command]
+pub async fn get_watchdog_trigger_history(state: State<'_, AppState>) -> Result<Vec<u64>, String> {
+    let manager = state.read().await;
+    Ok(manager.watchdog_trigger_history().await)
+}
+
+/// Plain console output: ASCII tags only, no emoji (default in release builds)
+#[tauri::command]
+pub async fn set_plain_output(plain: bool) -> Result<String, String> {
+    console::set_plain_output(plain);
+    Ok(format!("Console output {}", if plain { "plain" } else { "decorated" }))
+}
+
+/// Toggle printing of audit entries to the console (in-memory log is unaffected)
+#[tauri::command]
+pub async fn set_console_verbose(verbose: bool, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_console_verbose(verbose).await;
+    Ok(format!("Console output {}", if verbose { "verbose" } else { "quiet" }))
+}
+
+/// Channel-by-channel power-on self test (every channel ends disabled)
+#[tauri::command]
+pub async fn power_on_self_test(state: State<'_, AppState>) -> Result<Vec<PostResult>, String> {
+    let manager = state.read().await;
+    manager.power_on_self_test().await
+}
+
+/// Re-send the last N channel/source settings (e.g. after the device restarted)
+#[tauri::command]
+pub async fn replay_last_commands(count: usize, state: State<'_, AppState>) -> Result<usize, String> {
+    let manager = state.read().await;
+    manager.replay_last_commands(count).await
+}
+
+/// Event bus subscriber counts and lag totals (slow consumers show up as lag)
+#[tauri::command]
+pub async fn get_event_bus_health(state: State<'_, AppState>) -> Result<EventBusHealth, String> {
+    let manager = state.read().await;
+    Ok(manager.event_bus_health())
+}
+
+/// Get the last N events of a specific type (e.g. "WatchdogTriggered"), oldest first
+#[tauri::command]
+pub async fn get_recent_events(
+    event_type: String,
+    limit: usize,
+    state: State<'_, AppState>,
+) -> Result<Vec<EventType>, String> {
+    let manager = state.read().await;
+    Ok(manager.recent_events_of_type(&event_type, limit))
+}
+
+/// Enable/disable test mode (required for deliberately unsafe test helpers)
+#[tauri::command]
+pub async fn set_test_mode(enabled: bool, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.set_test_mode(enabled).await;
+    Ok(format!("Test mode {}", if enabled { "enabled" } else { "disabled" }))
+}
+
+/// Delay every command/query by `ms` to simulate a slow link (test mode only, 0 = off)
+#[tauri::command]
+pub async fn inject_latency(ms: u64, state: State<'_, AppState>) -> Result<String, String> {
+    let manager = state.read().await;
+    manager.inject_latency(ms).await?;
+    Ok(format!("Injected latency: {} ms", ms))
+}
+
+/// Starve the watchdog for `pause_ms` so the device trips (test mode only)
+#[tauri::command]
+pub async fn test_watchdog_trip(pause_ms: u64, state: State<'_, AppState>) -> Result<bool, String> {
+    let manager = state.read().await;
+    manager.test_watchdog_trip(pause_ms).await
+}
+
+/// Emit a recorded event sequence on the bus for UI testing (test mode only)
+#[tauri::command]
+pub async fn replay_events(
+    events: Vec<EventType>,
+    interval_ms: u64,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let manager = state.read().await;
+    let count = events.len();
+    manager.replay_events(events, interval_ms).await?;
+    Ok(format!("Replayed {} events", count))
 }
@@ -12,9 +12,19 @@ impl Config {
     pub const DEFAULT_PORT: u16 = 5000;
     pub const CONNECTION_TIMEOUT_SECS: u64 = 5;
     pub const COMMAND_TIMEOUT_SECS: u64 = 2;
+    pub const READ_BUFFER_BYTES: usize = 4096;  // Socket read buffer, kept for the connection's lifetime
+    pub const SETTINGS_FILE: &'static str = "settings.json";
+    pub const OUTPUT_CONFIRM_TIMEOUT_MS: u64 = 2000;  // Wait for device to confirm output state
+    pub const OUTPUT_CONFIRM_POLL_MS: u64 = 100;
+    pub const MIN_OUTPUT_INTERVAL_MS: u64 = 2000;  // Protects PA from rapid OUTPUT:STATE cycling
+    pub const SOURCE_RAMP_STEPS: u32 = 5;       // Amplitude ramp around a live source switch
+    pub const SOURCE_RAMP_STEP_MS: u64 = 20;
+    pub const IP_CHANGE_SETTLE_MS: u64 = 1000;  // Device network restart after SYSTEM:IP
 
     // POLLING
     pub const POLL_INTERVAL_MS: u64 = 500;  // 500ms = 2Hz polling
+    pub const MIN_POLL_INTERVAL_MS: u64 = 50;
+    pub const HIDDEN_STATUS_INTERVAL_MS: u64 = 5000;  // Status rate while the window is hidden (watchdog stays at full rate)
     pub const WATCHDOG_TIMEOUT_SECS: u64 = 5;
 
     // RECONNECTION
@@ -26,12 +36,89 @@ impl Config {
     pub const MIN_FREQUENCY: u32 = 500_000;    // 500 kHz
     pub const MAX_FREQUENCY: u32 = 1_700_000;  // 1700 kHz
     pub const DEFAULT_FREQUENCY: u32 = 540_000; // 540 kHz
+    pub const DEFAULT_RECONCILE_WINDOW_MS: u64 = 1500;  // Status polls don't overwrite a channel changed this recently
+    pub const MAX_RECONCILE_WINDOW_MS: u64 = 10_000;
+    pub const MIN_CHANNEL_SPACING_HZ: u32 = 20_000;  // Closer than this, adjacent carriers interfere
+    pub const AUTO_OFFSET_GRID_HZ: u32 = 10_000;     // Slots tried when offsetting a colliding channel
+    pub const MAX_AUTO_OFFSET_HZ: u32 = 100_000;     // Furthest an auto-offset may move it
+
+    // TEMPERATURE (FPGA, deg C) - trip at HIGH, clear only below LOW
+    pub const TEMP_HIGH_C: f32 = 70.0;
+    pub const TEMP_LOW_C: f32 = 65.0;
 
     // CHANNELS
     pub const NUM_CHANNELS: u8 = 12;
+    pub const DEFAULT_AMPLITUDE: f32 = 1.0;     // Applied when a silent (0) channel is enabled
+    pub const MAX_TOTAL_AMPLITUDE: f32 = 12.0;  // Sum over enabled channels (default: every channel at full scale)
+    pub const DEFAULT_PILOT_AMPLITUDE: f32 = 0.01;  // Carrier-only level for set_pilot
+    pub const MAX_PILOT_AMPLITUDE: f32 = 0.1;
+
+    // POWER-ON SELF TEST - each channel briefly on at a known frequency and low level
+    pub const POST_FREQUENCY: u32 = 1_000_000;
+    pub const POST_AMPLITUDE: f32 = 0.05;
+
+    // BURN IN - output cycled on/off with the current plan
+    pub const MAX_BURN_IN_CYCLES: u32 = 1000;
+    pub const MAX_BURN_IN_PERIOD_MS: u64 = 3_600_000;  // Longest on or off period
+
+    // BRAM
+    pub const MAX_BRAM_MESSAGES: u8 = 16;  // 4-bit message index in control register
+    pub const BRAM_READY_TIMEOUT_MS: u64 = 2000;  // Preloaded clip must report ready within this
+    pub const BRAM_READY_POLL_MS: u64 = 100;
 
     // AUDIT LOG
     pub const MAX_LOG_ENTRIES: usize = 100;
+    pub const MAX_OPERATOR_NAME_CHARS: usize = 64;
+    pub const MAX_NOTE_LENGTH: usize = 500;  // Operator notes, in characters
+    pub const DEFAULT_AUDIT_FILE_BYTES: u64 = 1_048_576;  // On-disk audit log rotates at this size
+    pub const MIN_AUDIT_FILE_BYTES: u64 = 4096;
+
+    // OUTPUT LOAD - 50 ohm or high-Z (1 Mohm)
+    pub const OUTPUT_LOADS_OHMS: [u32; 2] = [50, 1_000_000];
+
+    // ANTENNA VSWR - above the limit output is stopped (reflected power can damage the front end)
+    pub const DEFAULT_MAX_VSWR: f32 = 3.0;
+
+    // CLOCK SKEW (device vs host) worth a warning - log correlation gets unreliable
+    pub const MAX_CLOCK_SKEW_SECS: i64 = 5;
+
+    // PROTOCOL
+    pub const PROTOCOL_VERSION: &'static str = "1.0";  // Major bump = incompatible firmware
+
+    // WATCHDOG TRIGGER HISTORY
+    pub const MAX_WATCHDOG_TRIGGER_HISTORY: usize = 50;
+
+    // WATCHDOG MARGIN - feed intervals kept for the worst-case report, and the share
+    // of the timeout that counts as a near miss
+    pub const WATCHDOG_MARGIN_WINDOW_SECS: u64 = 60;
+    pub const WATCHDOG_NEAR_MISS_PERCENT: u64 = 80;
+
+    // TEST MODE
+    pub const MAX_INJECTED_LATENCY_MS: u64 = 10_000;
+
+    // LATENCY TREND
+    pub const MAX_LATENCY_SAMPLES: usize = 100;
+
+    // COMMAND REPLAY - mutating commands kept for replay_last_commands
+    pub const MAX_REPLAY_COMMANDS: usize = 100;
+
+    // STEPPED CHANNEL CHANGES (test tone sequence, frequency ramp)
+    pub const MAX_TONE_DWELL_MS: u64 = 60_000;
+    pub const MAX_RAMP_STEP_MS: u64 = 10_000;
+    pub const ABORT_CHECK_MS: u64 = 50;  // Abort latency while waiting between steps
+
+    // SCHEDULED STOP
+    pub const STOP_COUNTDOWN_SECS: u64 = 10;  // StopCountdown emitted once a second from here
+
+    // SCRIPTED SEQUENCES
+    pub const MAX_SEQUENCE_WAIT_MS: u64 = 60_000;
+
+    // LINK BENCHMARK
+    pub const MAX_BENCH_ITERATIONS: u32 = 1000;
+
+    // EVENT HISTORY
+    pub const MAX_EVENT_HISTORY: usize = 200;
+    pub const MAX_PENDING_CRITICAL_EVENTS: usize = 32;  // Held for the first subscriber (e.g. UI not attached yet)
 }
 
 /// SCPI Commands - matches FPGA firmware protocol
@@ -42,6 +129,7 @@ impl ScpiCommands {
     pub const IDENTITY: &'static str = "*IDN?";
     pub const RESET: &'static str = "*RST";
     pub const STATUS: &'static str = "STATUS?";
+    pub const SYSTEM_IP: &'static str = "SYSTEM:IP";  // SYSTEM:IP 192.168.0.101
 
     // WATCHDOG (CRITICAL FOR SAFETY)
     pub const WATCHDOG_RESET: &'static str = "WATCHDOG:RESET";
@@ -53,10 +141,14 @@ impl ScpiCommands {
     pub const OUTPUT_OFF: &'static str = "OUTPUT:STATE OFF";
     pub const OUTPUT_STATUS: &'static str = "OUTPUT:STATE?";
     pub const OUTPUT_CH_PREFIX: &'static str = "CH";  // OUTPUT:CH1 ON
+    pub const OUTPUT_LOAD: &'static str = "OUTPUT:LOAD";  // OUTPUT:LOAD 50 (not on all firmware)
+    pub const OUTPUT_LOAD_QUERY: &'static str = "OUTPUT:LOAD?";
 
     // FREQUENCY CONTROL
     pub const FREQ_PREFIX: &'static str = "CH";  // FREQ:CH1 540000
     pub const FREQ_QUERY_PREFIX: &'static str = "FREQ:CH";  // FREQ:CH1?
+    pub const OUTPUT_QUERY_PREFIX: &'static str = "OUTPUT:CH";  // OUTPUT:CH1?
+    pub const CAP_QUERY_SUFFIX: &'static str = ":CAP?";  // CH1:CAP? -> OUTPUT,ADC
 
     // AMPLITUDE CONTROL
     pub const AMP_PREFIX: &'static str = "AMP:CH";  // AMP:CH1 0.5
@@ -69,15 +161,24 @@ impl ScpiCommands {
     pub const SOURCE_BRAM: &'static str = "SOURCE:MODE BRAM";
     pub const SOURCE_ADC: &'static str = "SOURCE:MODE ADC";
     pub const SOURCE_STATUS: &'static str = "SOURCE:MODE?";
+    pub const SOURCE_INPUT: &'static str = "SOURCE:INPUT";  // SOURCE:INPUT LINE1 (ADC mode only)
+    pub const SOURCE_INPUT_LIST: &'static str = "SOURCE:INPUT:LIST?";
 
     // BRAM (Pre-recorded audio) CONTROL
     pub const BRAM_SELECT: &'static str = "BRAM:SELECT";  // BRAM:SELECT 0 (message index)
     pub const BRAM_LIST: &'static str = "BRAM:LIST?";
+    pub const BRAM_INFO: &'static str = "BRAM:INFO?";  // BRAM:INFO? 0 -> name,duration_secs,samples
+    pub const BRAM_PRELOAD: &'static str = "BRAM:PRELOAD";  // BRAM:PRELOAD 0 (buffer before selecting)
+    pub const BRAM_READY_QUERY: &'static str = "BRAM:READY?";  // -> 1 once the preloaded clip is buffered
 
     // DIAGNOSTIC COMMANDS
     pub const TEMP_QUERY: &'static str = "SYSTEM:TEMP?";
     pub const UPTIME_QUERY: &'static str = "SYSTEM:UPTIME?";
     pub const ERROR_QUERY: &'static str = "SYSTEM:ERROR?";
+    pub const PROTOCOL_QUERY: &'static str = "SYSTEM:PROTO?";  // -> "1.0"
+    pub const CLIENTS_QUERY: &'static str = "SYSTEM:CLIENTS?";  // -> connected control clients
+    pub const TIME_QUERY: &'static str = "SYSTEM:TIME?";  // -> device UNIX time (seconds)
+    pub const VSWR_QUERY: &'static str = "MEAS:VSWR?";  // MEAS:VSWR? 1 -> "1.35" (newer firmware only)
 }
 
 /// Frequency presets for quick channel setup
@@ -115,6 +216,7 @@ pub struct ChannelPresets;
 
 impl ChannelPresets {
     /// Get channel IDs for a given preset count
+    /// (hand-tuned patterns where they exist, evenly spread otherwise; out of range -> CH1)
     pub fn for_count(count: u8) -> Vec<u8> {
         match count {
             1 => vec![1],
@@ -124,9 +226,18 @@ impl ChannelPresets {
             6 => vec![12, 2, 4, 6, 8, 10],
             8 => vec![12, 1, 3, 4, 6, 7, 9, 10],
             12 => (1..=12).collect(),
+            5..=11 => Self::spread(count),
             _ => vec![1],
         }
     }
+
+    /// `count` channels spanning the whole band with the gaps as equal as possible
+    /// (both band edges used, so the smallest gap is as large as it can be)
+    fn spread(count: u8) -> Vec<u8> {
+        let last = (Config::NUM_CHANNELS - 1) as f32;
+        let step = last / (count - 1) as f32;
+        (0..count).map(|i| 1 + (i as f32 * step).round() as u8).collect()
+    }
 }
 
 #[cfg(test)]
@@ -212,11 +323,29 @@ mod additional_tests {
 
     #[test]
     fn test_invalid_preset_defaults_to_one() {
-        assert_eq!(ChannelPresets::for_count(5).len(), 1);
         assert_eq!(ChannelPresets::for_count(0).len(), 1);
         assert_eq!(ChannelPresets::for_count(99).len(), 1);
     }
 
+    #[test]
+    fn test_uncurated_counts_spread_across_band() {
+        assert_eq!(ChannelPresets::for_count(5), vec![1, 4, 7, 9, 12]);
+        assert_eq!(ChannelPresets::for_count(7), vec![1, 3, 5, 7, 8, 10, 12]);
+        assert_eq!(ChannelPresets::for_count(9), vec![1, 2, 4, 5, 7, 8, 9, 11, 12]);
+        assert_eq!(ChannelPresets::for_count(10).len(), 10);
+        assert_eq!(ChannelPresets::for_count(11).len(), 11);
+
+        for count in [5, 7, 9, 10, 11] {
+            let channels = ChannelPresets::for_count(count);
+            assert!(channels.windows(2).all(|w| w[0] < w[1]), "Preset {} not strictly increasing", count);
+            assert_eq!((channels[0], channels[channels.len() - 1]), (1, 12));
+
+            // No gap smaller than an even split allows
+            let min_gap = 11 / (count - 1);
+            assert!(channels.windows(2).all(|w| w[1] - w[0] >= min_gap), "Preset {}: {:?}", count, channels);
+        }
+    }
+
     #[test]
     fn test_polling_faster_than_watchdog() {
         assert!(Config::POLL_INTERVAL_MS < Config::WATCHDOG_TIMEOUT_SECS * 1000,
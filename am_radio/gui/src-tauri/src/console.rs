@@ -0,0 +1,108 @@
+#![allow(dead_code)]
+// console.rs
+// Every console line goes through here. Plain mode (default in release builds) uses
+// ASCII tags only, for terminals and log collectors that mangle emoji.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static PLAIN_OUTPUT: AtomicBool = AtomicBool::new(!cfg!(debug_assertions));
+
+/// What a console line is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogTag {
+    Event,
+    Broadcast,
+    Reconnect,
+    Audit,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogTag {
+    /// From an audit level ("INFO", "WARNING", "ERROR", "NOTE")
+    pub fn from_level(level: &str) -> Self {
+        match level {
+            "WARNING" => LogTag::Warn,
+            "ERROR" => LogTag::Error,
+            _ => LogTag::Info,
+        }
+    }
+
+    pub fn ascii(&self) -> &'static str {
+        match self {
+            LogTag::Event => "[EVENT]",
+            LogTag::Broadcast => "[BROADCAST]",
+            LogTag::Reconnect => "[RECONNECT]",
+            LogTag::Audit => "[AUDIT]",
+            LogTag::Info => "[INFO]",
+            LogTag::Warn => "[WARN]",
+            LogTag::Error => "[ERROR]",
+        }
+    }
+
+    fn decorated(&self) -> &'static str {
+        match self {
+            LogTag::Broadcast => "📡",
+            LogTag::Warn => "⚠️ ",
+            LogTag::Error => "❌",
+            other => other.ascii(),
+        }
+    }
+}
+
+pub fn set_plain_output(plain: bool) {
+    PLAIN_OUTPUT.store(plain, Ordering::Relaxed);
+}
+
+pub fn is_plain_output() -> bool {
+    PLAIN_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// The line as it would be printed (non-ASCII dropped in plain mode)
+pub fn format_log_line(tag: LogTag, message: &str) -> String {
+    format_line(tag, message, is_plain_output())
+}
+
+fn format_line(tag: LogTag, message: &str, plain: bool) -> String {
+    if plain {
+        let message: String = message.chars().filter(|c| c.is_ascii()).collect();
+        format!("{} {}", tag.ascii(), message.trim())
+    } else {
+        format!("{} {}", tag.decorated(), message)
+    }
+}
+
+/// Print one console line (warnings and errors to stderr)
+pub fn emit_log_line(tag: LogTag, message: &str) {
+    let line = format_log_line(tag, message);
+    match tag {
+        LogTag::Warn | LogTag::Error => eprintln!("{}", line),
+        _ => println!("{}", line),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_mode_is_ascii_only() {
+        // Pure formatter - the global flag is left alone so parallel tests aren't affected
+        for tag in [LogTag::Event, LogTag::Broadcast, LogTag::Reconnect, LogTag::Audit, LogTag::Info, LogTag::Warn, LogTag::Error] {
+            let line = format_line(tag, "⚠️  WATCHDOG TRIGGERED at 45.2°C 📡", true);
+            assert!(line.is_ascii(), "{}", line);
+            assert!(line.starts_with(tag.ascii()));
+        }
+        assert_eq!(format_line(LogTag::Broadcast, "📡 Broadcast is now LIVE", true), "[BROADCAST] Broadcast is now LIVE");
+
+        assert!(format_line(LogTag::Broadcast, "Broadcast is now LIVE", false).starts_with("📡"));
+    }
+
+    #[test]
+    fn test_level_tags() {
+        assert_eq!(LogTag::from_level("WARNING"), LogTag::Warn);
+        assert_eq!(LogTag::from_level("ERROR"), LogTag::Error);
+        assert_eq!(LogTag::from_level("NOTE"), LogTag::Info);
+    }
+}
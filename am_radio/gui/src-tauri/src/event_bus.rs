@@ -2,19 +2,30 @@
 // event_bus.rs - FULL EVENT SYSTEM
 // Pub/sub pattern using tokio broadcast channels
 
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use tokio::sync::broadcast;
 
+use crate::config::Config;
+use crate::console::{emit_log_line, LogTag};
 use crate::state_machine::{ConnectionState, SourceMode};
 
 // EVENT TYPES
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
 pub enum EventType {
     // CONNECTION EVENTS
+    Connecting,
     ConnectSuccess,
     ConnectFailed(String),
     Disconnected,
     ConnectionLost,
     ConnectionStateChanged(ConnectionState),
+    DeviceRebooted,  // Reported uptime went backwards
 
     // RECONNECTION EVENTS
     ReconnectAttempt(u8),  // Attempt number
@@ -24,6 +35,7 @@ pub enum EventType {
     // BROADCAST EVENTS
     BroadcastStarted,
     BroadcastStopped,
+    StopCountdown(u64),  // Seconds until a scheduled stop
 
     // WATCHDOG EVENTS (CRITICAL FOR SAFETY)
     WatchdogOk,
@@ -31,6 +43,10 @@ pub enum EventType {
     WatchdogTriggered,  // FPGA killed output - this is serious!
     WatchdogReset,
 
+    // TEMPERATURE EVENTS (with hysteresis - one per crossing)
+    OverTemperature(f32),
+    TemperatureNormal(f32),
+
     // CHANNEL EVENTS
     ChannelUpdated(u8),      // Channel ID
     ChannelEnabled(u8),
@@ -46,45 +62,218 @@ pub enum EventType {
     // ERROR EVENTS
     CommandFailed(String),
     NetworkError(String),
+    DeviceFault(String),  // Device-reported FAULT/ERROR from status
+}
+
+impl EventType {
+    /// Frequent status updates - carried on the low-priority channel so they
+    /// can never push safety/connection events out of a slow subscriber's queue
+    pub fn is_status(&self) -> bool {
+        matches!(
+            self,
+            EventType::DeviceStateUpdated
+                | EventType::WatchdogOk
+                | EventType::ChannelUpdated(_)
+                | EventType::ChannelEnabled(_)
+                | EventType::ChannelDisabled(_)
+                | EventType::FrequencyChanged(_, _)
+        )
+    }
+
+    /// Safety events that must reach the UI even if it subscribes late
+    pub fn is_critical(&self) -> bool {
+        matches!(
+            self,
+            EventType::WatchdogTriggered | EventType::ConnectionLost | EventType::DeviceFault(_)
+        )
+    }
+
+    /// Serde tag of the event (e.g. "WatchdogTriggered")
+    pub fn type_name(&self) -> String {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|v| v.get("type").and_then(|t| t.as_str()).map(str::to_string))
+            .unwrap_or_default()
+    }
+}
+
+// EVENT BUS HEALTH - Subscriber counts and how often subscriptions fell behind
+// (a rising lag count means a consumer too slow for the event rate)
+#[derive(Clone, Debug, Serialize)]
+pub struct EventBusHealth {
+    pub priority_subscribers: usize,
+    pub status_subscribers: usize,
+    pub lagged_count: u64,     // Lagged errors seen by EventSubscription::recv
+    pub events_skipped: u64,   // Events those lags dropped
+    pub pending_critical: usize,
+}
+
+#[derive(Default)]
+struct LagCounter {
+    occurrences: AtomicU64,
+    skipped: AtomicU64,
 }
 
 // EVENT BUS
+// Two channels: safety/connection events on `priority`, status spam on `status`
+// Critical events emitted with nobody listening are held in `pending` and replayed
+// to the next priority subscriber.
+#[derive(Clone)]
 pub struct EventBus {
-    sender: broadcast::Sender<EventType>,
+    priority: broadcast::Sender<EventType>,
+    status: broadcast::Sender<EventType>,
+    history: Arc<Mutex<VecDeque<EventType>>>,
+    pending: Arc<Mutex<VecDeque<EventType>>>,
+    lag: Arc<LagCounter>,
 }
 
 impl EventBus {
     /// Create new event bus with buffer capacity
     pub fn new() -> Self {
-        let (sender, _) = broadcast::channel(256);
-        Self { sender }
+        let (priority, _) = broadcast::channel(256);
+        let (status, _) = broadcast::channel(256);
+        Self {
+            priority,
+            status,
+            history: Arc::new(Mutex::new(VecDeque::with_capacity(Config::MAX_EVENT_HISTORY))),
+            pending: Arc::new(Mutex::new(VecDeque::new())),
+            lag: Arc::new(LagCounter::default()),
+        }
     }
 
     /// Emit an event to all subscribers
     pub fn emit(&self, event: EventType) {
         // Log significant events
         match &event {
-            EventType::ConnectSuccess => println!("[EVENT] Connect success"),
-            EventType::Disconnected => println!("[EVENT] Disconnected"),
-            EventType::BroadcastStarted => println!("[EVENT] Broadcast started"),
-            EventType::BroadcastStopped => println!("[EVENT] Broadcast stopped"),
-            EventType::WatchdogTriggered => println!("[EVENT] ⚠️  WATCHDOG TRIGGERED!"),
-            EventType::ConnectionLost => println!("[EVENT] Connection lost"),
+            EventType::ConnectSuccess => emit_log_line(LogTag::Event, "Connect success"),
+            EventType::Disconnected => emit_log_line(LogTag::Event, "Disconnected"),
+            EventType::BroadcastStarted => emit_log_line(LogTag::Broadcast, "Broadcast started"),
+            EventType::BroadcastStopped => emit_log_line(LogTag::Broadcast, "Broadcast stopped"),
+            EventType::WatchdogTriggered => emit_log_line(LogTag::Warn, "WATCHDOG TRIGGERED!"),
+            EventType::ConnectionLost => emit_log_line(LogTag::Event, "Connection lost"),
             _ => {}
         }
 
+        // Record in bounded history
+        {
+            let mut history = self.history.lock();
+            if history.len() >= Config::MAX_EVENT_HISTORY {
+                history.pop_front();
+            }
+            history.push_back(event.clone());
+        }
+
         // Send to all subscribers (ignore if no subscribers)
-        let _ = self.sender.send(event);
+        if event.is_status() {
+            let _ = self.status.send(event);
+        } else if event.is_critical() {
+            // Checked under the lock so a subscriber can't attach between check and send
+            let mut pending = self.pending.lock();
+            if self.priority.receiver_count() == 0 {
+                if pending.len() >= Config::MAX_PENDING_CRITICAL_EVENTS {
+                    pending.pop_front();
+                }
+                pending.push_back(event);
+            } else {
+                let _ = self.priority.send(event);
+            }
+        } else {
+            let _ = self.priority.send(event);
+        }
+    }
+
+    /// New priority receiver, with any critical events missed while nobody was listening
+    fn attach_priority(&self) -> broadcast::Receiver<EventType> {
+        let mut pending = self.pending.lock();
+        let rx = self.priority.subscribe();
+        for event in pending.drain(..) {
+            let _ = self.priority.send(event);
+        }
+        rx
     }
 
-    /// Subscribe to receive events
-    pub fn subscribe(&self) -> broadcast::Receiver<EventType> {
-        self.sender.subscribe()
+    /// Most recent `limit` events with the given serde tag, oldest first
+    pub fn recent_of_type(&self, type_name: &str, limit: usize) -> Vec<EventType> {
+        let history = self.history.lock();
+        let mut matches: Vec<EventType> = history
+            .iter()
+            .rev()
+            .filter(|e| e.type_name() == type_name)
+            .take(limit)
+            .cloned()
+            .collect();
+        matches.reverse();
+        matches
     }
 
-    /// Get a clone of the sender (for passing to other threads/tasks)
-    pub fn get_sender(&self) -> broadcast::Sender<EventType> {
-        self.sender.clone()
+    /// Subscribe to receive all events (priority events are delivered first)
+    pub fn subscribe(&self) -> EventSubscription {
+        EventSubscription {
+            priority: self.attach_priority(),
+            status: self.status.subscribe(),
+            lag: self.lag.clone(),
+        }
+    }
+
+    /// Subscriber counts and lag totals (lags on raw priority/status receivers aren't seen)
+    pub fn health(&self) -> EventBusHealth {
+        EventBusHealth {
+            priority_subscribers: self.priority.receiver_count(),
+            status_subscribers: self.status.receiver_count(),
+            lagged_count: self.lag.occurrences.load(Ordering::Relaxed),
+            events_skipped: self.lag.skipped.load(Ordering::Relaxed),
+            pending_critical: self.pending.lock().len(),
+        }
+    }
+
+    /// Subscribe to safety/connection events only
+    pub fn subscribe_priority(&self) -> broadcast::Receiver<EventType> {
+        self.attach_priority()
+    }
+
+    /// Subscribe to frequent status updates only
+    pub fn subscribe_status(&self) -> broadcast::Receiver<EventType> {
+        self.status.subscribe()
+    }
+}
+
+// EVENT SUBSCRIPTION
+/// Receives from both channels. Ordering is kept within each channel, not across them.
+/// A `Lagged` error only ever means status updates were skipped unless the
+/// priority queue itself overflowed.
+pub struct EventSubscription {
+    priority: broadcast::Receiver<EventType>,
+    status: broadcast::Receiver<EventType>,
+    lag: Arc<LagCounter>,
+}
+
+impl EventSubscription {
+    pub async fn recv(&mut self) -> Result<EventType, broadcast::error::RecvError> {
+        let mut priority_open = true;
+        let mut status_open = true;
+
+        loop {
+            if !priority_open && !status_open {
+                return Err(broadcast::error::RecvError::Closed);
+            }
+
+            let result = tokio::select! {
+                biased;
+                result = self.priority.recv(), if priority_open => match result {
+                    Err(broadcast::error::RecvError::Closed) => { priority_open = false; continue; }
+                    other => other,
+                },
+                result = self.status.recv(), if status_open => match result {
+                    Err(broadcast::error::RecvError::Closed) => { status_open = false; continue; }
+                    other => other,
+                },
+            };
+            if let Err(broadcast::error::RecvError::Lagged(skipped)) = result {
+                self.lag.occurrences.fetch_add(1, Ordering::Relaxed);
+                self.lag.skipped.fetch_add(skipped, Ordering::Relaxed);
+            }
+            return result;
+        }
     }
 }
 
@@ -96,27 +285,27 @@ impl Default for EventBus {
 
 // EVENT LISTENER EXAMPLE
 /// Example of how to listen for events in a background task
-pub async fn example_event_listener(mut rx: broadcast::Receiver<EventType>) {
+pub async fn example_event_listener(mut rx: EventSubscription) {
     loop {
         match rx.recv().await {
             Ok(event) => {
                 match event {
                     EventType::WatchdogTriggered => {
                         // CRITICAL: Handle watchdog trigger
-                        eprintln!("⚠️  WATCHDOG TRIGGERED - FPGA STOPPED OUTPUT!");
+                        emit_log_line(LogTag::Warn, "WATCHDOG TRIGGERED - FPGA STOPPED OUTPUT!");
                         // In real code: notify UI, log, maybe try to recover
                     }
                     EventType::ConnectionLost => {
-                        eprintln!("Connection lost - attempting reconnect...");
+                        emit_log_line(LogTag::Warn, "Connection lost - attempting reconnect...");
                     }
                     EventType::BroadcastStarted => {
-                        println!("📡 Broadcast is now LIVE");
+                        emit_log_line(LogTag::Broadcast, "Broadcast is now LIVE");
                     }
                     EventType::BroadcastStopped => {
-                        println!("📡 Broadcast stopped");
+                        emit_log_line(LogTag::Broadcast, "Broadcast stopped");
                     }
                     EventType::ChannelUpdated(ch) => {
-                        println!("Channel {} updated", ch);
+                        emit_log_line(LogTag::Event, &format!("Channel {} updated", ch));
                     }
                     EventType::DeviceStateUpdated => {
                         // This fires every 500ms - usually just update UI
@@ -125,10 +314,10 @@ pub async fn example_event_listener(mut rx: broadcast::Receiver<EventType>) {
                 }
             }
             Err(broadcast::error::RecvError::Lagged(n)) => {
-                eprintln!("Event listener lagged by {} events", n);
+                emit_log_line(LogTag::Warn, &format!("Event listener lagged by {} events", n));
             }
             Err(broadcast::error::RecvError::Closed) => {
-                println!("Event bus closed");
+                emit_log_line(LogTag::Event, "Event bus closed");
                 break;
             }
         }
@@ -197,10 +386,115 @@ mod additional_tests {
         assert!(matches!(rx.recv().await.unwrap(), EventType::WatchdogTriggered));
     }
 
+    #[test]
+    fn test_type_name_matches_serde_tag() {
+        assert_eq!(EventType::WatchdogTriggered.type_name(), "WatchdogTriggered");
+        assert_eq!(EventType::ChannelUpdated(4).type_name(), "ChannelUpdated");
+        assert_eq!(EventType::ConnectFailed("x".to_string()).type_name(), "ConnectFailed");
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let bus = EventBus::new();
+        for _ in 0..(Config::MAX_EVENT_HISTORY + 10) {
+            bus.emit(EventType::DeviceStateUpdated);
+        }
+        assert_eq!(bus.recent_of_type("DeviceStateUpdated", usize::MAX).len(), Config::MAX_EVENT_HISTORY);
+    }
+
+    #[tokio::test]
+    async fn test_status_flood_does_not_lag_out_safety_events() {
+        let bus = EventBus::new();
+        let mut rx = bus.subscribe();
+
+        for _ in 0..1000 {
+            bus.emit(EventType::DeviceStateUpdated);
+        }
+        bus.emit(EventType::WatchdogTriggered);
+        for _ in 0..1000 {
+            bus.emit(EventType::DeviceStateUpdated);
+        }
+        bus.emit(EventType::ConnectionLost);
+
+        // Safety events come through intact and ahead of the status backlog
+        assert!(matches!(rx.recv().await.unwrap(), EventType::WatchdogTriggered));
+        assert!(matches!(rx.recv().await.unwrap(), EventType::ConnectionLost));
+
+        // The status channel did overflow - that's the only loss
+        assert!(matches!(rx.recv().await, Err(broadcast::error::RecvError::Lagged(_))));
+        assert!(matches!(rx.recv().await.unwrap(), EventType::DeviceStateUpdated));
+    }
+
+    #[tokio::test]
+    async fn test_priority_only_subscriber_skips_status() {
+        let bus = EventBus::new();
+        let mut rx = bus.subscribe_priority();
+
+        bus.emit(EventType::DeviceStateUpdated);
+        bus.emit(EventType::ChannelUpdated(2));
+        bus.emit(EventType::BroadcastStopped);
+
+        assert!(matches!(rx.recv().await.unwrap(), EventType::BroadcastStopped));
+        assert!(rx.try_recv().is_err());
+    }
+
     #[test]
     fn test_no_subscribers_doesnt_panic() {
         let bus = EventBus::new();
         bus.emit(EventType::ConnectSuccess);
         bus.emit(EventType::WatchdogTriggered);
     }
+
+    #[tokio::test]
+    async fn test_critical_event_replayed_to_late_subscriber() {
+        let bus = EventBus::new();
+
+        // Startup: nobody listening yet
+        bus.emit(EventType::WatchdogTriggered);
+        bus.emit(EventType::DeviceStateUpdated);
+        bus.emit(EventType::BroadcastStopped);  // Not critical - dropped as before
+
+        let mut rx = bus.subscribe();
+        assert!(matches!(rx.recv().await.unwrap(), EventType::WatchdogTriggered));
+
+        // Replayed once only - a second subscriber doesn't see it again
+        let mut later = bus.subscribe_priority();
+        bus.emit(EventType::ConnectionLost);
+        assert!(matches!(later.recv().await.unwrap(), EventType::ConnectionLost));
+        assert!(matches!(rx.recv().await.unwrap(), EventType::ConnectionLost));
+    }
+
+    #[test]
+    fn test_pending_critical_events_bounded() {
+        let bus = EventBus::new();
+        for i in 0..(Config::MAX_PENDING_CRITICAL_EVENTS + 5) {
+            bus.emit(EventType::DeviceFault(format!("F{}", i)));
+        }
+        let mut rx = bus.subscribe_priority();
+        let mut replayed = Vec::new();
+        while let Ok(EventType::DeviceFault(f)) = rx.try_recv() {
+            replayed.push(f);
+        }
+        assert_eq!(replayed.len(), Config::MAX_PENDING_CRITICAL_EVENTS);
+        assert_eq!(replayed[0], "F5");  // Oldest dropped first
+    }
+
+    #[tokio::test]
+    async fn test_health_counts_lag_and_subscribers() {
+        let bus = EventBus::new();
+        let health = bus.health();
+        assert_eq!((health.priority_subscribers, health.status_subscribers, health.lagged_count), (0, 0, 0));
+
+        let mut rx = bus.subscribe();
+        let _priority_only = bus.subscribe_priority();
+        for _ in 0..300 {
+            bus.emit(EventType::DeviceStateUpdated);
+        }
+        assert!(matches!(rx.recv().await, Err(broadcast::error::RecvError::Lagged(44))));
+        assert!(matches!(rx.recv().await.unwrap(), EventType::DeviceStateUpdated));
+
+        let health = bus.health();
+        assert_eq!((health.priority_subscribers, health.status_subscribers), (2, 1));
+        assert_eq!((health.lagged_count, health.events_skipped), (1, 44));
+    }
 }
@@ -3,28 +3,46 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 #![allow(dead_code, unused_imports)]
 
+mod audit_file;
+mod channel_csv;
 mod commands;
 mod config;
+mod console;
 mod event_bus;
 mod model;
+mod plan_code;
 mod state_machine;
 mod retry;
+mod scene;
+mod settings;
+mod stations;
+
+// Simulated device models, so client tests can run against the real mock server
+#[cfg(test)]
+#[path = "mock_server.rs"]
+mod mock_server;
 
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tauri::Manager;
+use tauri::{Manager, WindowEvent};
 
 use commands::AppState;
-use tokio::sync::broadcast;
+use config::Config;
+use event_bus::EventBus;
 use model::NetworkManager;
 
 fn main() {
     tauri::Builder::default()
         .setup(|app| {
             // Create shared event bus
-            let (event_tx, _) = broadcast::channel(100);
+            let event_bus = EventBus::new();
             // Create network manager
-            let manager = NetworkManager::new(event_tx);
+            let mut manager = NetworkManager::new(event_bus);
+
+            // Persist settings (last endpoint etc.) in the app config dir
+            if let Some(dir) = app.path_resolver().app_config_dir() {
+                manager = manager.with_settings_path(dir.join(Config::SETTINGS_FILE));
+            }
 
 
             let app_state: AppState = Arc::new(RwLock::new(manager));
@@ -34,27 +52,156 @@ fn main() {
 
             Ok(())
         })
+        .on_window_event(|event| {
+            // Minimized/hidden window: throttle status polling (watchdog keeps full rate)
+            if matches!(event.event(), WindowEvent::Focused(_) | WindowEvent::Resized(_)) {
+                let window = event.window();
+                let visible = window.is_visible().unwrap_or(true) && !window.is_minimized().unwrap_or(false);
+                let app_state = window.state::<AppState>().inner().clone();
+                tauri::async_runtime::spawn(async move {
+                    app_state.read().await.set_ui_visible(visible).await;
+                });
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             // Connection
             commands::connect,
+            commands::connect_with_failover,
+            commands::begin_connect,
             commands::disconnect,
+            commands::safe_shutdown,
+            commands::set_device_ip,
+            commands::get_active_endpoint,
+            commands::get_connection_target,
+            commands::get_last_endpoint,
+            commands::save_last_endpoint,
+            commands::get_retry_config,
+            commands::set_retry_config,
+            commands::get_reliability_stats,
+            commands::replay_last_commands,
+            commands::get_last_connect_timing,
 
             // Broadcast state machine
+            commands::preflight_check,
             commands::arm,
             commands::start_broadcast,
             commands::stop_broadcast,
+            commands::resume_after_watchdog,
+            commands::schedule_stop_at,
+            commands::cancel_scheduled_stop,
             commands::start_emergency,
             commands::stop_emergency,
+            commands::run_sequence,
+            commands::set_min_output_interval,
+            commands::set_license_window,
+            commands::check_amplitude_budget,
+            commands::set_max_total_amplitude,
+            commands::set_default_amplitude,
 
             // Channel control
             commands::update_channel,
             commands::enable_preset_channels,
+            commands::preset_channels_for,
+            commands::verify_channel_plan,
+            commands::mute_all,
+            commands::unmute_all,
+            commands::set_pilot,
+            commands::set_pilot_amplitude,
+            commands::rotate_channel_plan,
+            commands::test_tone_sequence,
+            commands::abort_test_tone,
+            commands::ramp_frequency,
+            commands::abort_ramp,
+            commands::import_channels_csv,
+            commands::swap_plan,
+            commands::queue_channel_change,
+            commands::get_pending_changes,
+            commands::flush_pending_changes,
+            commands::clear_pending_changes,
+            commands::export_plan_code,
+            commands::import_plan_code,
+
+            // Stations
+            commands::load_stations,
+            commands::list_stations,
+            commands::tune_by_station,
+
+            // Scenes
+            commands::save_scene,
+            commands::load_scene,
+            commands::preview_scene,
 
             // Source control
             commands::set_source,
+            commands::switch_source_live,
+            commands::list_audio_inputs,
+            commands::set_audio_input,
+            commands::get_output_load,
+            commands::set_output_load,
+            commands::get_bram_message_info,
+            commands::switch_to_bram_message,
 
             // State query
             commands::get_state,
+            commands::get_broadcast_state,
+            commands::set_show_transitions,
+            commands::get_full_state,
+            commands::get_status_line,
+            commands::get_status_age,
+            commands::get_last_stop_reason,
+            commands::get_session_stats,
+            commands::get_serial,
+            commands::query_active_clients,
+            commands::get_clock_skew,
+            commands::get_vswr,
+            commands::set_max_vswr,
+            commands::get_channel_capabilities,
+            commands::check_protocol_version,
+            commands::verify_device_fingerprint,
+            commands::has_active_channels,
+            commands::get_channels_grouped,
+            commands::set_frequency_unit,
+            commands::get_temperature,
+            commands::set_temperature_unit,
+            commands::get_plan_bandwidth,
+            commands::get_preset_deviations,
+            commands::nearest_collision,
+
+            // Diagnostics
+            commands::run_diagnostics,
+            commands::power_on_self_test,
+            commands::burn_in,
+            commands::abort_burn_in,
+            commands::poll_task_healthy,
+            commands::run_state_machine_selftest,
+            commands::bench_link,
+            commands::get_latency_trend,
+            commands::get_watchdog_trigger_history,
+            commands::get_watchdog_margin,
+            commands::set_reconcile_guard,
+            commands::set_temperature_thresholds,
+            commands::set_channel_bounds,
+            commands::get_effective_config,
+            commands::set_poll_interval,
+            commands::set_watchdog_command,
+            commands::set_ui_visible,
+            commands::acknowledge_errors,
+            commands::add_audit_note,
+            commands::get_audit_log,
+            commands::audit_summary,
+            commands::set_operator,
+            commands::export_audit_csv,
+            commands::set_audit_persistence,
+            commands::set_console_verbose,
+            commands::set_plain_output,
+            commands::start_scpi_file_trace,
+            commands::stop_scpi_file_trace,
+            commands::get_recent_events,
+            commands::get_event_bus_health,
+            commands::set_test_mode,
+            commands::test_watchdog_trip,
+            commands::inject_latency,
+            commands::replay_events,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
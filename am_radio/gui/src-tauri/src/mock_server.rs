@@ -1,14 +1,61 @@
 //! Mock SCPI Server for Testing
 //!
-//! Run with: cargo run --bin mock-server
-//! Then connect GUI to: 127.0.0.1:5000
+//! Run with: cargo run --bin mock-server [-- --model stemlab-125-14]
+//! (or MOCK_MODEL=stemlab-125-14). Then connect GUI to: 127.0.0.1:5000
+#![allow(dead_code)]
 
 use std::io::{BufRead, BufReader, Write};
 use std::net::{TcpListener, TcpStream};
 
-fn handle_client(mut stream: TcpStream) {
+/// Simulated device model - each has its own *IDN?, status delimiter and optional commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MockModel {
+    /// Current firmware: every optional query answered, comma-separated status
+    Stemlab125_10,
+    /// Older 14-bit board: semicolon-separated status, no SYSTEM:PROTO?/TIME?/CLIENTS?,
+    /// MEAS:VSWR?, OUTPUT:LOAD?, BRAM:READY? or CH{n}:CAP? (unsupported queries get no reply)
+    Stemlab125_14,
+}
+
+impl MockModel {
+    pub const NAMES: [&'static str; 2] = ["stemlab-125-10", "stemlab-125-14"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "stemlab-125-10" => Some(MockModel::Stemlab125_10),
+            "stemlab-125-14" => Some(MockModel::Stemlab125_14),
+            _ => None,
+        }
+    }
+
+    pub fn idn(&self) -> &'static str {
+        match self {
+            MockModel::Stemlab125_10 => "RedPitaya,STEMlab125-10,MOCK,v1.0",
+            MockModel::Stemlab125_14 => "RedPitaya,STEMlab125-14,MOCK14,v1.1",
+        }
+    }
+
+    pub fn status_delimiter(&self) -> &'static str {
+        match self {
+            MockModel::Stemlab125_10 => ",",
+            MockModel::Stemlab125_14 => ";",
+        }
+    }
+
+    pub fn supports(&self, query: &str) -> bool {
+        match self {
+            MockModel::Stemlab125_10 => true,
+            MockModel::Stemlab125_14 => !(matches!(query,
+                    "SYSTEM:PROTO?" | "SYSTEM:TIME?" | "SYSTEM:CLIENTS?" | "OUTPUT:LOAD?" | "BRAM:READY?")
+                || query.starts_with("MEAS:VSWR?")
+                || query.ends_with(":CAP?")),
+        }
+    }
+}
+
+fn handle_client(mut stream: TcpStream, model: MockModel) {
     let addr = stream.peer_addr().unwrap();
-    println!("[CONNECTED] {}", addr);
+    println!("[CONNECTED] {} ({:?})", addr, model);
     println!("{}", "-".repeat(50));
 
     let reader = BufReader::new(stream.try_clone().unwrap());
@@ -30,36 +77,99 @@ fn handle_client(mut stream: TcpStream) {
                 println!("[RX] {}", data);
 
                 // Handle queries
-                if data == "*IDN?" {
-                    let response = "RedPitaya,STEMlab125-10,MOCK,v1.0\n";
+                if !model.supports(data) {
+                    println!("     -> Not supported by {:?} (no reply)", model);
+                } else if data == "*IDN?" {
+                    let response = format!("{}\n", model.idn());
                     stream.write_all(response.as_bytes()).unwrap();
                     println!("[TX] {}", response.trim());
+                } else if data == "SYSTEM:PROTO?" {
+                    stream.write_all(b"1.0\n").unwrap();
+                    println!("[TX] 1.0");
+                } else if data == "SYSTEM:CLIENTS?" {
+                    stream.write_all(b"1\n").unwrap();
+                    println!("[TX] 1");
+                } else if data == "SYSTEM:TIME?" {
+                    let now = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap()
+                        .as_secs();
+                    stream.write_all(format!("{}\n", now).as_bytes()).unwrap();
+                    println!("[TX] {}", now);
+                } else if data == "OUTPUT:LOAD?" {
+                    stream.write_all(b"50\n").unwrap();
+                    println!("[TX] 50");
+                } else if data == "BRAM:READY?" {
+                    // Preloads complete instantly here
+                    stream.write_all(b"1\n").unwrap();
+                    println!("[TX] 1");
+                } else if data.starts_with("MEAS:VSWR?") {
+                    stream.write_all(b"1.20\n").unwrap();
+                    println!("[TX] 1.20");
                 } else if data == "STATUS?" {
-                    // Build status response
+                    // Build status response ("BROADCAST:1,WATCHDOG:0,...,CH1:ON,...")
                     let mut parts = vec![
-                        format!("broadcasting={}", if broadcasting { "1" } else { "0" }),
-                        format!("source={}", source),
-                        "watchdog_triggered=0".to_string(),
-                        "watchdog_warning=0".to_string(),
-                        "watchdog_time=5".to_string(),
+                        format!("BROADCAST:{}", if broadcasting { "1" } else { "0" }),
+                        format!("SOURCE:{}", source),
+                        "WATCHDOG:0".to_string(),
+                        "TEMP:41.5".to_string(),
                     ];
-                    
-                    for (i, (enabled, freq)) in channels_enabled.iter().zip(channels_freq.iter()).enumerate() {
-                        parts.push(format!("ch{}_enabled={}", i + 1, if *enabled { "1" } else { "0" }));
-                        parts.push(format!("ch{}_freq={}", i + 1, freq));
+
+                    for (i, enabled) in channels_enabled.iter().enumerate() {
+                        parts.push(format!("CH{}:{}", i + 1, if *enabled { "ON" } else { "OFF" }));
                     }
-                    
-                    let response = format!("{}\n", parts.join(","));
+
+                    let response = format!("{}\n", parts.join(model.status_delimiter()));
                     stream.write_all(response.as_bytes()).unwrap();
                     println!("[TX] STATUS (truncated)");
                 } else if data == "WATCHDOG:RESET" {
                     println!("     -> Watchdog reset");
+                } else if data.starts_with("SYSTEM:IP ") {
+                    let ip = data.replace("SYSTEM:IP ", "");
+                    println!("     -> Static IP set to: {} (mock keeps listening here)", ip);
+                } else if data == "SOURCE:INPUT:LIST?" {
+                    let response = "LINE1,LINE2,MIC\n";
+                    stream.write_all(response.as_bytes()).unwrap();
+                    println!("[TX] {}", response.trim());
                 } else if data.starts_with("SOURCE:INPUT ") {
-                    source = data.replace("SOURCE:INPUT ", "");
+                    let input = data.replace("SOURCE:INPUT ", "");
+                    println!("     -> Audio input set to: {}", input);
+                } else if data.starts_with("SOURCE:MODE ") {
+                    source = data.replace("SOURCE:MODE ", "");
                     println!("     -> Audio source set to: {}", source);
                 } else if data.starts_with("SOURCE:MSG ") {
                     let msg = data.replace("SOURCE:MSG ", "");
                     println!("     -> Message selected: #{}", msg);
+                } else if let Some(ch) = data.strip_prefix("FREQ:CH").and_then(|d| d.strip_suffix('?')) {
+                    if let Some(freq) = ch.parse::<usize>().ok().filter(|c| (1..=12).contains(c)).map(|c| channels_freq[c - 1]) {
+                        stream.write_all(format!("{}\n", freq).as_bytes()).unwrap();
+                        println!("[TX] {}", freq);
+                    }
+                } else if let Some(ch) = data.strip_prefix("CH").and_then(|d| d.strip_suffix(":CAP?")) {
+                    // Every mock channel can output and take ADC audio
+                    if ch.parse::<usize>().ok().filter(|c| (1..=12).contains(c)).is_some() {
+                        stream.write_all(b"OUTPUT,ADC\n").unwrap();
+                        println!("[TX] OUTPUT,ADC");
+                    }
+                } else if let Some(ch) = data.strip_prefix("OUTPUT:CH").and_then(|d| d.strip_suffix('?')) {
+                    if let Some(enabled) = ch.parse::<usize>().ok().filter(|c| (1..=12).contains(c)).map(|c| channels_enabled[c - 1]) {
+                        let response = if enabled { "ON" } else { "OFF" };
+                        stream.write_all(format!("{}\n", response).as_bytes()).unwrap();
+                        println!("[TX] {}", response);
+                    }
+                } else if data.starts_with("CH") && data.contains(":FREQ ") {
+                    // Parse CH1:FREQ 540000
+                    let data = data.replace(":FREQ", "");
+                    let parts: Vec<&str> = data.split_whitespace().collect();
+                    if parts.len() >= 2 {
+                        let ch_str = parts[0].replace("CH", "");
+                        if let (Ok(ch), Ok(freq)) = (ch_str.parse::<usize>(), parts[1].parse::<u32>()) {
+                            if (1..=12).contains(&ch) {
+                                channels_freq[ch - 1] = freq;
+                                println!("     -> CH{} frequency: {} Hz ({:.0} kHz)", ch, freq, freq as f64 / 1000.0);
+                            }
+                        }
+                    }
                 } else if data.starts_with("FREQ:CH") {
                     // Parse FREQ:CH1 540000
                     let parts: Vec<&str> = data.split_whitespace().collect();
@@ -86,6 +196,10 @@ fn handle_client(mut stream: TcpStream) {
                             }
                         }
                     }
+                } else if data == "OUTPUT:STATE?" {
+                    let response = format!("{}\n", if broadcasting { "1" } else { "0" });
+                    stream.write_all(response.as_bytes()).unwrap();
+                    println!("[TX] {}", response.trim());
                 } else if data.starts_with("OUTPUT:STATE ") {
                     let state = data.replace("OUTPUT:STATE ", "");
                     broadcasting = state == "ON";
@@ -107,25 +221,52 @@ fn handle_client(mut stream: TcpStream) {
     println!();
 }
 
+/// Accept clients on `listener` forever, one thread each
+pub fn serve(listener: TcpListener, model: MockModel) {
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                std::thread::spawn(move || handle_client(stream, model));
+            }
+            Err(e) => {
+                eprintln!("Error accepting connection: {}", e);
+            }
+        }
+    }
+}
+
+/// `--model <name>` on the command line, else MOCK_MODEL, else the current firmware
+fn model_from_args() -> Result<MockModel, String> {
+    let args: Vec<String> = std::env::args().collect();
+    let name = match args.iter().position(|a| a == "--model") {
+        Some(i) => args.get(i + 1).cloned().ok_or("--model needs a value")?,
+        None => match std::env::var("MOCK_MODEL") {
+            Ok(name) => name,
+            Err(_) => return Ok(MockModel::Stemlab125_10),
+        },
+    };
+    MockModel::from_name(&name)
+        .ok_or_else(|| format!("Unknown model '{}' (known: {})", name, MockModel::NAMES.join(", ")))
+}
+
 fn main() {
+    let model = match model_from_args() {
+        Ok(model) => model,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
     let listener = TcpListener::bind("0.0.0.0:5000").expect("Failed to bind to port 5000");
 
     println!("{}", "=".repeat(50));
     println!("MOCK SCPI SERVER (Rust)");
+    println!("Model: {} ({})", model.idn(), model.status_delimiter());
     println!("{}", "=".repeat(50));
     println!("Listening on port 5000...");
     println!("Connect GUI to: 127.0.0.1:5000");
     println!("{}", "=".repeat(50));
     println!();
 
-    for stream in listener.incoming() {
-        match stream {
-            Ok(stream) => {
-                std::thread::spawn(|| handle_client(stream));
-            }
-            Err(e) => {
-                eprintln!("Error accepting connection: {}", e);
-            }
-        }
-    }
+    serve(listener, model);
 }
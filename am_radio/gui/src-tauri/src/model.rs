@@ -2,7 +2,8 @@
 
 // model.rs - FULL PRODUCTION VERSION
 // Complete NetworkManager with all features from Python
-use crate::state_machine::{BroadcastState, ConnectionState, WatchdogState, SourceMode};
+use crate::state_machine::{self, BroadcastState, ConnectionState, SelftestResult, StopReason, WatchdogState, SourceMode};
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use tokio::io::AsyncReadExt;
 use std::time::Duration;
@@ -11,10 +12,26 @@ use tokio::net::TcpStream;
 use tokio::sync::{broadcast, RwLock};
 use tokio::time::{timeout, sleep, Instant};
 use serde::{Deserialize, Serialize};
+use chrono::NaiveTime;
 use crate::retry::{RetryConfig, RetryResult, with_retry};
 
-use crate::config::{Config, ScpiCommands};
-use crate::event_bus::EventType;
+use crate::audit_file::{self, AuditPersistence};
+use crate::channel_csv::{self, CsvImportReport, CsvRowResult};
+use crate::config::{ChannelPresets, Config, FrequencyPresets, ScpiCommands};
+use crate::console::{self, emit_log_line, LogTag};
+use crate::event_bus::{EventBus, EventBusHealth, EventType};
+use crate::plan_code;
+use crate::scene::Scene;
+use crate::settings::{Endpoint, Settings};
+use crate::stations;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::BufWriter;
+
+// The device socket with a read buffer that lives as long as the connection, so bytes
+// read ahead of one response are still there for the next (writes pass straight through)
+type DeviceStream = BufReader<TcpStream>;
 
 
 // CHANNEL STRUCT
@@ -25,9 +42,11 @@ pub struct Channel {
     pub frequency: u32,      // Hz (540000 = 540 kHz)
     pub amplitude: f32,      // 0.0 - 1.0
     pub phase: f32,          // degrees
+    #[serde(default)]
+    pub pilot: bool,         // Carrier-only at the pilot level (set_pilot), not program audio
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ChannelChange {
     pub channel_id: u8,
     pub frequency: Option<u32>,
@@ -42,7 +61,98 @@ impl Channel {
             frequency: 505_000,  // Default 505 kHz
             amplitude: 1.0,
             phase: 0.0,
+            pilot: false,
+        }
+    }
+}
+
+// FREQUENCY UNIT - display/entry unit at the command boundary (storage is always Hz)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrequencyUnit {
+    #[default]
+    Hz,
+    Khz,
+}
+
+impl FrequencyUnit {
+    pub fn hz_to_unit(self, hz: u32) -> f64 {
+        match self {
+            FrequencyUnit::Hz => hz as f64,
+            FrequencyUnit::Khz => hz as f64 / 1000.0,
+        }
+    }
+
+    /// Rounded to the nearest Hz (e.g. 540.5 kHz -> 540500)
+    pub fn unit_to_hz(self, value: f64) -> Result<u32, String> {
+        let hz = match self {
+            FrequencyUnit::Hz => value,
+            FrequencyUnit::Khz => value * 1000.0,
+        }.round();
+        if !hz.is_finite() || hz < 0.0 || hz > u32::MAX as f64 {
+            return Err(format!("Invalid frequency: {}", value));
+        }
+        Ok(hz as u32)
+    }
+}
+
+// TEMPERATURE UNIT - display preference (the device reports, and we store, Celsius)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+/// Celsius to Fahrenheit, rounded to 0.1 degree
+pub fn celsius_to_fahrenheit(celsius: f32) -> f32 {
+    ((celsius as f64 * 9.0 / 5.0 + 32.0) * 10.0).round() as f32 / 10.0
+}
+
+// TEMPERATURE READING - both units, plus the preferred one for display
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct TemperatureReading {
+    pub temp_c: f32,
+    pub temp_f: f32,
+    pub unit: TemperatureUnit,
+}
+
+impl TemperatureReading {
+    pub fn from_celsius(temp_c: f32, unit: TemperatureUnit) -> Self {
+        Self { temp_c, temp_f: celsius_to_fahrenheit(temp_c), unit }
+    }
+}
+
+// SESSION STATS - totals since the last connect, updated at broadcast transitions
+// and on every parsed status
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SessionStats {
+    pub broadcast_ms: u64,  // Completed on-air time plus the current stretch
+    pub broadcast_cycles: u32,
+    pub emergency_activations: u32,
+    pub peak_temperature: Option<f32>,
+    pub peak_active_channels: usize,
+    #[serde(skip)]
+    on_air_since: Option<Instant>,
+}
+
+impl SessionStats {
+    fn observe_active_channels(&mut self, active: usize) {
+        self.peak_active_channels = self.peak_active_channels.max(active);
+    }
+
+    fn observe_temperature(&mut self, temp: f32) {
+        self.peak_temperature = Some(self.peak_temperature.map_or(temp, |peak| peak.max(temp)));
+    }
+
+    /// Copy with the running broadcast (if any) counted up to now
+    pub fn snapshot(&self) -> SessionStats {
+        let mut stats = self.clone();
+        if let Some(since) = self.on_air_since {
+            stats.broadcast_ms += since.elapsed().as_millis() as u64;
         }
+        stats
     }
 }
 
@@ -53,10 +163,34 @@ pub struct DeviceState {
     pub broadcast: BroadcastState,
     pub watchdog: WatchdogState,
     pub source: SourceMode,
+    pub audio_input: Option<String>,  // Physical input selected in ADC mode
+    pub bram_message: Option<u8>,  // Message selected by switch_to_bram_message
     pub channels: Vec<Channel>,
     pub fpga_temperature: Option<f32>,
+    pub over_temperature: bool,  // Latched at the high threshold, cleared below the low one
     pub last_status_time: Option<u64>,
+    pub uptime_secs: Option<u64>,  // UPTIME from status; a drop means the device rebooted
     pub error_count: u32,
+    pub last_fault: Option<String>,  // Most recent FAULT/ERROR reported in status
+    pub fault_active: bool,
+    pub serial: Option<String>,  // From *IDN? during init
+    pub muted: bool,             // All amplitudes at 0, previous values held for unmute
+    pub protocol_version: Option<String>,  // Reported by the device (SYSTEM:PROTO? or *IDN?)
+    pub expected_protocol_version: String,
+    pub last_stop_reason: Option<StopReason>,
+    pub channel_capabilities: Vec<ChannelCapability>,  // Empty if the firmware doesn't report them
+    pub active_clients: Option<u32>,  // SYSTEM:CLIENTS? (includes us)
+    pub clock_skew_secs: Option<i64>,  // Device clock minus host clock (SYSTEM:TIME?)
+    pub vswr: HashMap<u8, f32>,  // Latest MEAS:VSWR? reading per channel
+    pub output_load_ohms: Option<u32>,  // OUTPUT:LOAD, if the firmware supports it
+    pub scheduled_stop_at: Option<u64>,  // Unix time set by schedule_stop_at
+    pub consecutive_poll_errors: u8,  // Failed watchdog feeds in a row; MAX_CONSECUTIVE_ERRORS drops the link
+    #[serde(skip)]
+    pub resume_channels: Vec<Channel>,  // Channel set at the last watchdog stop
+    #[serde(skip)]
+    pub pilot_saved_amplitudes: HashMap<u8, f32>,  // Normal levels of channels made pilots
+    #[serde(skip)]
+    pub session: SessionStats,
 }
 
 impl Default for DeviceState {
@@ -66,73 +200,332 @@ impl Default for DeviceState {
             broadcast: BroadcastState::Idle,
             watchdog: WatchdogState::Ok,
             last_status_time: None,
+            uptime_secs: None,
             source: SourceMode::Bram,
+            audio_input: None,
+            bram_message: None,
             channels: (1..=12).map(|id| Channel {
                 id,
                 enabled: false,
                 frequency: 540_000 + (id as u32 - 1) * 100_000,
                 amplitude: 1.0,
                 phase: 0.0,
+                pilot: false,
             }).collect(),
             fpga_temperature: None,
+            over_temperature: false,
             error_count: 0,
+            last_fault: None,
+            fault_active: false,
+            serial: None,
+            muted: false,
+            protocol_version: None,
+            expected_protocol_version: Config::PROTOCOL_VERSION.to_string(),
+            last_stop_reason: None,
+            channel_capabilities: Vec::new(),
+            active_clients: None,
+            clock_skew_secs: None,
+            vswr: HashMap::new(),
+            output_load_ohms: None,
+            scheduled_stop_at: None,
+            consecutive_poll_errors: 0,
+            resume_channels: Vec::new(),
+            pilot_saved_amplitudes: HashMap::new(),
+            session: SessionStats::default(),
         }
     }
 }
-// AUDIT LOG ENTRY
-#[derive(Clone, Debug, Serialize)]
-pub struct AuditEntry {
-    pub timestamp: u64,
-    pub level: String,
-    pub message: String,
+
+impl DeviceState {
+    /// Every broadcast state change goes through here so session stats see it
+    pub fn set_broadcast(&mut self, broadcast: BroadcastState) {
+        let was_on_air = self.broadcast.is_broadcasting();
+        self.broadcast = broadcast;
+        match (was_on_air, broadcast.is_broadcasting()) {
+            (false, true) => {
+                self.session.broadcast_cycles += 1;
+                self.session.on_air_since = Some(Instant::now());
+                let active = self.channels.iter().filter(|c| c.enabled).count();
+                self.session.observe_active_channels(active);
+            }
+            (true, false) => {
+                if let Some(since) = self.session.on_air_since.take() {
+                    self.session.broadcast_ms += since.elapsed().as_millis() as u64;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Source changes go through here; a BRAM message selection only holds while on BRAM
+    pub fn set_source(&mut self, source: SourceMode) {
+        self.source = source;
+        if source != SourceMode::Bram {
+            self.bram_message = None;
+        }
+    }
+
+    /// Channel `ch` changed outside set_pilot: no longer a pilot, saved level dropped
+    pub fn end_pilot(&mut self, ch: u8) {
+        if let Some(channel) = self.channels.iter_mut().find(|c| c.id == ch) {
+            channel.pilot = false;
+        }
+        self.pilot_saved_amplitudes.remove(&ch);
+    }
+
+    /// Seconds since the last parsed status at unix time `now`; None if none received yet
+    pub fn status_age_secs(&self, now: u64) -> Option<u64> {
+        self.last_status_time.map(|t| now.saturating_sub(t))
+    }
 }
 
-// NETWORK MANAGER - The main class
-pub struct NetworkManager {
-    // TCP connection (wrapped for async access)
-    stream: Arc<RwLock<Option<TcpStream>>>,
-    pending_changes: RwLock<Vec<ChannelChange>>,
+// CONNECTION TARGET - "host:port" with IPv6 literals bracketed
+pub fn format_endpoint(host: &str, port: u16) -> String {
+    match strip_brackets(host).parse::<IpAddr>() {
+        Ok(IpAddr::V6(ip)) => format!("[{}]:{}", ip, port),
+        _ => format!("{}:{}", host, port),
+    }
+}
 
-    // Device state
-    state: Arc<RwLock<DeviceState>>,
+fn strip_brackets(host: &str) -> &str {
+    let host = host.trim();
+    host.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(host)
+}
 
-    // Event bus for pub/sub
-    event_tx: broadcast::Sender<EventType>,
+// OPEN STREAM - IP literal (v4 or v6) or hostname; every resolved address is tried
+// in turn and the first that accepts wins
+async fn open_stream(host: &str, port: u16) -> Result<(TcpStream, SocketAddr), String> {
+    let host = strip_brackets(host);
+    let candidates: Vec<SocketAddr> = match host.parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => tokio::net::lookup_host((host, port)).await
+            .map_err(|e| format!("Cannot resolve {}: {}", host, e))?
+            .collect(),
+    };
+    if candidates.is_empty() {
+        return Err(format!("No addresses for {}", host));
+    }
 
-    // Audit log (thread-safe, max 100 entries)
-    audit_log: Arc<RwLock<Vec<AuditEntry>>>,
+    let mut last_error = String::new();
+    for addr in candidates {
+        match timeout(Duration::from_secs(Config::CONNECTION_TIMEOUT_SECS), TcpStream::connect(addr)).await {
+            Ok(Ok(stream)) => return Ok((stream, addr)),
+            Ok(Err(e)) => last_error = format!("Connection refused: {}", e),
+            Err(_) => last_error = "Connection timeout".to_string(),
+        }
+    }
+    Err(last_error)
+}
 
-    // Connection info
-    current_ip: Arc<RwLock<Option<String>>>,
-    current_port: Arc<RwLock<Option<u16>>>,
+// OPEN FIRST REACHABLE - Endpoints tried in order; returns the index of the one that accepted
+async fn open_first(endpoints: &[Endpoint]) -> Result<(TcpStream, SocketAddr, usize), String> {
+    let mut errors = Vec::new();
+    for (index, endpoint) in endpoints.iter().enumerate() {
+        match open_stream(&endpoint.ip, endpoint.port).await {
+            Ok((stream, addr)) => return Ok((stream, addr, index)),
+            Err(e) if endpoints.len() == 1 => return Err(e),
+            Err(e) => errors.push(format!("{}: {}", format_endpoint(&endpoint.ip, endpoint.port), e)),
+        }
+    }
+    Err(errors.join("; "))
+}
 
-    // Control flags
-    is_running: Arc<RwLock<bool>>,
-    reconnect_attempts: Arc<RwLock<u8>>,
+// DEVICE SERIAL - *IDN? is "manufacturer,model,serial,firmware"
+pub fn parse_serial(identity: &str) -> Option<String> {
+    let fields: Vec<&str> = identity.trim().split(',').map(str::trim).collect();
+    if fields.len() < 4 || fields[2].is_empty() {
+        return None;
+    }
+    Some(fields[2].to_string())
+}
 
-    // Watchdog tracking
-    last_watchdog_reset: Arc<RwLock<Instant>>,
+// PROTOCOL VERSION - "major.minor", optional leading 'v' ("v1.0" in *IDN?)
+pub fn parse_protocol_version(version: &str) -> Option<(u32, u32)> {
+    let version = version.trim().trim_start_matches(['v', 'V']);
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = match parts.next() {
+        Some(minor) => minor.parse().ok()?,
+        None => 0,
+    };
+    Some((major, minor))
 }
 
-impl NetworkManager {
-    // CONSTRUCTOR
-    pub fn new(event_tx: broadcast::Sender<EventType>) -> Self {
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtocolStatus {
+    Match,
+    MinorMismatch,  // Compatible, but worth a warning
+    MajorMismatch,  // Incompatible
+    Unknown,        // Device didn't report a parseable version
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ProtocolCheck {
+    pub compatible: bool,
+    pub status: ProtocolStatus,
+    pub device_version: Option<String>,
+    pub expected_version: String,
+}
+
+impl ProtocolCheck {
+    pub fn evaluate(device_version: Option<&str>, expected_version: &str) -> Self {
+        let status = match (device_version.and_then(parse_protocol_version), parse_protocol_version(expected_version)) {
+            (Some(device), Some(expected)) if device == expected => ProtocolStatus::Match,
+            (Some(device), Some(expected)) if device.0 == expected.0 => ProtocolStatus::MinorMismatch,
+            (Some(_), Some(_)) => ProtocolStatus::MajorMismatch,
+            _ => ProtocolStatus::Unknown,
+        };
+
         Self {
-            stream: Arc::new(RwLock::new(None)),
-            state: Arc::new(RwLock::new(DeviceState::default())),
-            event_tx,
-            audit_log: Arc::new(RwLock::new(Vec::new())),
-            current_ip: Arc::new(RwLock::new(None)),
-            current_port: Arc::new(RwLock::new(None)),
-            is_running: Arc::new(RwLock::new(false)),
-            reconnect_attempts: Arc::new(RwLock::new(0)),
-            pending_changes: RwLock::new(Vec::new()),
-            last_watchdog_reset: Arc::new(RwLock::new(Instant::now())),
+            compatible: matches!(status, ProtocolStatus::Match | ProtocolStatus::MinorMismatch),
+            status,
+            device_version: device_version.map(str::to_string),
+            expected_version: expected_version.to_string(),
         }
     }
+}
 
-    // AUDIT LOGGING (Same as Python)
-    async fn log(&self, level: &str, message: &str) {
+// TEMPERATURE THRESHOLDS (hysteresis band)
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct TemperatureThresholds {
+    pub high: f32,
+    pub low: f32,
+}
+
+impl Default for TemperatureThresholds {
+    fn default() -> Self {
+        Self { high: Config::TEMP_HIGH_C, low: Config::TEMP_LOW_C }
+    }
+}
+
+// RECONCILE GUARD - a channel changed locally within `window` keeps its local value when
+// a status poll disagrees (the device may not reflect the change yet)
+#[derive(Clone, Debug)]
+pub struct ReconcileGuard {
+    pub enabled: bool,
+    pub window: Duration,
+    changed: HashMap<u8, Instant>,
+}
+
+impl Default for ReconcileGuard {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window: Duration::from_millis(Config::DEFAULT_RECONCILE_WINDOW_MS),
+            changed: HashMap::new(),
+        }
+    }
+}
+
+impl ReconcileGuard {
+    fn mark(&mut self, ch: u8) {
+        self.changed.insert(ch, Instant::now());
+    }
+
+    fn suppresses(&self, ch: u8) -> bool {
+        self.enabled && self.changed.get(&ch).is_some_and(|t| t.elapsed() < self.window)
+    }
+}
+
+// CHANNEL BOUNDS - per-channel frequency sub-range (Hz), inside the global band
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct ChannelBounds {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl ChannelBounds {
+    pub fn contains(&self, freq: u32) -> bool {
+        (self.min..=self.max).contains(&freq)
+    }
+}
+
+// LINK BENCHMARK - *IDN? round-trip stats
+#[derive(Clone, Debug, Serialize)]
+pub struct LinkBench {
+    pub iterations: u32,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub avg_ms: f64,
+    pub requests_per_sec: f64,
+}
+
+// EFFECTIVE CONFIG - live tunables as currently applied
+#[derive(Clone, Debug, Serialize)]
+pub struct EffectiveConfig {
+    pub poll_interval_ms: u64,
+    pub watchdog_timeout_ms: u64,
+    pub watchdog_command: String,
+    pub connection_timeout_secs: u64,
+    pub command_timeout_secs: u64,
+    pub min_output_interval_ms: u64,
+    pub max_total_amplitude: f32,
+    pub max_vswr: f32,
+    pub default_amplitude: f32,
+    pub pilot_amplitude: f32,
+    pub temperature: TemperatureThresholds,
+    pub channel_bounds: HashMap<u8, ChannelBounds>,
+    pub retry: RetryConfig,
+    pub console_verbose: bool,
+    pub plain_output: bool,
+    pub test_mode: bool,
+    pub reconcile_guard: bool,
+    pub reconcile_window_ms: u64,
+    pub show_transitions: bool,
+}
+
+// AUDIT LOG ENTRY
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: u64,
+    pub level: String,
+    pub message: String,
+    #[serde(default)]
+    pub operator: Option<String>,  // Set by set_operator; None before one is named
+}
+
+// AUDIT SUMMARY - counts per level for a dashboard tile
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct AuditSummary {
+    pub total: usize,
+    pub info: usize,
+    pub warning: usize,
+    pub error: usize,
+    pub note: usize,
+    pub last_error_timestamp: Option<u64>,
+}
+
+impl AuditSummary {
+    pub fn from_entries(entries: &[AuditEntry]) -> Self {
+        let mut summary = Self { total: entries.len(), ..Self::default() };
+        for entry in entries {
+            match entry.level.to_uppercase().as_str() {
+                "INFO" => summary.info += 1,
+                "WARNING" => summary.warning += 1,
+                "ERROR" => {
+                    summary.error += 1;
+                    summary.last_error_timestamp = summary.last_error_timestamp.max(Some(entry.timestamp));
+                }
+                "NOTE" => summary.note += 1,
+                _ => {}
+            }
+        }
+        summary
+    }
+}
+
+// AUDIT SINK - In-memory log, optional on-disk copy (None = memory only) and the
+// operator stamped on new entries. Cloned into the poll task, which has no &self.
+#[derive(Clone, Default)]
+struct AuditSink {
+    log: Arc<RwLock<Vec<AuditEntry>>>,
+    file: Arc<RwLock<Option<AuditPersistence>>>,
+    operator: Arc<RwLock<Option<String>>>,
+}
+
+impl AuditSink {
+    async fn record(&self, level: &str, message: &str) {
         let entry = AuditEntry {
             timestamp: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
@@ -140,738 +533,7464 @@ impl NetworkManager {
                 .as_secs(),
             level: level.to_string(),
             message: message.to_string(),
+            operator: self.operator.read().await.clone(),
         };
+        self.persist(&entry).await;
 
-        let mut log = self.audit_log.write().await;
+        // Keep only the last MAX_LOG_ENTRIES (same as Python)
+        let mut log = self.log.write().await;
         log.push(entry);
-
-        // Keep only last 100 entries (same as Python)
-        if log.len() > 100 {
+        if log.len() > Config::MAX_LOG_ENTRIES {
             log.remove(0);
         }
-
-        // Also print to console
-        println!("[{}] {}: {}",
-            chrono::Local::now().format("%H:%M:%S"),
-            level,
-            message
-        );
     }
 
-    async fn log_info(&self, message: &str) {
-        self.log("INFO", message).await;
+    // A failed write stops persistence (reported on the console and in memory only).
+    // The file I/O runs on the blocking pool; the lock keeps entries in order.
+    async fn persist(&self, entry: &AuditEntry) {
+        let mut audit_file = self.file.write().await;
+        let Some(persistence) = audit_file.clone() else { return };
+        let entry = entry.clone();
+        let result = tokio::task::spawn_blocking(move || audit_file::append(&persistence, &entry))
+            .await
+            .unwrap_or_else(|e| Err(format!("Audit write task failed: {}", e)));
+        if let Err(e) = result {
+            emit_log_line(LogTag::Audit, &format!("{} - persistence stopped", e));
+            *audit_file = None;
+        }
     }
+}
 
-    async fn log_error(&self, message: &str) {
-        self.log("ERROR", message).await;
-        self.state.write().await.error_count += 1;
-    }
+// RECONNECT RELIABILITY (session counters, cleared on explicit disconnect)
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ReliabilityStats {
+    pub reconnect_attempts_total: u32,
+    pub reconnect_successes: u32,
+    pub success_rate: Option<f32>,  // None until a reconnect has been attempted
+}
 
-    async fn log_warning(&self, message: &str) {
-        self.log("WARNING", message).await;
+impl ReliabilityStats {
+    fn with_rate(mut self) -> Self {
+        self.success_rate = if self.reconnect_attempts_total == 0 {
+            None
+        } else {
+            Some(self.reconnect_successes as f32 / self.reconnect_attempts_total as f32)
+        };
+        self
     }
+}
 
-    // CONNECT TO FPGA
-    pub async fn connect(&self, ip: &str, port: u16) -> Result<(), String> {
-        // Check if already connected
-        if *self.is_running.read().await {
-            return Err("Already connected".to_string());
-        }
-
-        self.log_info(&format!("Connecting to {}:{}...", ip, port)).await;
+// AMPLITUDE BUDGET - combined drive of enabled channels
+#[derive(Clone, Debug, Serialize)]
+pub struct AmplitudeBudget {
+    pub total: f32,
+    pub max_total: f32,
+    pub enabled_channels: usize,
+    pub within_budget: bool,
+}
 
-        // Update state to Connecting
-        {
-            let mut state = self.state.write().await;
-            state.connection = ConnectionState::Connecting;
-        }
-        let _ = self.event_tx.send(EventType::ConnectionStateChanged(ConnectionState::Connecting));
+// CHANNEL PLAN BANDWIDTH
+#[derive(Clone, Debug, Serialize)]
+pub struct PlanBandwidth {
+    pub span_hz: u32,          // Highest - lowest enabled frequency
+    pub channel_count: usize,
+    pub utilization_pct: f32,  // span as % of the AM band (MIN..MAX_FREQUENCY)
+}
 
-        // Store connection info for reconnection
-        *self.current_ip.write().await = Some(ip.to_string());
-        *self.current_port.write().await = Some(port);
-
-        // Try to connect with retry/backoff
-        let addr = format!("{}:{}", ip, port);
-        let retry_config = RetryConfig::default();
-
-        let stream = match with_retry(&retry_config, || {
-            let addr = addr.clone();
-            async move {
-                timeout(
-                    Duration::from_secs(Config::CONNECTION_TIMEOUT_SECS),
-                    TcpStream::connect(&addr)
-                ).await
-                .map_err(|_| "Connection timeout".to_string())?
-                .map_err(|e| format!("Connection refused: {}", e))
-            }
-        }).await {
-            RetryResult::Success(s) => s,
-            RetryResult::Failed { attempts, last_error } => {
-                self.handle_connect_failure(&format!(
-                    "Connection failed after {} attempts: {}",
-                    attempts,
-                    last_error
-                )).await;
-                return Err(format!("Connection failed after {} attempts", attempts));
-            }
+impl PlanBandwidth {
+    pub fn from_channels(channels: &[Channel]) -> Self {
+        let enabled: Vec<u32> = channels.iter().filter(|c| c.enabled).map(|c| c.frequency).collect();
+        let span_hz = match (enabled.iter().min(), enabled.iter().max()) {
+            (Some(min), Some(max)) => max - min,
+            _ => 0,
         };
+        let band_hz = Config::MAX_FREQUENCY - Config::MIN_FREQUENCY;
 
-        // Configure TCP socket
-        if let Err(e) = stream.set_nodelay(true) {
-            self.log_warning(&format!("Failed to set TCP_NODELAY: {}", e)).await;
+        Self {
+            span_hz,
+            channel_count: enabled.len(),
+            utilization_pct: span_hz as f32 / band_hz as f32 * 100.0,
         }
+    }
+}
 
-        // Store the stream
-        *self.stream.write().await = Some(stream);
+// BRAM MESSAGE METADATA
+#[derive(Clone, Debug, Serialize)]
+pub struct BramInfo {
+    pub index: u8,
+    pub name: String,
+    pub duration_secs: f32,
+    pub samples: u32,
+}
 
-        // Update state to Connected
-        {
-            let mut state = self.state.write().await;
-            state.connection = ConnectionState::Connected;
-            state.error_count = 0;
+impl BramInfo {
+    /// Parse a `BRAM:INFO?` response: "name,duration_secs,samples"
+    pub fn parse(index: u8, response: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = response.trim().split(',').map(str::trim).collect();
+        if fields.len() != 3 {
+            return Err(format!("BRAM message {} not available: {}", index, response.trim()));
         }
 
-        // Reset reconnect counter
-        *self.reconnect_attempts.write().await = 0;
+        Ok(Self {
+            index,
+            name: fields[0].to_string(),
+            duration_secs: fields[1].parse().map_err(|_| format!("Invalid duration: {}", fields[1]))?,
+            samples: fields[2].parse().map_err(|_| format!("Invalid sample count: {}", fields[2]))?,
+        })
+    }
+}
 
-        // Set running flag
-        *self.is_running.write().await = true;
+// CHANNEL CAPABILITIES
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ChannelCapability {
+    pub channel: u8,
+    pub output: bool,
+    pub adc: bool,
+}
 
-        // Emit success event
-        let _ = self.event_tx.send(EventType::ConnectSuccess);
-        let _ = self.event_tx.send(EventType::ConnectionStateChanged(ConnectionState::Connected));
+impl ChannelCapability {
+    /// Parse a `CH{n}:CAP?` response: comma-separated flags, e.g. "OUTPUT,ADC"
+    pub fn parse(channel: u8, response: &str) -> Option<Self> {
+        let flags: Vec<String> = response.trim().split(',').map(|f| f.trim().to_uppercase()).collect();
+        let output = flags.iter().any(|f| f == "OUTPUT");
+        let adc = flags.iter().any(|f| f == "ADC");
+        if !output && !adc {
+            return None;
+        }
+        Some(Self { channel, output, adc })
+    }
+}
 
-        self.log_info(&format!("Connected to {}:{}", ip, port)).await;
+// DIAGNOSTICS
+#[derive(Clone, Debug, Serialize)]
+pub struct DiagnosticCheck {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+}
 
-        // Initialize device - query current state
-        self.initialize_device().await?;
+// POWER-ON SELF TEST - one result per channel
+#[derive(Clone, Debug, Serialize)]
+pub struct PostResult {
+    pub channel: u8,
+    pub passed: bool,
+    pub detail: String,
+}
 
-        // Start background polling task
-        self.spawn_poll_task();
+// BURN IN - one result per on/off cycle; the run stops at the first failure
+#[derive(Clone, Debug, Serialize)]
+pub struct BurnInCycle {
+    pub cycle: u32,  // 1-based
+    pub passed: bool,
+    pub detail: String,
+    pub elapsed_ms: u64,
+}
 
-        Ok(())
-    }
+#[derive(Clone, Debug, Serialize)]
+pub struct DiagnosticReport {
+    pub timestamp: u64,
+    pub checks: Vec<DiagnosticCheck>,
+    pub latency_ms: Option<u64>,
+    pub all_passed: bool,
+}
 
-    async fn handle_connect_failure(&self, reason: &str) {
-        self.log_error(reason).await;
+// LICENSE WINDOW - local hours broadcasting is permitted (may span midnight)
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct LicenseWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
 
-        let mut state = self.state.write().await;
-        state.connection = ConnectionState::Disconnected;
+impl LicenseWindow {
+    /// Parse "HH:MM" start/end times
+    pub fn parse(start: &str, end: &str) -> Result<Self, String> {
+        let parse = |t: &str| NaiveTime::parse_from_str(t.trim(), "%H:%M")
+            .map_err(|_| format!("Invalid time '{}' (expected HH:MM)", t.trim()));
+        let window = Self { start: parse(start)?, end: parse(end)? };
+        if window.start == window.end {
+            return Err("License window start and end must differ".to_string());
+        }
+        Ok(window)
+    }
 
-        let _ = self.event_tx.send(EventType::ConnectFailed(reason.to_string()));
-        let _ = self.event_tx.send(EventType::ConnectionStateChanged(ConnectionState::Disconnected));
+    /// Start inclusive, end exclusive
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            time >= self.start && time < self.end
+        } else {
+            time >= self.start || time < self.end  // Overnight
+        }
     }
+}
 
-    // INITIALIZE DEVICE - Query current state after connect
-    async fn initialize_device(&self) -> Result<(), String> {
-        self.log_info("Initializing device...").await;
+// SCRIPTED SEQUENCE - {"action": "arm"}, {"action": "wait", "ms": 500},
+// {"action": "set_channel", "ch": 1, "frequency": 540000, "enabled": true, "optional": true}, ...
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum SequenceAction {
+    Arm,
+    Start,
+    Stop,  // Also ends an emergency broadcast
+    Emergency,
+    Wait { ms: u64 },
+    SetChannel { ch: u8, frequency: u32, enabled: bool },
+}
 
-        // Query device ID
-        if let Ok(response) = self.query(ScpiCommands::IDENTITY).await {
-            self.log_info(&format!("Device: {}", response.trim())).await;
-        }
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SequenceStep {
+    #[serde(flatten)]
+    pub action: SequenceAction,
+    #[serde(default)]
+    pub optional: bool,  // A failed optional step is reported but doesn't abort the run
+}
 
-        // Query current status
-        if let Ok(response) = self.query(ScpiCommands::STATUS).await {
-            self.parse_status_response(&response).await;
-        }
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepStatus {
+    Passed,
+    Failed,
+    Skipped,  // Not run - an earlier mandatory step failed
+}
 
-        // Query each channel's current state
-        for ch in 1..=12 {
-            if let Ok(response) = self.query(&format!("FREQ:CH{}?", ch)).await {
-                if let Ok(freq) = response.trim().parse::<u32>() {
-                    let mut state = self.state.write().await;
-                    if let Some(channel) = state.channels.iter_mut().find(|c| c.id == ch) {
-                        channel.frequency = freq;
-                    }
-                }
+#[derive(Clone, Debug, Serialize)]
+pub struct StepResult {
+    pub index: usize,
+    pub action: SequenceAction,
+    pub status: StepStatus,
+    pub message: String,
+    pub elapsed_ms: u64,
+}
+
+// CONNECT TIMING - where the last successful connect spent its time
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ConnectTiming {
+    pub tcp_connect_ms: u64,  // Including retries/backoff
+    pub init_ms: u64,         // initialize_device queries
+    pub poll_spawn_ms: u64,
+    pub total_ms: u64,
+}
+
+// CONNECTION TARGET REPORT - what the operator asked for vs. what the link actually uses
+#[derive(Clone, Debug, Serialize)]
+pub struct ConnectionTarget {
+    pub requested: Endpoint,      // Primary endpoint as typed (hostnames kept)
+    pub active: Endpoint,         // Endpoint that accepted; differs from requested after failover
+    pub resolved_addr: SocketAddr,
+    pub failed_over: bool,
+}
+
+// WATCHDOG MARGIN - worst gap between watchdog resets over the last minute
+#[derive(Clone, Debug, Serialize)]
+pub struct WatchdogMargin {
+    pub worst_interval_ms: u64,
+    pub timeout_ms: u64,
+    pub margin_ms: i64,      // timeout - worst (negative = the device would have tripped)
+    pub near_miss: bool,     // Some interval has come within WATCHDOG_NEAR_MISS_PERCENT of the timeout
+}
+
+// LATENCY TREND - one sample per completed command/query
+#[derive(Clone, Debug, Serialize)]
+pub struct LatencySample {
+    pub timestamp: u64,  // Unix ms
+    pub command: String,
+    pub latency_ms: f64,
+}
+
+// NETWORK MANAGER - The main class
+// Clones share every handle, so a clone can drive the same connection from a spawned task
+#[derive(Clone)]
+pub struct NetworkManager {
+    // TCP connection (wrapped for async access)
+    stream: Arc<RwLock<Option<DeviceStream>>>,
+    pending_changes: Arc<RwLock<Vec<ChannelChange>>>,
+
+    // Device state
+    state: Arc<RwLock<DeviceState>>,
+
+    // Event bus for pub/sub (keeps a bounded history)
+    event_bus: EventBus,
+
+    // Audit log (thread-safe, max 100 entries), its on-disk copy and the current operator
+    audit: AuditSink,
+
+    // Connection info
+    current_ip: Arc<RwLock<Option<String>>>,
+    current_port: Arc<RwLock<Option<u16>>>,
+    endpoints: Arc<RwLock<Vec<Endpoint>>>,  // Failover order (primary first); current_ip/port is the active one
+    connected_addr: Arc<RwLock<Option<SocketAddr>>>,  // Address actually in use (after resolution)
+    last_connect_timing: Arc<RwLock<Option<ConnectTiming>>>,
+
+    // Control flags
+    is_running: Arc<RwLock<bool>>,
+    connecting: Arc<RwLock<bool>>,    // Held for the whole connect sequence
+    poll_tasks: Arc<RwLock<usize>>,   // Live poll loops (must never exceed 1)
+    last_poll_tick: Arc<RwLock<Instant>>,  // Bumped every poll iteration
+    poll_interval: Arc<RwLock<Duration>>,
+    ui_visible: Arc<RwLock<bool>>,  // Hidden window: keepalive-only polling
+    reconnect_attempts: Arc<RwLock<u8>>,
+    reliability: Arc<RwLock<ReliabilityStats>>,
+
+    // Watchdog tracking
+    last_watchdog_reset: Arc<RwLock<Instant>>,
+    watchdog_command: Arc<RwLock<String>>,  // Reset string sent every poll (varies by firmware)
+    watchdog_triggers: Arc<RwLock<VecDeque<u64>>>,  // Unix ms of recent trips, oldest first
+    watchdog_feeds: Arc<RwLock<VecDeque<(Instant, u64)>>>,  // (reset time, ms since previous reset)
+    watchdog_near_miss: Arc<RwLock<bool>>,
+
+    // Over-temperature hysteresis band
+    temp_thresholds: Arc<RwLock<TemperatureThresholds>>,
+    reconcile_guard: Arc<RwLock<ReconcileGuard>>,
+
+    // Per-channel frequency overrides (e.g. antenna-limited channels)
+    channel_bounds: Arc<RwLock<HashMap<u8, ChannelBounds>>>,
+
+    // Cancellation flags for a running test tone sequence / burn-in
+    tone_abort: Arc<RwLock<bool>>,
+    burn_in_abort: Arc<RwLock<bool>>,
+
+    // Bumped by abort_ramp; a ramp stops once it differs from the value it started with
+    ramp_generation: Arc<RwLock<u64>>,
+
+    // Persisted settings file
+    settings_path: PathBuf,
+
+    // Console output (audit log is always recorded in memory)
+    console_verbose: Arc<RwLock<bool>>,
+
+    // Set after a failed write/flush - next command discards stale input first
+    needs_resync: Arc<RwLock<bool>>,
+
+    // SCPI trace file (command/response lines; poll traffic is not traced)
+    scpi_trace: Arc<RwLock<Option<BufWriter<File>>>>,
+
+    // Call sign -> frequency (Hz) for tune_by_station
+    stations: Arc<RwLock<HashMap<String, u32>>>,
+
+    // Unit for frequencies returned to the UI when a query doesn't ask for one
+    frequency_unit: Arc<RwLock<FrequencyUnit>>,
+    temperature_unit: Arc<RwLock<TemperatureUnit>>,
+    show_transitions: Arc<RwLock<bool>>,  // false: UI sees ARMING/STARTING/STOPPING as their target
+
+    // Recent command latencies, oldest first (poll traffic is not included)
+    latency_trend: Arc<RwLock<VecDeque<LatencySample>>>,
+
+    // Recent mutating commands, oldest first (survives reconnect for replay)
+    command_history: Arc<RwLock<VecDeque<String>>>,
+
+    // Per-channel amplitudes saved by mute_all (restored by unmute_all)
+    muted_amplitudes: Arc<RwLock<Vec<(u8, f32)>>>,
+
+    // Connect retry/backoff (tunable at runtime)
+    retry_config: Arc<RwLock<RetryConfig>>,
+
+    // Licensed broadcast hours (None = unrestricted); override is set by an emergency start
+    license_window: Arc<RwLock<Option<LicenseWindow>>>,
+    license_override: Arc<RwLock<bool>>,
+    clock_override: Arc<RwLock<Option<NaiveTime>>>,  // Fixed local time for tests
+
+    // Bumped on every schedule/cancel so a superseded stop task exits
+    stop_schedule_generation: Arc<RwLock<u64>>,
+
+    // Output toggle safeguard (protects the power amplifier)
+    last_output_change: Arc<RwLock<Option<Instant>>>,
+    min_output_interval: Arc<RwLock<Duration>>,
+    max_total_amplitude: Arc<RwLock<f32>>,
+    max_vswr: Arc<RwLock<f32>>,
+    default_amplitude: Arc<RwLock<f32>>,
+    pilot_amplitude: Arc<RwLock<f32>>,
+
+    // Test mode - enables deliberately unsafe test helpers
+    test_mode: Arc<RwLock<bool>>,
+    watchdog_paused: Arc<RwLock<bool>>,
+    watchdog_timeout: Arc<RwLock<Duration>>,
+    injected_latency: Arc<RwLock<Duration>>,  // Added before every command/query
+}
+
+impl NetworkManager {
+    // CONSTRUCTOR
+    pub fn new(event_bus: EventBus) -> Self {
+        Self {
+            stream: Arc::new(RwLock::new(None)),
+            state: Arc::new(RwLock::new(DeviceState::default())),
+            event_bus,
+            audit: AuditSink::default(),
+            current_ip: Arc::new(RwLock::new(None)),
+            current_port: Arc::new(RwLock::new(None)),
+            endpoints: Arc::new(RwLock::new(Vec::new())),
+            connected_addr: Arc::new(RwLock::new(None)),
+            last_connect_timing: Arc::new(RwLock::new(None)),
+            is_running: Arc::new(RwLock::new(false)),
+            connecting: Arc::new(RwLock::new(false)),
+            poll_tasks: Arc::new(RwLock::new(0)),
+            last_poll_tick: Arc::new(RwLock::new(Instant::now())),
+            poll_interval: Arc::new(RwLock::new(Duration::from_millis(Config::POLL_INTERVAL_MS))),
+            ui_visible: Arc::new(RwLock::new(true)),
+            reconnect_attempts: Arc::new(RwLock::new(0)),
+            reliability: Arc::new(RwLock::new(ReliabilityStats::default())),
+            pending_changes: Arc::new(RwLock::new(Vec::new())),
+            last_watchdog_reset: Arc::new(RwLock::new(Instant::now())),
+            watchdog_command: Arc::new(RwLock::new(ScpiCommands::WATCHDOG_RESET.to_string())),
+            watchdog_triggers: Arc::new(RwLock::new(VecDeque::new())),
+            watchdog_feeds: Arc::new(RwLock::new(VecDeque::new())),
+            watchdog_near_miss: Arc::new(RwLock::new(false)),
+            temp_thresholds: Arc::new(RwLock::new(TemperatureThresholds::default())),
+            reconcile_guard: Arc::new(RwLock::new(ReconcileGuard::default())),
+            channel_bounds: Arc::new(RwLock::new(HashMap::new())),
+            tone_abort: Arc::new(RwLock::new(false)),
+            burn_in_abort: Arc::new(RwLock::new(false)),
+            settings_path: PathBuf::from(Config::SETTINGS_FILE),
+            console_verbose: Arc::new(RwLock::new(cfg!(debug_assertions))),
+            needs_resync: Arc::new(RwLock::new(false)),
+            scpi_trace: Arc::new(RwLock::new(None)),
+            stations: Arc::new(RwLock::new(HashMap::new())),
+            frequency_unit: Arc::new(RwLock::new(FrequencyUnit::Hz)),
+            temperature_unit: Arc::new(RwLock::new(TemperatureUnit::Celsius)),
+            show_transitions: Arc::new(RwLock::new(true)),
+            latency_trend: Arc::new(RwLock::new(VecDeque::with_capacity(Config::MAX_LATENCY_SAMPLES))),
+            command_history: Arc::new(RwLock::new(VecDeque::with_capacity(Config::MAX_REPLAY_COMMANDS))),
+            muted_amplitudes: Arc::new(RwLock::new(Vec::new())),
+            retry_config: Arc::new(RwLock::new(RetryConfig::default())),
+            license_window: Arc::new(RwLock::new(None)),
+            license_override: Arc::new(RwLock::new(false)),
+            clock_override: Arc::new(RwLock::new(None)),
+            stop_schedule_generation: Arc::new(RwLock::new(0)),
+            ramp_generation: Arc::new(RwLock::new(0)),
+            last_output_change: Arc::new(RwLock::new(None)),
+            min_output_interval: Arc::new(RwLock::new(Duration::from_millis(Config::MIN_OUTPUT_INTERVAL_MS))),
+            max_total_amplitude: Arc::new(RwLock::new(Config::MAX_TOTAL_AMPLITUDE)),
+            max_vswr: Arc::new(RwLock::new(Config::DEFAULT_MAX_VSWR)),
+            default_amplitude: Arc::new(RwLock::new(Config::DEFAULT_AMPLITUDE)),
+            pilot_amplitude: Arc::new(RwLock::new(Config::DEFAULT_PILOT_AMPLITUDE)),
+            test_mode: Arc::new(RwLock::new(false)),
+            watchdog_paused: Arc::new(RwLock::new(false)),
+            watchdog_timeout: Arc::new(RwLock::new(Duration::from_secs(Config::WATCHDOG_TIMEOUT_SECS))),
+            injected_latency: Arc::new(RwLock::new(Duration::ZERO)),
+        }
+    }
+
+    // Use a specific settings file (defaults to the working directory)
+    pub fn with_settings_path(mut self, path: PathBuf) -> Self {
+        self.settings_path = path;
+        self
+    }
+
+    // AUDIT LOGGING (Same as Python)
+    async fn log(&self, level: &str, message: &str) {
+        self.audit.record(level, message).await;
+
+        // Also print to console (verbose by default in debug builds only)
+        if *self.console_verbose.read().await {
+            emit_log_line(LogTag::from_level(level),
+                &format!("[{}] {}", chrono::Local::now().format("%H:%M:%S"), message));
+        }
+    }
+
+    // AUDIT PERSISTENCE - Append every audit entry to a rotating JSONL file as well
+    // `preload` fills the in-memory log from the file's tail (e.g. at startup);
+    // `path: None` goes back to memory only. Returns the number of entries preloaded.
+    pub async fn set_audit_persistence(
+        &self,
+        path: Option<PathBuf>,
+        max_bytes: u64,
+        preload: bool,
+    ) -> Result<usize, String> {
+        let Some(path) = path else {
+            if self.audit.file.write().await.take().is_some() {
+                self.log_info("Audit log persistence disabled").await;
             }
+            return Ok(0);
+        };
+        let persistence = AuditPersistence::new(path, max_bytes)?;
+
+        let mut loaded = 0;
+        if preload {
+            let mut entries = audit_file::read_tail(&persistence.path, Config::MAX_LOG_ENTRIES);
+            loaded = entries.len();
+            let mut log = self.audit.log.write().await;
+            entries.append(&mut log);
+            let excess = entries.len().saturating_sub(Config::MAX_LOG_ENTRIES);
+            entries.drain(..excess);
+            *log = entries;
         }
 
-        self.log_info("Device initialized").await;
-        Ok(())
+        let display = persistence.path.display().to_string();
+        *self.audit.file.write().await = Some(persistence);
+        self.log_info(&format!("Audit log persisted to {} ({} entries reloaded)", display, loaded)).await;
+        Ok(loaded)
     }
 
-    // DISCONNECT
-    pub async fn disconnect(&self) -> Result<(), String> {
-        self.log_info("Disconnecting...").await;
+    // SCPI FILE TRACE - Appends timestamped ">>" command / "<<" response lines
+    pub async fn start_scpi_file_trace(&self, path: &Path) -> Result<(), String> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .map_err(|e| format!("Failed to open trace file {}: {}", path.display(), e))?;
+
+        // Replacing an active trace - flush what it buffered
+        let previous = self.scpi_trace.write().await.replace(BufWriter::new(file));
+        if let Some(mut writer) = previous {
+            let _ = writer.flush().await;
+        }
 
-        // Stop polling
-        *self.is_running.write().await = false;
-        self.pending_changes.write().await.clear();
+        self.log_info(&format!("SCPI trace started: {}", path.display())).await;
+        Ok(())
+    }
 
-        // If broadcasting, stop first
-        {
-            let state = self.state.read().await;
-            if state.broadcast == BroadcastState::Broadcasting {
-                drop(state);  // Release lock before calling stop
-                let _ = self.stop_broadcast().await;
+    pub async fn stop_scpi_file_trace(&self) -> Result<(), String> {
+        let writer = self.scpi_trace.write().await.take();
+        match writer {
+            Some(mut writer) => {
+                writer.flush().await.map_err(|e| format!("Failed to flush trace file: {}", e))?;
+                self.log_info("SCPI trace stopped").await;
+                Ok(())
             }
+            None => Err("No SCPI trace active".to_string()),
         }
+    }
 
-        // Close TCP stream
-        {
-            let mut stream = self.stream.write().await;
-            if let Some(s) = stream.take() {
-                drop(s);  // Close connection
+    async fn trace_scpi(&self, direction: &str, line: &str) {
+        let failed = {
+            let mut trace = self.scpi_trace.write().await;
+            let Some(writer) = trace.as_mut() else { return };
+
+            let entry = format!(
+                "{} {} {}\n",
+                chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+                direction,
+                line.trim_end()
+            );
+            let failed = writer.write_all(entry.as_bytes()).await.is_err();
+            if failed {
+                *trace = None;
             }
+            failed
+        };
+
+        if failed {
+            self.log_warning("SCPI trace write failed - trace stopped").await;
         }
+    }
 
-        // Update state
-        {
-            let mut state = self.state.write().await;
-            state.connection = ConnectionState::Disconnected;
-            state.broadcast = BroadcastState::Idle;
-            state.watchdog = WatchdogState::Ok;
+    // REMEMBER ENDPOINT - Persist last-used ip:port
+    pub async fn remember_endpoint(&self, ip: &str, port: u16) -> Result<(), String> {
+        let ip = ip.trim();
+        if ip.is_empty() || port == 0 {
+            return Err(format!("Invalid endpoint {}:{}", ip, port));
         }
 
-        // Clear connection info
-        *self.current_ip.write().await = None;
-        *self.current_port.write().await = None;
+        let mut settings = Settings::load(&self.settings_path);
+        settings.last_endpoint = Some(Endpoint { ip: ip.to_string(), port });
+        settings.save(&self.settings_path)
+    }
 
-        // Emit event
-        let _ = self.event_tx.send(EventType::Disconnected);
-        let _ = self.event_tx.send(EventType::ConnectionStateChanged(ConnectionState::Disconnected));
+    // LAST ENDPOINT - Falls back to Config::DEFAULT_IP/PORT
+    pub fn last_endpoint(&self) -> Endpoint {
+        Settings::load(&self.settings_path).last_endpoint.unwrap_or_default()
+    }
 
-        self.log_info("Disconnected").await;
-        Ok(())
+    // RELIABILITY STATS
+    pub async fn reliability_stats(&self) -> ReliabilityStats {
+        self.reliability.read().await.clone().with_rate()
     }
 
-    // SEND COMMAND (Low-level)
-    async fn send_command(&self, command: &str) -> Result<(), String> {
-        let mut stream_guard = self.stream.write().await;
+    // RETRY CONFIG
+    pub async fn get_retry_config(&self) -> RetryConfig {
+        self.retry_config.read().await.clone()
+    }
 
-        if let Some(stream) = stream_guard.as_mut() {
-            let msg = format!("{}\n", command);
+    pub async fn set_retry_config(&self, config: RetryConfig) -> Result<(), String> {
+        config.validate()?;
+        self.log_info(&format!(
+            "Retry config: {} attempts, {}-{}ms, x{}",
+            config.max_attempts, config.initial_delay_ms, config.max_delay_ms, config.multiplier
+        )).await;
+        *self.retry_config.write().await = config;
+        Ok(())
+    }
 
-            match timeout(
-                Duration::from_secs(Config::COMMAND_TIMEOUT_SECS),
-                stream.write_all(msg.as_bytes())
-            ).await {
-                Ok(Ok(_)) => {
-                    // Flush to ensure it's sent
-                    if let Err(e) = stream.flush().await {
-                        return Err(format!("Flush failed: {}", e));
-                    }
-                    Ok(())
-                }
-                Ok(Err(e)) => Err(format!("Write failed: {}", e)),
-                Err(_) => Err("Command timeout".to_string()),
-            }
-        } else {
-            Err("Not connected".to_string())
+    // TEST MODE
+    pub async fn set_test_mode(&self, enabled: bool) {
+        *self.test_mode.write().await = enabled;
+        if !enabled {
+            *self.injected_latency.write().await = Duration::ZERO;
         }
+        self.log_warning(&format!("Test mode {}", if enabled { "ENABLED" } else { "disabled" })).await;
     }
 
-    // QUERY (Send command, get response)
-    async fn query(&self, command: &str) -> Result<String, String> {
-        // Send the command
-        self.send_command(command).await?;
+    // INJECT LATENCY - Delay every command/query to mimic a sluggish link (test mode only)
+    // Cleared when test mode is turned off; 0 removes it
+    pub async fn inject_latency(&self, ms: u64) -> Result<(), String> {
+        if !*self.test_mode.read().await {
+            return Err("Latency injection requires test mode".to_string());
+        }
+        if ms > Config::MAX_INJECTED_LATENCY_MS {
+            return Err(format!("Latency must be at most {} ms", Config::MAX_INJECTED_LATENCY_MS));
+        }
 
-        // Read response
-        let mut stream_guard = self.stream.write().await;
+        *self.injected_latency.write().await = Duration::from_millis(ms);
+        self.log_warning(&format!("TEST: injected latency {} ms", ms)).await;
+        Ok(())
+    }
 
-        if let Some(stream) = stream_guard.as_mut() {
-            let mut reader = BufReader::new(stream);
-            let mut response = String::new();
+    // TEST WATCHDOG TRIP - Starve the watchdog so the device trips, then resume feeding
+    // Returns whether the device reported the trip
+    pub async fn test_watchdog_trip(&self, pause_ms: u64) -> Result<bool, String> {
+        if !*self.test_mode.read().await {
+            return Err("Watchdog trip test requires test mode".to_string());
+        }
+        if !self.is_connected().await {
+            return Err("Not connected".to_string());
+        }
 
-            match timeout(
-                Duration::from_secs(Config::COMMAND_TIMEOUT_SECS),
-                reader.read_line(&mut response)
-            ).await {
-                Ok(Ok(0)) => Err("Connection closed".to_string()),
-                Ok(Ok(_)) => Ok(response),
-                Ok(Err(e)) => Err(format!("Read failed: {}", e)),
-                Err(_) => Err("Response timeout".to_string()),
-            }
-        } else {
-            Err("Not connected".to_string())
+        let timeout_ms = self.watchdog_timeout.read().await.as_millis() as u64;
+        if pause_ms <= timeout_ms {
+            return Err(format!("Pause must exceed watchdog timeout ({} ms)", timeout_ms));
         }
+
+        self.log_warning(&format!("TEST: pausing watchdog resets for {} ms", pause_ms)).await;
+        *self.watchdog_paused.write().await = true;
+        sleep(Duration::from_millis(pause_ms)).await;
+
+        // Read back status - parser emits WatchdogTriggered and auto-stops broadcast
+        let status = self.query(ScpiCommands::STATUS).await;
+        *self.watchdog_paused.write().await = false;
+
+        self.parse_status_response(&status?).await;
+
+        let tripped = self.state.read().await.watchdog == WatchdogState::Triggered;
+        self.log_warning(&format!("TEST: watchdog resets resumed (tripped={})", tripped)).await;
+        Ok(tripped)
     }
 
-    // POLLING TASK - Runs every 500ms in background
-    fn spawn_poll_task(&self) {
-        let stream = self.stream.clone();
-        let state = self.state.clone();
-        let event_tx = self.event_tx.clone();
-        let is_running = self.is_running.clone();
-        let last_watchdog_reset = self.last_watchdog_reset.clone();
-        let audit_log = self.audit_log.clone();
-        let current_ip = self.current_ip.clone();
-        let current_port = self.current_port.clone();
-        let reconnect_attempts = self.reconnect_attempts.clone();
+    // REPLAY EVENTS - Emit a recorded sequence on the bus (test mode only)
+    pub async fn replay_events(&self, events: Vec<EventType>, interval_ms: u64) -> Result<(), String> {
+        if !*self.test_mode.read().await {
+            return Err("Event replay requires test mode".to_string());
+        }
 
-        tokio::spawn(async move {
-            let mut consecutive_errors = 0u8;
+        self.log_info(&format!("Replaying {} events at {} ms intervals", events.len(), interval_ms)).await;
 
-            loop {
-                // Check if we should stop
-                if !*is_running.read().await {
-                    break;
-                }
+        for (i, event) in events.into_iter().enumerate() {
+            if i > 0 {
+                sleep(Duration::from_millis(interval_ms)).await;
+            }
+            self.event_bus.emit(event);
+        }
+        Ok(())
+    }
 
-                // Sleep between polls
-                sleep(Duration::from_millis(Config::POLL_INTERVAL_MS)).await;
+    // UI VISIBILITY - While hidden the poll loop only keeps the watchdog fed and refreshes
+    // status slowly; full-rate polling resumes on the next iteration once visible
+    pub async fn set_ui_visible(&self, visible: bool) {
+        let changed = *self.ui_visible.read().await != visible;
+        *self.ui_visible.write().await = visible;
+        if changed {
+            self.log_info(if visible { "UI visible - full status polling" } else { "UI hidden - keepalive polling" }).await;
+        }
+    }
 
-                // Check if we should stop (again, after sleep)
-                if !*is_running.read().await {
-                    break;
-                }
+    // CONSOLE VERBOSITY
+    pub async fn set_console_verbose(&self, verbose: bool) {
+        *self.console_verbose.write().await = verbose;
+    }
 
-                // CRITICAL: WATCHDOG RESET
-                // Must send this every poll or FPGA stops output!
-                let watchdog_result = {
-                    let mut stream_guard = stream.write().await;
-                    if let Some(s) = stream_guard.as_mut() {
-                        let msg = format!("{}\n", ScpiCommands::WATCHDOG_RESET);
-                        s.write_all(msg.as_bytes()).await
-                    } else {
-                        Err(std::io::Error::new(std::io::ErrorKind::NotConnected, "No stream"))
-                    }
-                };
+    pub async fn is_console_verbose(&self) -> bool {
+        *self.console_verbose.read().await
+    }
 
-                if let Err(e) = watchdog_result {
-                    consecutive_errors += 1;
+    async fn log_info(&self, message: &str) {
+        self.log("INFO", message).await;
+    }
 
-                    // Log error
-                    let entry = AuditEntry {
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs(),
-                        level: "ERROR".to_string(),
-                        message: format!("Watchdog reset failed: {}", e),
-                    };
-                    audit_log.write().await.push(entry);
+    async fn log_error(&self, message: &str) {
+        self.log("ERROR", message).await;
+        self.state.write().await.error_count += 1;
+    }
 
-                    // Too many errors - connection lost
-                    if consecutive_errors >= Config::MAX_CONSECUTIVE_ERRORS {
-                        Self::handle_connection_lost(
-                            &state, &event_tx, &is_running, &current_ip,
-                            &current_port, &reconnect_attempts
-                        ).await;
-                        break;
-                    }
-                    continue;
-                }
+    async fn log_warning(&self, message: &str) {
+        self.log("WARNING", message).await;
+    }
 
-                // Update watchdog timestamp
-                *last_watchdog_reset.write().await = Instant::now();
-                consecutive_errors = 0;
+    // SET OPERATOR - Name stamped on every audit entry from now on (control characters
+    // dropped, whitespace collapsed, at most MAX_OPERATOR_NAME_CHARS)
+    pub async fn set_operator(&self, name: &str) -> Result<String, String> {
+        let cleaned: String = name
+            .chars()
+            .filter(|c| !c.is_control() || c.is_whitespace())
+            .collect();
+        let cleaned = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+        if cleaned.is_empty() {
+            return Err("Operator name is empty".to_string());
+        }
 
-                // QUERY STATUS
-                let status_result = {
-                    let mut stream_guard = stream.write().await;
-                    if let Some(s) = stream_guard.as_mut() {
-                        // Send query
-                        let msg = format!("{}\n", ScpiCommands::STATUS);
-                        if s.write_all(msg.as_bytes()).await.is_ok() {
-                            // Read response
-                            let mut buf = [0u8; 1024];
-                            match timeout(Duration::from_secs(2), s.read(&mut buf)).await {
-                                Ok(Ok(n)) if n > 0 => {
-                                    Some(String::from_utf8_lossy(&buf[..n]).to_string())
-                                }
-                                _ => None
-                            }
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
-                };
+        let cleaned: String = cleaned.chars().take(Config::MAX_OPERATOR_NAME_CHARS).collect();
 
-                // Parse status response
-                if let Some(response) = status_result {
-                    Self::parse_status_static(&response, &state, &event_tx).await;
-                }
+        let previous = self.audit.operator.write().await.replace(cleaned.clone());
+        match previous {
+            Some(previous) => self.log_info(&format!("Operator changed from {} to {}", previous, cleaned)).await,
+            None => self.log_info(&format!("Operator set to {}", cleaned)).await,
+        }
+        Ok(cleaned)
+    }
 
-                // Emit state update event
-                let _ = event_tx.send(EventType::DeviceStateUpdated);
-            }
-        });
+    // EXPORT AUDIT CSV - In-memory log, with operator attribution
+    pub async fn export_audit_csv(&self, path: &Path) -> Result<usize, String> {
+        let entries = self.audit.log.read().await.clone();
+        tokio::fs::write(path, audit_file::to_csv(&entries)).await
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(entries.len())
     }
 
-    // HANDLE CONNECTION LOST - Attempt reconnection
-    async fn handle_connection_lost(
-        state: &Arc<RwLock<DeviceState>>,
-        event_tx: &broadcast::Sender<EventType>,
-        is_running: &Arc<RwLock<bool>>,
-        current_ip: &Arc<RwLock<Option<String>>>,
-        current_port: &Arc<RwLock<Option<u16>>>,
-        reconnect_attempts: &Arc<RwLock<u8>>,
-    ) {
-        // Update state
-        {
-            let mut s = state.write().await;
-            s.connection = ConnectionState::Reconnecting;
-            s.broadcast = BroadcastState::Idle;  // Stop broadcast on disconnect
+    // ACKNOWLEDGE ERRORS - Operator has investigated; zero the error and watchdog trip counters
+    pub async fn acknowledge_errors(&self) {
+        let errors = std::mem::take(&mut self.state.write().await.error_count);
+        let trips = {
+            let mut triggers = self.watchdog_triggers.write().await;
+            let trips = triggers.len();
+            triggers.clear();
+            trips
+        };
+        self.log_info(&format!(
+            "Operator acknowledged {} errors and {} watchdog trips - counters reset", errors, trips
+        )).await;
+    }
+
+    // OPERATOR NOTE - Freeform text in the audit timeline (NOTE level)
+    pub async fn add_note(&self, note: &str) -> Result<(), String> {
+        let cleaned: String = note
+            .chars()
+            .map(|c| if c.is_control() { ' ' } else { c })
+            .collect();
+        let cleaned = cleaned.trim();
+        if cleaned.is_empty() {
+            return Err("Note is empty".to_string());
         }
 
-        let _ = event_tx.send(EventType::ConnectionLost);
-        let _ = event_tx.send(EventType::ConnectionStateChanged(ConnectionState::Reconnecting));
+        let note: String = cleaned.chars().take(Config::MAX_NOTE_LENGTH).collect();
+        self.log("NOTE", &note).await;
+        Ok(())
+    }
 
-        // Get connection info
-        let ip = current_ip.read().await.clone();
-        let port = *current_port.read().await;
+    // CONNECT TO FPGA
+    pub async fn connect(&self, ip: &str, port: u16) -> Result<(), String> {
+        self.claim_connect().await?;
 
-        if ip.is_none() || port.is_none() {
-            // No connection info - can't reconnect
-            *is_running.write().await = false;
-            state.write().await.connection = ConnectionState::Disconnected;
-            let _ = event_tx.send(EventType::ConnectionStateChanged(ConnectionState::Disconnected));
-            return;
+        let result = self.connect_sequence(&[Endpoint::new(ip, port)]).await;
+        *self.connecting.write().await = false;
+        result
+    }
+
+    // CONNECT WITH FAILOVER - First reachable endpoint wins; the whole list is
+    // kept for reconnection after a mid-session drop
+    pub async fn connect_with_failover(&self, endpoints: &[Endpoint]) -> Result<Endpoint, String> {
+        if endpoints.is_empty() {
+            return Err("No endpoints given".to_string());
         }
+        self.claim_connect().await?;
 
-        let ip = ip.unwrap();
-        let port = port.unwrap();
+        let result = self.connect_sequence(endpoints).await;
+        *self.connecting.write().await = false;
+        result?;
+        self.active_endpoint().await.ok_or_else(|| "Connected endpoint unknown".to_string())
+    }
 
-        // Attempt reconnection
-        for attempt in 1..=Config::MAX_RECONNECT_ATTEMPTS {
-            *reconnect_attempts.write().await = attempt;
+    // BEGIN CONNECT - Returns at once; outcome arrives as ConnectSuccess/ConnectFailed
+    pub async fn begin_connect(&self, ip: &str, port: u16) -> Result<(), String> {
+        self.claim_connect().await?;
 
-            let _ = event_tx.send(EventType::ReconnectAttempt(attempt));
+        let manager = self.clone();
+        let ip = ip.to_string();
+        tokio::spawn(async move {
+            let _ = manager.connect_sequence(&[Endpoint::new(&ip, port)]).await;
+            *manager.connecting.write().await = false;
+        });
+        Ok(())
+    }
+
+    // Check-and-set under one lock so overlapping calls can't both proceed
+    async fn claim_connect(&self) -> Result<(), String> {
+        let mut connecting = self.connecting.write().await;
+        if *connecting {
+            return Err("Connect already in progress".to_string());
+        }
+        if *self.is_running.read().await {
+            return Err("Already connected".to_string());
+        }
+        *connecting = true;
+        Ok(())
+    }
+
+    async fn connect_sequence(&self, endpoints: &[Endpoint]) -> Result<(), String> {
+        let targets: Vec<String> = endpoints.iter().map(|e| format_endpoint(&e.ip, e.port)).collect();
+        self.log_info(&format!("Connecting to {}...", targets.join(" / "))).await;
+
+        // Update state to Connecting
+        {
+            let mut state = self.state.write().await;
+            state.connection = ConnectionState::Connecting;
+        }
+        self.event_bus.emit(EventType::Connecting);
+        self.event_bus.emit(EventType::ConnectionStateChanged(ConnectionState::Connecting));
+
+        // Store connection info for reconnection
+        *self.endpoints.write().await = endpoints.to_vec();
+        *self.current_ip.write().await = Some(endpoints[0].ip.clone());
+        *self.current_port.write().await = Some(endpoints[0].port);
+
+        // Try to connect with retry/backoff (each attempt walks the endpoint list)
+        let started = Instant::now();
+        let retry_config = self.retry_config.read().await.clone();
+
+        let (stream, addr, index) = match with_retry(&retry_config, || open_first(endpoints)).await {
+            RetryResult::Success(connected) => connected,
+            RetryResult::Failed { attempts, last_error } => {
+                self.handle_connect_failure(&format!(
+                    "Connection failed after {} attempts: {}",
+                    attempts,
+                    last_error
+                )).await;
+                return Err(format!("Connection failed after {} attempts", attempts));
+            }
+        };
+
+        let tcp_connect_ms = started.elapsed().as_millis() as u64;
+
+        if index > 0 {
+            self.log_warning(&format!("{} unreachable - failed over to {}",
+                targets[0], targets[index])).await;
+            *self.current_ip.write().await = Some(endpoints[index].ip.clone());
+            *self.current_port.write().await = Some(endpoints[index].port);
+        }
+
+        // Configure TCP socket
+        if let Err(e) = stream.set_nodelay(true) {
+            self.log_warning(&format!("Failed to set TCP_NODELAY: {}", e)).await;
+        }
+
+        // Store the stream
+        *self.stream.write().await = Some(BufReader::with_capacity(Config::READ_BUFFER_BYTES, stream));
+        *self.connected_addr.write().await = Some(addr);
+
+        // Update state to Connected
+        {
+            let mut state = self.state.write().await;
+            state.connection = ConnectionState::Connected;
+            state.error_count = 0;
+            state.session = SessionStats::default();
+        }
+
+        // Reset reconnect counter
+        *self.reconnect_attempts.write().await = 0;
+
+        // Set running flag
+        *self.is_running.write().await = true;
+
+        // Emit success event
+        self.event_bus.emit(EventType::ConnectSuccess);
+        self.event_bus.emit(EventType::ConnectionStateChanged(ConnectionState::Connected));
+
+        self.log_info(&format!("Connected to {}", addr)).await;
+
+        // Initialize device - query current state
+        let phase = Instant::now();
+        self.initialize_device().await?;
+        let init_ms = phase.elapsed().as_millis() as u64;
+
+        // Start background polling task
+        let phase = Instant::now();
+        self.spawn_poll_task();
+        let poll_spawn_ms = phase.elapsed().as_millis() as u64;
+
+        let timing = ConnectTiming {
+            tcp_connect_ms,
+            init_ms,
+            poll_spawn_ms,
+            total_ms: started.elapsed().as_millis() as u64,
+        };
+        self.log_info(&format!(
+            "Connect took {} ms (tcp {} ms, init {} ms)", timing.total_ms, tcp_connect_ms, init_ms
+        )).await;
+        *self.last_connect_timing.write().await = Some(timing);
+
+        Ok(())
+    }
+
+    async fn handle_connect_failure(&self, reason: &str) {
+        self.log_error(reason).await;
+
+        let mut state = self.state.write().await;
+        state.connection = ConnectionState::Disconnected;
+
+        self.event_bus.emit(EventType::ConnectFailed(reason.to_string()));
+        self.event_bus.emit(EventType::ConnectionStateChanged(ConnectionState::Disconnected));
+    }
+
+    // INITIALIZE DEVICE - Query current state after connect
+    async fn initialize_device(&self) -> Result<(), String> {
+        self.log_info("Initializing device...").await;
+
+        // Query device ID
+        if let Ok(response) = self.query(ScpiCommands::IDENTITY).await {
+            self.log_info(&format!("Device: {}", response.trim())).await;
+            let mut state = self.state.write().await;
+            state.serial = parse_serial(&response);
+            // Firmware field doubles as the protocol version until SYSTEM:PROTO? is asked
+            state.protocol_version = response.trim().split(',').nth(3).map(|v| v.trim().to_string());
+        }
+
+        // Query current status
+        if let Ok(response) = self.query(ScpiCommands::STATUS).await {
+            self.parse_status_response(&response).await;
+        }
+
+        self.query_channel_capabilities().await;
+
+        // Query each channel's current state
+        for ch in 1..=12 {
+            if let Ok(response) = self.query(&format!("FREQ:CH{}?", ch)).await {
+                if let Ok(freq) = response.trim().parse::<u32>() {
+                    let mut state = self.state.write().await;
+                    if let Some(channel) = state.channels.iter_mut().find(|c| c.id == ch) {
+                        channel.frequency = freq;
+                    }
+                }
+            }
+        }
+
+        self.log_info("Device initialized").await;
+        Ok(())
+    }
+
+    // CHANNEL CAPABILITIES - CH{n}:CAP? per channel
+    // Older firmware doesn't answer; give up after the first channel instead of timing out 12 times
+    async fn query_channel_capabilities(&self) {
+        let mut capabilities = Vec::new();
+        for ch in 1..=Config::NUM_CHANNELS {
+            let response = self.query(&format!("CH{}{}", ch, ScpiCommands::CAP_QUERY_SUFFIX)).await;
+            match response.ok().and_then(|r| ChannelCapability::parse(ch, &r)) {
+                Some(capability) => capabilities.push(capability),
+                None if ch == 1 => break,
+                None => {}
+            }
+        }
+
+        if !capabilities.is_empty() {
+            let adc: Vec<String> = capabilities.iter().filter(|c| c.adc).map(|c| c.channel.to_string()).collect();
+            self.log_info(&format!("ADC-capable channels: {}",
+                if adc.is_empty() { "none".to_string() } else { adc.join(", ") })).await;
+        }
+        self.state.write().await.channel_capabilities = capabilities;
+    }
+
+    // ADC CAPABILITY - Channels the device reported as output-only can't carry ADC audio
+    // (channels it didn't report on are not restricted)
+    async fn check_adc_capable(&self, channels: &[u8]) -> Result<(), String> {
+        let state = self.state.read().await;
+        let blocked: Vec<String> = channels
+            .iter()
+            .filter(|&&ch| state.channel_capabilities.iter().any(|c| c.channel == ch && !c.adc))
+            .map(|ch| format!("CH{}", ch))
+            .collect();
+
+        if blocked.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("No ADC capability on {}", blocked.join(", ")))
+        }
+    }
+
+    async fn enabled_channel_ids(&self) -> Vec<u8> {
+        self.state.read().await.channels.iter().filter(|c| c.enabled).map(|c| c.id).collect()
+    }
+
+    // DISCONNECT
+    // `stop_on_disconnect = false` detaches without touching output (handoff to another
+    // controller, which must keep feeding the watchdog)
+    pub async fn disconnect(&self, stop_on_disconnect: bool) -> Result<(), String> {
+        // Already down (e.g. auto-reconnect gave up) - keep the audit log quiet, but a
+        // failed reconnect leaves session info behind that an explicit disconnect must clear
+        if self.state.read().await.connection == ConnectionState::Disconnected {
+            *self.is_running.write().await = false;
+            self.clear_session().await;
+            return Ok(());
+        }
+
+        self.log_info("Disconnecting...").await;
+
+        // Stop polling
+        *self.is_running.write().await = false;
+        self.pending_changes.write().await.clear();
+
+        // If broadcasting, stop first
+        {
+            let state = self.state.read().await;
+            if state.broadcast == BroadcastState::Broadcasting {
+                drop(state);  // Release lock before calling stop
+                if stop_on_disconnect {
+                    let _ = self.stop_output(true, StopReason::Disconnect).await;
+                } else {
+                    self.log_warning("Detaching with output left ON - another controller must feed the watchdog").await;
+                }
+            }
+        }
+
+        // Close TCP stream
+        {
+            let mut stream = self.stream.write().await;
+            if let Some(s) = stream.take() {
+                drop(s);  // Close connection
+            }
+        }
+
+        // Update state
+        {
+            let mut state = self.state.write().await;
+            state.connection = ConnectionState::Disconnected;
+            if state.broadcast.is_broadcasting() {
+                state.last_stop_reason = Some(StopReason::Disconnect);
+            }
+            state.set_broadcast(BroadcastState::Idle);
+            state.watchdog = WatchdogState::Ok;
+        }
+
+        self.clear_session().await;
+
+        // Emit event
+        self.event_bus.emit(EventType::Disconnected);
+        self.event_bus.emit(EventType::ConnectionStateChanged(ConnectionState::Disconnected));
+
+        self.log_info("Disconnected").await;
+        Ok(())
+    }
+
+    // CLEAR SESSION - Connection info and per-session counters
+    async fn clear_session(&self) {
+        *self.current_ip.write().await = None;
+        *self.current_port.write().await = None;
+        *self.connected_addr.write().await = None;
+
+        // Reliability counters cover one session (mid-session drops keep them)
+        *self.reliability.write().await = ReliabilityStats::default();
+    }
+
+    // DEVICE IP VALIDATION - dotted-quad unicast address only
+    pub fn validate_device_ip(ip: &str) -> Result<Ipv4Addr, String> {
+        let addr: Ipv4Addr = ip.parse().map_err(|_| format!("Invalid IPv4 address: '{}'", ip))?;
+        if addr.is_unspecified() || addr.is_broadcast() || addr.is_multicast() || addr.is_loopback() {
+            return Err(format!("Not a usable device address: {}", addr));
+        }
+        Ok(addr)
+    }
+
+    // SET DEVICE IP - Reassigns the FPGA's static IP, then reconnects to it
+    pub async fn set_device_ip(&self, ip: &str) -> Result<(), String> {
+        let addr = Self::validate_device_ip(ip)?;
+
+        if self.state.read().await.connection != ConnectionState::Connected {
+            return Err("Not connected".to_string());
+        }
+        let port = self.current_port.read().await.unwrap_or(Config::DEFAULT_PORT);
+
+        self.log_warning(&format!(
+            "Changing device IP to {} - current connection will drop", addr
+        )).await;
+        self.send_command(&format!("{} {}", ScpiCommands::SYSTEM_IP, addr)).await?;
+
+        // Old connection is gone once the device applies the address
+        self.disconnect(true).await?;
+        while *self.poll_tasks.read().await > 0 {
+            sleep(Duration::from_millis(Config::POLL_INTERVAL_MS / 10)).await;
+        }
+        sleep(Duration::from_millis(Config::IP_CHANGE_SETTLE_MS)).await;
+
+        self.connect(&addr.to_string(), port).await
+    }
+
+    // SEND COMMAND (Low-level)
+    async fn send_command(&self, command: &str) -> Result<(), String> {
+        let started = Instant::now();
+        let result = self.write_command(command).await;
+        if result.is_ok() {
+            self.record_latency(command, started).await;
+            self.record_for_replay(command).await;
+        }
+        result
+    }
+
+    // WRITE COMMAND - Untimed write shared by send_command and query
+    async fn write_command(&self, command: &str) -> Result<(), String> {
+        self.apply_injected_latency().await;
+
+        let mut stream_guard = self.stream.write().await;
+
+        if let Some(stream) = stream_guard.as_mut() {
+            self.write_to(stream, command).await
+        } else {
+            Err("Not connected".to_string())
+        }
+    }
+
+    async fn apply_injected_latency(&self) {
+        let delay = *self.injected_latency.read().await;
+        if !delay.is_zero() {
+            sleep(delay).await;
+        }
+    }
+
+    // WRITE TO - One command on a stream the caller already holds
+    async fn write_to(&self, stream: &mut DeviceStream, command: &str) -> Result<(), String> {
+        // Previous write failed - socket may hold a stale/partial exchange
+        if *self.needs_resync.read().await {
+            let discarded = Self::drain_input(stream);
+            *self.needs_resync.write().await = false;
+            self.log_warning(&format!("Resynced connection (discarded {} stale bytes)", discarded)).await;
+        }
+
+        let msg = format!("{}\n", command);
+
+        let result = match timeout(
+            Duration::from_secs(Config::COMMAND_TIMEOUT_SECS),
+            stream.write_all(msg.as_bytes())
+        ).await {
+            Ok(Ok(_)) => {
+                // Flush to ensure it's sent
+                stream.flush().await.map_err(|e| format!("Flush failed: {}", e))
+            }
+            Ok(Err(e)) => Err(format!("Write failed: {}", e)),
+            Err(_) => Err("Command timeout".to_string()),
+        };
+
+        if result.is_err() {
+            *self.needs_resync.write().await = true;
+        } else {
+            self.trace_scpi(">>", command).await;
+        }
+        result
+    }
+
+    // DRAIN INPUT - Discard any bytes already read ahead or waiting on the socket
+    fn drain_input(stream: &mut DeviceStream) -> usize {
+        let mut discarded = stream.buffer().len();
+        stream.consume(discarded);
+
+        let mut buf = [0u8; 1024];
+        loop {
+            match stream.get_ref().try_read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => discarded += n,
+                Err(_) => break,  // WouldBlock - nothing left to read
+            }
+        }
+        discarded
+    }
+
+    // RECORD LATENCY - Bounded ring buffer for the UI sparkline
+    async fn record_latency(&self, command: &str, started: Instant) {
+        let sample = LatencySample {
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+            command: command.to_string(),
+            latency_ms: started.elapsed().as_secs_f64() * 1000.0,
+        };
+
+        let mut trend = self.latency_trend.write().await;
+        if trend.len() >= Config::MAX_LATENCY_SAMPLES {
+            trend.pop_front();
+        }
+        trend.push_back(sample);
+    }
+
+    // QUERY (Send command, get response)
+    // The stream stays locked from write to response, so the poll loop can't slip a
+    // STATUS? in between and take this command's reply
+    async fn query(&self, command: &str) -> Result<String, String> {
+        // Send the command (timed through to the response)
+        let started = Instant::now();
+        self.apply_injected_latency().await;
+        let mut stream_guard = self.stream.write().await;
+
+        if let Some(stream) = stream_guard.as_mut() {
+            self.write_to(stream, command).await?;
+
+            // Read response
+            let mut response = String::new();
+
+            let result = match timeout(
+                Duration::from_secs(Config::COMMAND_TIMEOUT_SECS),
+                stream.read_line(&mut response)
+            ).await {
+                Ok(Ok(0)) => Err("Connection closed".to_string()),
+                Ok(Ok(_)) => Ok(response),
+                Ok(Err(e)) => Err(format!("Read failed: {}", e)),
+                Err(_) => Err("Response timeout".to_string()),
+            };
+
+            if let Ok(response) = &result {
+                self.trace_scpi("<<", response).await;
+            }
+            drop(stream_guard);
+            if result.is_ok() {
+                self.record_latency(command, started).await;
+            }
+            result
+        } else {
+            Err("Not connected".to_string())
+        }
+    }
+
+    // QUERY PIPELINED - Write every query in one go, then read the responses in order
+    // (one round-trip of latency instead of one per query)
+    async fn query_pipelined(&self, commands: &[String]) -> Result<Vec<String>, String> {
+        let started = Instant::now();
+        let mut stream_guard = self.stream.write().await;
+        let stream = stream_guard.as_mut().ok_or("Not connected")?;
+        self.write_batch(stream, commands).await?;
+
+        let mut responses = Vec::with_capacity(commands.len());
+        for command in commands {
+            let mut response = String::new();
+            let result = match timeout(
+                Duration::from_secs(Config::COMMAND_TIMEOUT_SECS),
+                stream.read_line(&mut response)
+            ).await {
+                Ok(Ok(0)) => Err("Connection closed".to_string()),
+                Ok(Ok(_)) => Ok(()),
+                Ok(Err(e)) => Err(format!("Read failed: {}", e)),
+                Err(_) => Err(format!("Response timeout ({})", command)),
+            };
+            if let Err(e) = result {
+                // Remaining responses may still arrive - discard them before the next command
+                *self.needs_resync.write().await = true;
+                return Err(e);
+            }
+            self.trace_scpi("<<", &response).await;
+            responses.push(response.trim().to_string());
+        }
+        drop(stream_guard);
+
+        // One sample for the whole batch (first query + how many rode along)
+        if let Some(first) = commands.first() {
+            let label = match commands.len() {
+                1 => first.clone(),
+                n => format!("{} (+{} pipelined)", first, n - 1),
+            };
+            self.record_latency(&label, started).await;
+        }
+        Ok(responses)
+    }
+
+    // SEND BATCH - Several commands in a single write (no responses expected)
+    async fn send_batch(&self, commands: &[String]) -> Result<(), String> {
+        let mut stream_guard = self.stream.write().await;
+        let stream = stream_guard.as_mut().ok_or("Not connected")?;
+        self.write_batch(stream, commands).await
+    }
+
+    async fn write_batch(&self, stream: &mut DeviceStream, commands: &[String]) -> Result<(), String> {
+        let batch: String = commands.iter().map(|c| format!("{}\n", c)).collect();
+        let written = match timeout(
+            Duration::from_secs(Config::COMMAND_TIMEOUT_SECS),
+            stream.write_all(batch.as_bytes())
+        ).await {
+            Ok(Ok(_)) => stream.flush().await.map_err(|e| format!("Flush failed: {}", e)),
+            Ok(Err(e)) => Err(format!("Write failed: {}", e)),
+            Err(_) => Err("Command timeout".to_string()),
+        };
+        if let Err(e) = written {
+            *self.needs_resync.write().await = true;
+            return Err(e);
+        }
+        for command in commands {
+            self.trace_scpi(">>", command).await;
+            self.record_for_replay(command).await;
+        }
+        Ok(())
+    }
+
+    // REPLAYABLE - Settings only: no queries, watchdog, reset, IP change or master output
+    // (a replay must never put the transmitter on air by itself)
+    fn is_replayable(command: &str) -> bool {
+        !command.contains('?')
+            && !command.starts_with("WATCHDOG:")
+            && !command.starts_with("OUTPUT:STATE")
+            && !command.starts_with(ScpiCommands::SYSTEM_IP)
+            && command != ScpiCommands::RESET
+    }
+
+    async fn record_for_replay(&self, command: &str) {
+        if !Self::is_replayable(command) {
+            return;
+        }
+        let mut history = self.command_history.write().await;
+        if history.len() >= Config::MAX_REPLAY_COMMANDS {
+            history.pop_front();
+        }
+        history.push_back(command.to_string());
+    }
+
+    // REPLAY LAST COMMANDS - Re-send the last `n` recorded settings in order (e.g. after the
+    // device came back from a drop with defaults). Local channel state follows the replay.
+    // Not while on air; the plan the replay leaves must pass the usual channel and budget checks.
+    pub async fn replay_last_commands(&self, n: usize) -> Result<usize, String> {
+        {
+            let state = self.state.read().await;
+            if state.connection != ConnectionState::Connected {
+                return Err("Not connected".to_string());
+            }
+            if state.broadcast.is_broadcasting() {
+                return Err("Cannot replay commands while broadcasting".to_string());
+            }
+        }
+
+        let commands: Vec<String> = {
+            let history = self.command_history.read().await;
+            history.iter().skip(history.len().saturating_sub(n)).cloned().collect()
+        };
+
+        // Validate the resulting plan before anything is sent
+        let mut plan = self.state.read().await.channels.clone();
+        let mut touched = Vec::new();
+        for command in &commands {
+            if let Some(ch) = Self::apply_setting(&mut plan, command) {
+                if !touched.contains(&ch) {
+                    touched.push(ch);
+                }
+            }
+        }
+        for channel in plan.iter().filter(|c| touched.contains(&c.id)) {
+            self.check_channel_setting(channel.id, channel.frequency, channel.enabled, Some(channel.amplitude)).await?;
+        }
+        let total: f32 = plan.iter().filter(|c| c.enabled).map(|c| c.amplitude).sum();
+        let max_total = *self.max_total_amplitude.read().await;
+        if total > max_total + 1e-4 {
+            return Err(format!("Replay would leave total amplitude {:.2} over budget {:.2}", total, max_total));
+        }
+
+        for command in &commands {
+            self.write_command(command).await?;
+            self.apply_replayed(command).await;
+        }
+
+        self.log_info(&format!("Replayed {} commands", commands.len())).await;
+        Ok(commands.len())
+    }
+
+    // Mirror a replayed CHn:FREQ / CHn:OUTPUT / AMP:CHn into local state
+    async fn apply_replayed(&self, command: &str) {
+        let ch = {
+            let mut state = self.state.write().await;
+            let Some(ch) = Self::apply_setting(&mut state.channels, command) else { return };
+            state.end_pilot(ch);
+            ch
+        };
+        self.reconcile_guard.write().await.mark(ch);
+        self.event_bus.emit(EventType::ChannelUpdated(ch));
+    }
+
+    // Apply one replayable channel setting to `channels`; returns the channel it changed
+    fn apply_setting(channels: &mut [Channel], command: &str) -> Option<u8> {
+        let (key, value) = command.split_once(' ')?;
+        let (ch, setting) = if let Some(ch) = key.strip_prefix(ScpiCommands::AMP_PREFIX) {
+            (ch, "AMP")
+        } else {
+            key.strip_prefix(ScpiCommands::FREQ_PREFIX)?.split_once(':')?
+        };
+        let ch = ch.parse::<u8>().ok()?;
+        let channel = channels.iter_mut().find(|c| c.id == ch)?;
+        match setting {
+            "FREQ" => channel.frequency = value.parse().ok()?,
+            "OUTPUT" => channel.enabled = value == "ON",
+            "AMP" => channel.amplitude = value.parse().ok()?,
+            _ => return None,
+        }
+        Some(ch)
+    }
+
+    // POLLING TASK - Runs every 500ms in background
+    fn spawn_poll_task(&self) {
+        let stream = self.stream.clone();
+        let state = self.state.clone();
+        let event_bus = self.event_bus.clone();
+        let is_running = self.is_running.clone();
+        let last_watchdog_reset = self.last_watchdog_reset.clone();
+        let watchdog_command = self.watchdog_command.clone();
+        let audit = self.audit.clone();
+        let watchdog_triggers = self.watchdog_triggers.clone();
+        let temp_thresholds = self.temp_thresholds.clone();
+        let reconcile_guard = self.reconcile_guard.clone();
+        let watchdog_paused = self.watchdog_paused.clone();
+        let poll_tasks = self.poll_tasks.clone();
+        let last_poll_tick = self.last_poll_tick.clone();
+        let poll_interval = self.poll_interval.clone();
+        let ui_visible = self.ui_visible.clone();
+        let license_window = self.license_window.clone();
+        let license_override = self.license_override.clone();
+        let clock_override = self.clock_override.clone();
+        let manager = self.clone();
+
+        tokio::spawn(async move {
+            *poll_tasks.write().await += 1;
+            manager.watchdog_feeds.write().await.clear();  // No interval across a reconnect
+            let mut consecutive_errors = 0u8;
+            let mut connection_lost = false;
+            state.write().await.consecutive_poll_errors = 0;
+            let mut last_status: Option<Instant> = None;
+            let mut status_line = String::with_capacity(Config::READ_BUFFER_BYTES);  // Reused every poll
+
+            loop {
+                // Check if we should stop
+                if !*is_running.read().await {
+                    break;
+                }
+
+                // Sleep between polls
+                let interval = *poll_interval.read().await;
+                sleep(interval).await;
+
+                // Check if we should stop (again, after sleep)
+                if !*is_running.read().await {
+                    break;
+                }
+                *last_poll_tick.write().await = Instant::now();
+
+                // CRITICAL: WATCHDOG RESET
+                // Must send this every poll or FPGA stops output!
+                // (Only skipped while a test-mode watchdog trip is in progress)
+                let feed_watchdog = !*watchdog_paused.read().await;
+                let watchdog_result = if feed_watchdog {
+                    let mut stream_guard = stream.write().await;
+                    if let Some(s) = stream_guard.as_mut() {
+                        let msg = format!("{}\n", watchdog_command.read().await);
+                        s.write_all(msg.as_bytes()).await
+                    } else {
+                        Err(std::io::Error::new(std::io::ErrorKind::NotConnected, "No stream"))
+                    }
+                } else {
+                    Ok(())
+                };
+
+                if let Err(e) = watchdog_result {
+                    consecutive_errors += 1;
+                    state.write().await.consecutive_poll_errors = consecutive_errors;
+
+                    // Log error
+                    audit.record("ERROR",
+                        &format!("Watchdog reset failed: {}", e)).await;
+
+                    // Too many errors - connection lost (handled once this loop has exited,
+                    // since a successful reconnect starts a new one)
+                    if consecutive_errors >= Config::MAX_CONSECUTIVE_ERRORS {
+                        connection_lost = true;
+                        break;
+                    }
+                    continue;
+                }
+
+                // Update watchdog timestamp
+                if feed_watchdog {
+                    *last_watchdog_reset.write().await = Instant::now();
+                    manager.record_watchdog_feed().await;
+                }
+                if consecutive_errors > 0 {
+                    consecutive_errors = 0;
+                    state.write().await.consecutive_poll_errors = 0;
+                }
+
+                // QUERY STATUS (only every HIDDEN_STATUS_INTERVAL_MS while the UI is hidden)
+                let status_due = *ui_visible.read().await
+                    || last_status.is_none_or(|t| t.elapsed() >= Duration::from_millis(Config::HIDDEN_STATUS_INTERVAL_MS));
+                if status_due {
+                    last_status = Some(Instant::now());
+                }
+                let status_result = if !status_due {
+                    None
+                } else {
+                    let mut stream_guard = stream.write().await;
+                    if let Some(s) = stream_guard.as_mut() {
+                        // Send query
+                        let msg = format!("{}\n", ScpiCommands::STATUS);
+                        if s.write_all(msg.as_bytes()).await.is_ok() {
+                            // Read response (one line, however long)
+                            status_line.clear();
+                            match timeout(Duration::from_secs(2), s.read_line(&mut status_line)).await {
+                                Ok(Ok(n)) if n > 0 => Some(status_line.as_str()),
+                                _ => None
+                            }
+                        } else {
+                            None
+                        }
+                    } else {
+                        None
+                    }
+                };
+
+                // Parse status response
+                if let Some(response) = status_result {
+                    let (previous_uptime, was_broadcasting) = {
+                        let s = state.read().await;
+                        (s.uptime_secs, s.broadcast.is_broadcasting())
+                    };
+                    let fault_stop = Self::parse_status_static(
+                        response, &state, &event_bus, &audit, &watchdog_triggers,
+                        &temp_thresholds, &reconcile_guard
+                    ).await;
+
+                    let uptime = state.read().await.uptime_secs;
+                    if let (Some(before), Some(now)) = (previous_uptime, uptime) {
+                        if now < before {
+                            manager.handle_device_reboot(before, now, was_broadcasting).await;
+                        }
+                    }
+                    if fault_stop {
+                        if let Some(s) = stream.write().await.as_mut() {
+                            let msg = format!("{}\n", ScpiCommands::OUTPUT_OFF);
+                            let _ = s.write_all(msg.as_bytes()).await;
+                        }
+                    }
+                }
+
+                // LICENSE WINDOW - closed mid-broadcast forces output off
+                // (unless the broadcast was an emergency override)
+                if let Some(window) = *license_window.read().await {
+                    let now = clock_override.read().await.unwrap_or_else(|| chrono::Local::now().time());
+                    let mut s = state.write().await;
+                    if s.broadcast == BroadcastState::Broadcasting
+                        && !window.contains(now)
+                        && !*license_override.read().await
+                    {
+                        s.set_broadcast(BroadcastState::Idle);
+                        s.last_stop_reason = Some(StopReason::LicenseWindow);
+                        drop(s);
+                        if let Some(s) = stream.write().await.as_mut() {
+                            let msg = format!("{}\n", ScpiCommands::OUTPUT_OFF);
+                            let _ = s.write_all(msg.as_bytes()).await;
+                        }
+                        audit.record("WARNING",
+                            &format!("License window closed at {} - broadcast stopped", now.format("%H:%M"))).await;
+                        event_bus.emit(EventType::BroadcastStopped);
+                    }
+                }
+
+                // Emit state update event
+                event_bus.emit(EventType::DeviceStateUpdated);
+            }
+
+            *poll_tasks.write().await -= 1;
+            if connection_lost {
+                manager.handle_connection_lost().await;
+            }
+        });
+    }
+
+    // WATCHDOG FEED - Interval since the previous reset, kept for WATCHDOG_MARGIN_WINDOW_SECS
+    async fn record_watchdog_feed(&self) {
+        let now = Instant::now();
+        let interval_ms = {
+            let mut feeds = self.watchdog_feeds.write().await;
+            let interval_ms = feeds.back().map_or(0, |(last, _)| now.duration_since(*last).as_millis() as u64);
+            feeds.push_back((now, interval_ms));
+            let window = Duration::from_secs(Config::WATCHDOG_MARGIN_WINDOW_SECS);
+            while feeds.front().is_some_and(|(t, _)| now.duration_since(*t) > window) {
+                feeds.pop_front();
+            }
+            interval_ms
+        };
+
+        let timeout_ms = self.watchdog_timeout.read().await.as_millis() as u64;
+        if interval_ms * 100 >= timeout_ms * Config::WATCHDOG_NEAR_MISS_PERCENT {
+            *self.watchdog_near_miss.write().await = true;
+            self.log_warning(&format!("Watchdog fed {} ms after the previous reset (timeout {} ms)",
+                interval_ms, timeout_ms)).await;
+        }
+    }
+
+    // HANDLE DEVICE REBOOT - Uptime went backwards: the link survived but the device
+    // restarted with output off, so local state is stale. `was_broadcasting` is the state
+    // before this poll (the status line itself may already have reported output off).
+    async fn handle_device_reboot(&self, before: u64, now: u64, was_broadcasting: bool) {
+        let still_broadcasting = {
+            let mut state = self.state.write().await;
+            let still_broadcasting = state.broadcast.is_broadcasting();
+            state.set_broadcast(BroadcastState::Idle);
+            if was_broadcasting {
+                state.last_stop_reason = Some(StopReason::Fault);
+            }
+            still_broadcasting
+        };
+
+        self.log_warning(&format!("Device rebooted (uptime {} s -> {} s) - re-initializing", before, now)).await;
+        self.event_bus.emit(EventType::DeviceRebooted);
+        if still_broadcasting {
+            self.event_bus.emit(EventType::BroadcastStopped);
+        }
+
+        if let Err(e) = self.initialize_device().await {
+            self.log_error(&format!("Re-initialization after reboot failed: {}", e)).await;
+        }
+    }
+
+    // HANDLE CONNECTION LOST - Attempt reconnection
+    async fn handle_connection_lost(&self) {
+        // Update state
+        {
+            let mut s = self.state.write().await;
+            s.connection = ConnectionState::Reconnecting;
+            if s.broadcast.is_broadcasting() {
+                s.last_stop_reason = Some(StopReason::Disconnect);
+            }
+            s.set_broadcast(BroadcastState::Idle);  // Stop broadcast on disconnect
+        }
+
+        self.event_bus.emit(EventType::ConnectionLost);
+        self.event_bus.emit(EventType::ConnectionStateChanged(ConnectionState::Reconnecting));
+
+        // Get connection info
+        let ip = self.current_ip.read().await.clone();
+        let port = *self.current_port.read().await;
+
+        if ip.is_none() || port.is_none() {
+            // No connection info - can't reconnect
+            *self.is_running.write().await = false;
+            self.state.write().await.connection = ConnectionState::Disconnected;
+            self.event_bus.emit(EventType::ConnectionStateChanged(ConnectionState::Disconnected));
+            return;
+        }
+
+        // Last working endpoint first, then the rest of the failover list in order
+        let mut targets = vec![Endpoint::new(&ip.unwrap(), port.unwrap())];
+        for endpoint in self.endpoints.read().await.iter() {
+            if !targets.contains(endpoint) {
+                targets.push(endpoint.clone());
+            }
+        }
+        let target_list: Vec<String> = targets.iter().map(|e| format_endpoint(&e.ip, e.port)).collect();
+        let target_list = target_list.join(" / ");
+
+        // Attempt reconnection
+        for attempt in 1..=Config::MAX_RECONNECT_ATTEMPTS {
+            *self.reconnect_attempts.write().await = attempt;
+            self.reliability.write().await.reconnect_attempts_total += 1;
+
+            self.event_bus.emit(EventType::ReconnectAttempt(attempt));
+
+            emit_log_line(LogTag::Reconnect, &format!("Attempt {}/{} to {}",
+                attempt, Config::MAX_RECONNECT_ATTEMPTS, target_list));
+
+            // Wait before retry
+            sleep(Duration::from_secs(Config::RECONNECT_DELAY_SECS)).await;
+
+            // Try to connect
+            match open_first(&targets).await {
+                Ok((stream, addr, index)) => {
+                    // Success!
+                    emit_log_line(LogTag::Reconnect,
+                        &format!("Success via {}", format_endpoint(&targets[index].ip, targets[index].port)));
+                    *self.current_ip.write().await = Some(targets[index].ip.clone());
+                    *self.current_port.write().await = Some(targets[index].port);
+
+                    // Swap in the new link (the old socket is dead)
+                    if let Err(e) = stream.set_nodelay(true) {
+                        self.log_warning(&format!("Failed to set TCP_NODELAY: {}", e)).await;
+                    }
+                    *self.stream.write().await = Some(BufReader::with_capacity(Config::READ_BUFFER_BYTES, stream));
+                    *self.connected_addr.write().await = Some(addr);
+
+                    {
+                        let mut s = self.state.write().await;
+                        s.connection = ConnectionState::Connected;
+                        s.error_count = 0;
+                    }
+
+                    *self.reconnect_attempts.write().await = 0;
+                    self.reliability.write().await.reconnect_successes += 1;
+
+                    self.event_bus.emit(EventType::ReconnectSuccess);
+                    self.event_bus.emit(EventType::ConnectionStateChanged(ConnectionState::Connected));
+
+                    // Re-read the device (it may be a different unit after failover) and resume polling
+                    if let Err(e) = self.initialize_device().await {
+                        self.log_warning(&format!("Device re-initialization after reconnect failed: {}", e)).await;
+                    }
+                    self.spawn_poll_task();
+
+                    return;
+                }
+                _ => {
+                    emit_log_line(LogTag::Reconnect, &format!("Attempt {} failed", attempt));
+                }
+            }
+        }
+
+        // All attempts failed
+        emit_log_line(LogTag::Reconnect, "All attempts failed, giving up");
+
+        *self.is_running.write().await = false;
+        self.state.write().await.connection = ConnectionState::Disconnected;
+
+        self.event_bus.emit(EventType::ReconnectFailed);
+        self.event_bus.emit(EventType::ConnectionStateChanged(ConnectionState::Disconnected));
+    }
+
+    // PARSE STATUS RESPONSE
+    async fn parse_status_response(&self, response: &str) {
+        let fault_stop = Self::parse_status_static(
+            response, &self.state, &self.event_bus, &self.audit,
+            &self.watchdog_triggers, &self.temp_thresholds, &self.reconcile_guard
+        ).await;
+        if fault_stop {
+            let _ = self.send_command(ScpiCommands::OUTPUT_OFF).await;
+        }
+    }
+
+    // Returns true if a fatal device fault requires output to be forced off
+    async fn parse_status_static(
+        response: &str,
+        state: &Arc<RwLock<DeviceState>>,
+        event_bus: &EventBus,
+        audit: &AuditSink,
+        watchdog_triggers: &Arc<RwLock<VecDeque<u64>>>,
+        temp_thresholds: &Arc<RwLock<TemperatureThresholds>>,
+        reconcile_guard: &Arc<RwLock<ReconcileGuard>>,
+    ) -> bool {
+        // Example response: "BROADCAST:1,WATCHDOG:0,TEMP:45.2,CH1:ON,CH2:OFF,..."
+        let thresholds = *temp_thresholds.read().await;
+        let guard = reconcile_guard.read().await.clone();
+        let mut s = state.write().await;
+        let mut reported_fault: Option<(String, bool)> = None;  // (fault, fatal)
+        let mut device_stopped = false;  // Output went off on the device side
+
+        // Comma-separated; some older boards use ';'
+        for part in response.split([',', ';']) {
+            let kv: Vec<&str> = part.split(':').collect();
+            if kv.len() != 2 {
+                continue;
+            }
+
+            let key = kv[0].trim();
+            let value = kv[1].trim();
+
+            match key {
+                "BROADCAST" | "OUTPUT" => {
+                    let was_broadcasting = s.broadcast == BroadcastState::Broadcasting;
+                    s.set_broadcast(if value == "1" || value == "ON" {
+                        BroadcastState::Broadcasting
+                    } else {
+                        BroadcastState::Idle
+                    });
+
+                    // Emit event if changed
+                    let is_broadcasting = s.broadcast == BroadcastState::Broadcasting;
+                    if was_broadcasting != is_broadcasting {
+                        if is_broadcasting {
+                            event_bus.emit(EventType::BroadcastStarted);
+                        } else {
+                            device_stopped = true;
+                            event_bus.emit(EventType::BroadcastStopped);
+                        }
+                    }
+                }
+                "WATCHDOG" => {
+                    let old_state = s.watchdog;
+                    s.watchdog = match value {
+                        "0" | "OK" => WatchdogState::Ok,
+                        "1" | "WARNING" => WatchdogState::Warning,
+                        "2" | "TRIGGERED" | "FAIL" => WatchdogState::Triggered,
+                        _ => WatchdogState::Ok,
+                    };
+
+                    // Emit event if watchdog triggered
+                    if s.watchdog == WatchdogState::Triggered && old_state != WatchdogState::Triggered {
+                        event_bus.emit(EventType::WatchdogTriggered);
+
+                        let mut triggers = watchdog_triggers.write().await;
+                        triggers.push_back(
+                            std::time::SystemTime::now()
+                                .duration_since(std::time::UNIX_EPOCH)
+                                .unwrap()
+                                .as_millis() as u64
+                        );
+                        if triggers.len() > Config::MAX_WATCHDOG_TRIGGER_HISTORY {
+                            triggers.pop_front();
+                        }
+                        drop(triggers);
+
+                        // Auto-stop broadcast on watchdog trigger
+                        s.set_broadcast(BroadcastState::Idle);
+                        s.last_stop_reason = Some(StopReason::Watchdog);
+                        s.resume_channels = s.channels.clone();
+                        event_bus.emit(EventType::BroadcastStopped);
+                    } else if s.watchdog == WatchdogState::Warning && old_state == WatchdogState::Ok {
+                        event_bus.emit(EventType::WatchdogWarning);
+                    }
+                }
+                "FAULT" | "ERROR" => {
+                    if !matches!(value, "" | "0" | "NONE" | "OK") {
+                        reported_fault = Some((format!("{}:{}", key, value), key == "FAULT"));
+                    }
+                }
+                "TEMP" | "TEMPERATURE" => {
+                    if let Ok(temp) = value.parse::<f32>() {
+                        s.fpga_temperature = Some(temp);
+                        s.session.observe_temperature(temp);
+
+                        // Hysteresis: hovering around one threshold must not flap
+                        if !s.over_temperature && temp >= thresholds.high {
+                            s.over_temperature = true;
+                            audit.record("WARNING",
+                                &format!("FPGA over temperature: {:.1} C", temp)).await;
+                            event_bus.emit(EventType::OverTemperature(temp));
+                        } else if s.over_temperature && temp < thresholds.low {
+                            s.over_temperature = false;
+                            audit.record("INFO",
+                                &format!("FPGA temperature normal: {:.1} C", temp)).await;
+                            event_bus.emit(EventType::TemperatureNormal(temp));
+                        }
+                    }
+                }
+                "UPTIME" => {
+                    if let Ok(uptime) = value.parse::<u64>() {
+                        s.uptime_secs = Some(uptime);
+                    }
+                }
+                "SOURCE" => {
+                    s.set_source(if value == "ADC" {
+                        SourceMode::Adc
+                    } else {
+                        SourceMode::Bram
+                    });
+                }
+                _ => {
+                    // Check for channel status: "CH1", "CH2", etc.
+                    if let Some(stripped) = key.strip_prefix("CH") {
+                        if let Ok(ch_num) = stripped.parse::<u8>() {
+                            if guard.suppresses(ch_num) {
+                                continue;  // Local change still in flight
+                            }
+                            let enabled = value == "1" || value == "ON";
+                            if let Some(channel) = s.channels.iter_mut().find(|c| c.id == ch_num) {
+                                channel.enabled = enabled;
+                            }
+                            if !enabled {
+                                s.end_pilot(ch_num);  // Device has the carrier off
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Device fault - report once per new fault, auto-stop if fatal
+        let mut fault_stop = false;
+        match reported_fault {
+            Some((fault, fatal)) => {
+                let is_new = !s.fault_active || s.last_fault.as_deref() != Some(fault.as_str());
+                s.last_fault = Some(fault.clone());
+                s.fault_active = true;
+
+                if is_new {
+                    s.error_count += 1;
+                    audit.record("ERROR", &format!("Device fault: {}", fault)).await;
+                    event_bus.emit(EventType::DeviceFault(fault));
+
+                    if fatal && s.broadcast.is_broadcasting() {
+                        s.set_broadcast(BroadcastState::Idle);
+                        s.last_stop_reason = Some(StopReason::Fault);
+                        event_bus.emit(EventType::BroadcastStopped);
+                        fault_stop = true;
+                    }
+                }
+            }
+            None => s.fault_active = false,
+        }
+
+        // Output reported off without us asking - attribute it once the whole line is parsed
+        // (TEMP/FAULT may come after BROADCAST). Otherwise another controller stopped it.
+        if device_stopped {
+            s.last_stop_reason = Some(if s.watchdog == WatchdogState::Triggered {
+                StopReason::Watchdog
+            } else if s.over_temperature {
+                StopReason::OverTemp
+            } else if s.fault_active {
+                StopReason::Fault
+            } else {
+                StopReason::Manual
+            });
+        }
+
+        if s.broadcast.is_broadcasting() {
+            let active = s.channels.iter().filter(|c| c.enabled).count();
+            s.session.observe_active_channels(active);
+        }
+
+        // Update timestamp
+        s.last_status_time = Some(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+        );
+
+        fault_stop
+    }
+
+    // START BROADCAST
+    pub async fn start_broadcast(&self) -> Result<(), String> {
+        // Check connection
+        let state = self.state.read().await;
+        if state.connection != ConnectionState::Connected {
+            return Err("Not connected".to_string());
+        }
+        drop(state);
+
+        // Check if any channels are enabled
+        let active_count = {
+            let state = self.state.read().await;
+            state.channels.iter().filter(|c| c.enabled).count()
+        };
+
+        if active_count == 0 {
+            return Err("No active channels".to_string());
+        }
+
+        self.check_license_window().await?;
+
+        // Combined amplitude must not overdrive the transmitter
+        let budget = self.amplitude_budget().await;
+        if !budget.within_budget {
+            return Err(format!(
+                "Total amplitude {:.2} across {} channels exceeds budget {:.2}",
+                budget.total, budget.enabled_channels, budget.max_total
+            ));
+        }
+
+        self.check_output_interval().await?;
+
+        self.log_info(&format!("Starting broadcast on {} channels", active_count)).await;
+
+        // Send command to FPGA
+        self.send_command(ScpiCommands::OUTPUT_ON).await?;
+        self.mark_output_change().await;
+
+        // Starting until the device confirms output is live
+        let previous = {
+            let mut state = self.state.write().await;
+            let previous = state.broadcast;
+            state.set_broadcast(BroadcastState::Starting);
+            previous
+        };
+
+        let confirmed = self.confirm_output(
+            true,
+            Duration::from_millis(Config::OUTPUT_CONFIRM_TIMEOUT_MS)
+        ).await;
+
+        if !confirmed {
+            // Don't leave a half-started carrier - revert
+            let _ = self.send_command(ScpiCommands::OUTPUT_OFF).await;
+            self.state.write().await.set_broadcast(previous);
+            self.log_error("Broadcast start not confirmed by device").await;
+            return Err("Broadcast start not confirmed by device".to_string());
+        }
+
+        // Update state
+        {
+            let mut state = self.state.write().await;
+            let confirmed = state.broadcast.confirm_broadcasting();
+            state.set_broadcast(confirmed);
+        }
+        *self.license_override.write().await = false;
+
+        // Emit event
+        self.event_bus.emit(EventType::BroadcastStarted);
+
+        self.log_info("Broadcast started").await;
+        Ok(())
+    }
+
+    // CONFIRM OUTPUT - Query OUTPUT:STATE? until it matches or the timeout expires
+    async fn confirm_output(&self, on: bool, within: Duration) -> bool {
+        let deadline = Instant::now() + within;
+
+        loop {
+            if let Ok(response) = self.query(ScpiCommands::OUTPUT_STATUS).await {
+                let value = response.trim();
+                let is_on = value == "1" || value.eq_ignore_ascii_case("ON");
+                if is_on == on {
+                    return true;
+                }
+            }
+
+            if Instant::now() >= deadline {
+                return false;
+            }
+            sleep(Duration::from_millis(Config::OUTPUT_CONFIRM_POLL_MS)).await;
+        }
+    }
+
+    // STOP BROADCAST
+    pub async fn stop_broadcast(&self) -> Result<(), String> {
+        self.stop_output(false, StopReason::Manual).await
+    }
+
+    // STOP OUTPUT - `always` bypasses the minimum interval (emergency stop / disconnect)
+    async fn stop_output(&self, always: bool, reason: StopReason) -> Result<(), String> {
+        if !always {
+            self.check_output_interval().await?;
+        }
+
+        self.log_info("Stopping broadcast").await;
+
+        // Send command to FPGA
+        if let Err(e) = self.send_command(ScpiCommands::OUTPUT_OFF).await {
+            self.log_error(&format!("Failed to send stop command: {}", e)).await;
+            // Continue anyway to update local state
+        }
+        self.mark_output_change().await;
+
+        // Update state
+        {
+            let mut state = self.state.write().await;
+            state.set_broadcast(BroadcastState::Idle);
+            state.last_stop_reason = Some(reason);
+        }
+
+        // Emit event
+        self.event_bus.emit(EventType::BroadcastStopped);
+
+        self.log_info("Broadcast stopped").await;
+        Ok(())
+    }
+
+    // SCHEDULE STOP AT - Stop output at a unix time (replaces any earlier schedule).
+    // StopCountdown is emitted each second over the last STOP_COUNTDOWN_SECS.
+    pub async fn schedule_stop_at(&self, unix_timestamp: u64) -> Result<(), String> {
+        let now_ms = Self::unix_millis();
+        let deadline_ms = unix_timestamp.checked_mul(1000)
+            .ok_or_else(|| format!("Scheduled stop time {} out of range", unix_timestamp))?;
+        if deadline_ms <= now_ms {
+            return Err(format!("Scheduled stop time {} is in the past", unix_timestamp));
+        }
+
+        let generation = {
+            let mut generation = self.stop_schedule_generation.write().await;
+            *generation += 1;
+            *generation
+        };
+        self.state.write().await.scheduled_stop_at = Some(unix_timestamp);
+        self.log_info(&format!("Broadcast stop scheduled in {}s", (deadline_ms - now_ms) / 1000)).await;
+
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let mut last_announced = None;
+            loop {
+                if *manager.stop_schedule_generation.read().await != generation {
+                    return;
+                }
+                let now_ms = Self::unix_millis();
+                if now_ms >= deadline_ms {
+                    break;
+                }
+                let remaining_secs = (deadline_ms - now_ms).div_ceil(1000);
+                if remaining_secs <= Config::STOP_COUNTDOWN_SECS && last_announced != Some(remaining_secs) {
+                    manager.event_bus.emit(EventType::StopCountdown(remaining_secs));
+                    last_announced = Some(remaining_secs);
+                }
+                sleep(Duration::from_millis((deadline_ms - now_ms).min(Config::ABORT_CHECK_MS))).await;
+            }
+
+            manager.state.write().await.scheduled_stop_at = None;
+            if manager.state.read().await.broadcast.is_broadcasting() {
+                manager.log_info("Scheduled stop time reached").await;
+                let _ = manager.stop_output(true, StopReason::Scheduled).await;
+            }
+        });
+        Ok(())
+    }
+
+    // CANCEL SCHEDULED STOP - Returns false if nothing was scheduled
+    pub async fn cancel_scheduled_stop(&self) -> bool {
+        *self.stop_schedule_generation.write().await += 1;
+        let cancelled = self.state.write().await.scheduled_stop_at.take().is_some();
+        if cancelled {
+            self.log_info("Scheduled stop cancelled").await;
+        }
+        cancelled
+    }
+
+    fn unix_millis() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64
+    }
+
+    // RESUME AFTER WATCHDOG - Restore the channel set from the watchdog stop and restart
+    // Only once the watchdog has cleared; any other stop needs the normal arm/start path
+    pub async fn resume_after_watchdog(&self) -> Result<(), String> {
+        let channels = {
+            let state = self.state.read().await;
+            if state.connection != ConnectionState::Connected {
+                return Err("Not connected".to_string());
+            }
+            if state.last_stop_reason != Some(StopReason::Watchdog) {
+                return Err("Last stop was not a watchdog trigger".to_string());
+            }
+            if state.watchdog != WatchdogState::Ok {
+                return Err(format!("Watchdog is still {}", state.watchdog.display()));
+            }
+            if state.broadcast.is_broadcasting() {
+                return Err("Already broadcasting".to_string());
+            }
+            state.resume_channels.clone()
+        };
+
+        self.log_info("Resuming broadcast after watchdog stop").await;
+        for channel in channels {
+            self.set_channel(channel.id, channel.frequency, channel.enabled, Some(channel.amplitude), false).await?;
+        }
+
+        self.start_broadcast().await
+    }
+
+    // SAFE SHUTDOWN - Output off (confirmed), all channels off, then disconnect
+    // If the stop can't be confirmed the link is kept so the operator can retry
+    pub async fn safe_shutdown(&self) -> Result<(), String> {
+        if self.state.read().await.connection != ConnectionState::Connected {
+            return Err("Not connected".to_string());
+        }
+
+        self.log_info("Safe shutdown: stopping output").await;
+        if self.state.read().await.broadcast.is_broadcasting() {
+            self.stop_output(true, StopReason::Manual).await?;
+        } else {
+            // Not broadcasting as far as we know - make sure anyway
+            self.send_command(ScpiCommands::OUTPUT_OFF).await?;
+        }
+
+        let confirmed = self.confirm_output(
+            false,
+            Duration::from_millis(Config::OUTPUT_CONFIRM_TIMEOUT_MS)
+        ).await;
+        if !confirmed {
+            let msg = "Safe shutdown aborted: output stop not confirmed - carrier may still be live";
+            self.log_error(msg).await;
+            return Err(msg.to_string());
+        }
+
+        self.log_info("Safe shutdown: disabling all channels").await;
+        let channels = self.state.read().await.channels.clone();
+        for channel in channels {
+            self.set_channel(channel.id, channel.frequency, false, None, false).await?;
+        }
+
+        self.log_info("Safe shutdown: disconnecting").await;
+        self.disconnect(true).await
+    }
+
+    // AUTO OFFSET - `freq` if it's clear of every other enabled channel, else the nearest
+    // free in-band grid slot within MAX_AUTO_OFFSET_HZ (upward wins a tie). Nothing is applied.
+    pub async fn auto_offset_frequency(&self, ch: u8, freq: u32) -> Result<u32, String> {
+        let Some((other, _)) = self.nearest_collision(ch, freq).await else {
+            return Ok(freq);
+        };
+
+        let grid = Config::AUTO_OFFSET_GRID_HZ;
+        let base = (freq + grid / 2) / grid * grid;
+        for step in 1..=Config::MAX_AUTO_OFFSET_HZ / grid {
+            let candidates = [base.checked_add(step * grid), base.checked_sub(step * grid)];
+            for candidate in candidates.into_iter().flatten() {
+                if candidate.abs_diff(freq) > Config::MAX_AUTO_OFFSET_HZ
+                    || self.check_channel_setting(ch, candidate, false, None).await.is_err()
+                    || self.nearest_collision(ch, candidate).await.is_some()
+                {
+                    continue;
+                }
+                self.log_warning(&format!("CH{} at {} Hz collides with CH{} - offset to {} Hz",
+                    ch, freq, other, candidate)).await;
+                return Ok(candidate);
+            }
+        }
+        Err(format!("CH{} at {} Hz collides with CH{} and no free slot within {} Hz",
+            ch, freq, other, Config::MAX_AUTO_OFFSET_HZ))
+    }
+
+    // CHECK CHANNEL SETTING - Limits shared by every path that changes a channel
+    async fn check_channel_setting(&self, ch: u8, freq: u32, enabled: bool, amplitude: Option<f32>) -> Result<(), String> {
+        if !(1..=12).contains(&ch) {
+            return Err(format!("Invalid channel: {}", ch));
+        }
+
+        if !(Config::MIN_FREQUENCY..=Config::MAX_FREQUENCY).contains(&freq) {
+            return Err(format!("Frequency {} out of range ({}-{})",
+                freq, Config::MIN_FREQUENCY, Config::MAX_FREQUENCY));
+        }
+
+        if let Some(bounds) = self.channel_bounds.read().await.get(&ch) {
+            if !bounds.contains(freq) {
+                return Err(format!("Frequency {} outside CH{} limits ({}-{})",
+                    freq, ch, bounds.min, bounds.max));
+            }
+        }
+
+        if let Some(amplitude) = amplitude {
+            if !(0.0..=1.0).contains(&amplitude) {
+                return Err(format!("Amplitude {} out of range (0.0-1.0)", amplitude));
+            }
+        }
+
+        if enabled && self.state.read().await.source == SourceMode::Adc {
+            self.check_adc_capable(&[ch]).await?;
+        }
+        Ok(())
+    }
+
+    // SET CHANNEL
+    // `amplitude: None` keeps the current level (a silent channel being enabled gets the default)
+    // `verify` reads both settings back from the device before accepting them
+    pub async fn set_channel(
+        &self,
+        ch: u8,
+        freq: u32,
+        enabled: bool,
+        amplitude: Option<f32>,
+        verify: bool,
+    ) -> Result<(), String> {
+        self.check_channel_setting(ch, freq, enabled, amplitude).await?;
+
+        // A channel set normally is no longer a pilot - back to its own level
+        let amplitude = match amplitude {
+            Some(amplitude) => Some(amplitude),
+            None => self.state.read().await.pilot_saved_amplitudes.get(&ch).copied(),
+        };
+
+        // Enabling a channel left at 0 (e.g. interrupted mute) would go live silent
+        let amplitude = match amplitude {
+            Some(amplitude) => Some(amplitude),
+            None if enabled => {
+                let state = self.state.read().await;
+                let silent = state.channels.iter().any(|c| c.id == ch && c.amplitude == 0.0);
+                if silent && !state.muted {
+                    drop(state);
+                    let default = *self.default_amplitude.read().await;
+                    self.log_warning(&format!("CH{} enabled at zero amplitude - raised to {}", ch, default)).await;
+                    Some(default)
+                } else {
+                    None
+                }
+            }
+            None => None,
+        };
+
+        // Set frequency
+        let freq_cmd = format!("CH{}:FREQ {}", ch, freq);
+        self.send_command(&freq_cmd).await?;
+
+        // Set amplitude before the channel goes live
+        if let Some(amplitude) = amplitude {
+            self.send_command(&format!("{}{} {}", ScpiCommands::AMP_PREFIX, ch, amplitude)).await?;
+        }
+
+        // Set enabled state
+        let state_cmd = format!("CH{}:OUTPUT {}", ch, if enabled { "ON" } else { "OFF" });
+        self.send_command(&state_cmd).await?;
+
+        if verify {
+            if let Err(e) = self.verify_channel(ch, freq, enabled).await {
+                self.log_error(&e).await;
+                return Err(e);
+            }
+        }
+
+        // Update local state
+        {
+            let mut state = self.state.write().await;
+            if let Some(channel) = state.channels.iter_mut().find(|c| c.id == ch) {
+                channel.frequency = freq;
+                channel.enabled = enabled;
+                if let Some(amplitude) = amplitude {
+                    channel.amplitude = amplitude;
+                }
+            }
+            state.end_pilot(ch);
+        }
+
+        self.reconcile_guard.write().await.mark(ch);
+
+        // Emit event
+        self.event_bus.emit(EventType::ChannelUpdated(ch));
+
+        self.log_info(&format!("CH{} set to {} Hz, enabled={}", ch, freq, enabled)).await;
+        Ok(())
+    }
+
+    // SET CHANNEL (AUTO OFFSET) - As set_channel, but an enabled channel colliding with
+    // another enabled channel is moved to the nearest free slot. Returns the frequency applied.
+    pub async fn set_channel_auto_offset(
+        &self,
+        ch: u8,
+        freq: u32,
+        enabled: bool,
+        amplitude: Option<f32>,
+        verify: bool,
+    ) -> Result<u32, String> {
+        let freq = if enabled { self.auto_offset_frequency(ch, freq).await? } else { freq };
+        self.set_channel(ch, freq, enabled, amplitude, verify).await?;
+        Ok(freq)
+    }
+
+    // VERIFY CHANNEL - Round-trip FREQ:CHn? / OUTPUT:CHn? against what was sent
+    async fn verify_channel(&self, ch: u8, freq: u32, enabled: bool) -> Result<(), String> {
+        let response = self.query(&format!("{}{}?", ScpiCommands::FREQ_QUERY_PREFIX, ch)).await?;
+        let reported = response.trim();
+        if reported.parse::<u32>().ok() != Some(freq) {
+            return Err(format!("CH{} verify failed: frequency {} sent, device reports '{}'", ch, freq, reported));
+        }
+
+        let response = self.query(&format!("{}{}?", ScpiCommands::OUTPUT_QUERY_PREFIX, ch)).await?;
+        let reported = response.trim();
+        let reported_enabled = match reported.to_uppercase().as_str() {
+            "ON" | "1" => Some(true),
+            "OFF" | "0" => Some(false),
+            _ => None,
+        };
+        if reported_enabled != Some(enabled) {
+            return Err(format!(
+                "CH{} verify failed: output {} sent, device reports '{}'",
+                ch, if enabled { "ON" } else { "OFF" }, reported
+            ));
+        }
+
+        Ok(())
+    }
+
+    // VERIFY PLAN - Read back every channel's frequency and output state (pipelined)
+    // Returns one description per mismatching channel; empty = device matches
+    pub async fn verify_plan(&self) -> Result<Vec<(u8, String)>, String> {
+        if self.state.read().await.connection != ConnectionState::Connected {
+            return Err("Not connected".to_string());
+        }
+
+        let channels = self.state.read().await.channels.clone();
+        let queries: Vec<String> = channels.iter()
+            .flat_map(|c| [
+                format!("{}{}?", ScpiCommands::FREQ_QUERY_PREFIX, c.id),
+                format!("{}{}?", ScpiCommands::OUTPUT_QUERY_PREFIX, c.id),
+            ])
+            .collect();
+        let responses = self.query_pipelined(&queries).await?;
+
+        let mut mismatches = Vec::new();
+        for (channel, reported) in channels.iter().zip(responses.chunks(2)) {
+            let mut problems = Vec::new();
+            if reported[0].parse::<u32>().ok() != Some(channel.frequency) {
+                problems.push(format!("frequency {} local, device reports '{}'", channel.frequency, reported[0]));
+            }
+            let reported_enabled = match reported[1].to_uppercase().as_str() {
+                "ON" | "1" => Some(true),
+                "OFF" | "0" => Some(false),
+                _ => None,
+            };
+            if reported_enabled != Some(channel.enabled) {
+                problems.push(format!(
+                    "output {} local, device reports '{}'",
+                    if channel.enabled { "ON" } else { "OFF" }, reported[1]
+                ));
+            }
+            if !problems.is_empty() {
+                mismatches.push((channel.id, problems.join("; ")));
+            }
+        }
+
+        if mismatches.is_empty() {
+            self.log_info("Channel plan verified against device").await;
+        } else {
+            self.log_warning(&format!("Channel plan mismatch on {} channels", mismatches.len())).await;
+        }
+        Ok(mismatches)
+    }
+
+    // SET AMPLITUDE (device + local state)
+    async fn set_amplitude(&self, ch: u8, amplitude: f32) -> Result<(), String> {
+        self.send_command(&format!("{}{} {}", ScpiCommands::AMP_PREFIX, ch, amplitude)).await?;
+        if let Some(channel) = self.state.write().await.channels.iter_mut().find(|c| c.id == ch) {
+            channel.amplitude = amplitude;
+        }
+        Ok(())
+    }
+
+    // POWER-ON SELF TEST - Each channel in turn: tune to POST_FREQUENCY at POST_AMPLITUDE,
+    // enable, read both back, disable. Every channel ends disabled, previously enabled
+    // ones included; frequencies and levels are put back. Not while on air.
+    pub async fn power_on_self_test(&self) -> Result<Vec<PostResult>, String> {
+        if self.state.read().await.broadcast.is_broadcasting() {
+            return Err("Cannot run self test while broadcasting".to_string());
+        }
+        self.log_info("Running power-on self test").await;
+
+        let channels = self.state.read().await.channels.clone();
+        let mut results = Vec::new();
+        for channel in &channels {
+            let ch = channel.id;
+            let outcome = async {
+                self.send_command(&format!("CH{}:FREQ {}", ch, Config::POST_FREQUENCY)).await?;
+                self.send_command(&format!("{}{} {}", ScpiCommands::AMP_PREFIX, ch, Config::POST_AMPLITUDE)).await?;
+                self.send_command(&format!("CH{}:OUTPUT ON", ch)).await?;
+                self.verify_channel(ch, Config::POST_FREQUENCY, true).await
+            }.await;
+
+            // Off and restored whatever happened above
+            let cleanup = self.send_batch(&[
+                format!("CH{}:OUTPUT OFF", ch),
+                format!("CH{}:FREQ {}", ch, channel.frequency),
+                format!("{}{} {}", ScpiCommands::AMP_PREFIX, ch, channel.amplitude),
+            ]).await;
+
+            let result = match outcome.and(cleanup) {
+                Ok(()) => PostResult { channel: ch, passed: true, detail: "OK".to_string() },
+                Err(e) => PostResult { channel: ch, passed: false, detail: e },
+            };
+            results.push(result);
+        }
+
+        {
+            let mut state = self.state.write().await;
+            for channel in state.channels.iter_mut() {
+                channel.enabled = false;
+                channel.pilot = false;
+            }
+            state.pilot_saved_amplitudes.clear();
+        }
+        for channel in &channels {
+            self.reconcile_guard.write().await.mark(channel.id);
+            self.event_bus.emit(EventType::ChannelUpdated(channel.id));
+        }
+
+        let failed: Vec<String> = results.iter().filter(|r| !r.passed).map(|r| format!("CH{}", r.channel)).collect();
+        if failed.is_empty() {
+            self.log_info(&format!("Self test passed ({} channels)", results.len())).await;
+        } else {
+            self.log_error(&format!("Self test failed: {}", failed.join(", "))).await;
+        }
+        Ok(results)
+    }
+
+    // BURN IN - Output on for `on_ms`, off for `off_ms`, `cycles` times with the current plan.
+    // Both periods must cover the minimum output interval. A watchdog trip, device fault,
+    // dropped output or abort ends the run at that cycle; output is always left off.
+    pub async fn burn_in(&self, on_ms: u64, off_ms: u64, cycles: u32) -> Result<Vec<BurnInCycle>, String> {
+        if cycles == 0 || cycles > Config::MAX_BURN_IN_CYCLES {
+            return Err(format!("Burn-in cycles must be 1-{}", Config::MAX_BURN_IN_CYCLES));
+        }
+        if on_ms.max(off_ms) > Config::MAX_BURN_IN_PERIOD_MS {
+            return Err(format!("Burn-in period too long (max {} ms)", Config::MAX_BURN_IN_PERIOD_MS));
+        }
+        let min_interval = self.min_output_interval.read().await.as_millis() as u64;
+        if on_ms.min(off_ms) < min_interval {
+            return Err(format!(
+                "Burn-in on/off periods must be at least the {} ms minimum output interval", min_interval
+            ));
+        }
+        {
+            let state = self.state.read().await;
+            if state.connection != ConnectionState::Connected {
+                return Err("Not connected".to_string());
+            }
+            if state.broadcast.is_broadcasting() {
+                return Err("Cannot start burn-in while broadcasting".to_string());
+            }
+        }
+
+        *self.burn_in_abort.write().await = false;
+        self.log_info(&format!("Burn-in: {} cycles, {} ms on / {} ms off", cycles, on_ms, off_ms)).await;
+
+        let last_trigger = self.watchdog_triggers.read().await.back().copied();
+        let mut results = Vec::new();
+        for cycle in 1..=cycles {
+            let started = Instant::now();
+            let outcome = async {
+                self.start_broadcast().await?;
+                self.burn_in_hold(Duration::from_millis(on_ms), true, last_trigger).await?;
+                self.stop_output(false, StopReason::Manual).await?;
+                self.burn_in_hold(Duration::from_millis(off_ms), false, last_trigger).await
+            }.await;
+
+            let passed = outcome.is_ok();
+            let detail = outcome.err().unwrap_or_else(|| "OK".to_string());
+            if passed {
+                self.log_info(&format!("Burn-in cycle {}/{} passed", cycle, cycles)).await;
+            } else {
+                self.log_warning(&format!("Burn-in cycle {}/{} failed: {}", cycle, cycles, detail)).await;
+            }
+            results.push(BurnInCycle { cycle, passed, detail, elapsed_ms: started.elapsed().as_millis() as u64 });
+            if !passed {
+                break;
+            }
+        }
+
+        if self.state.read().await.broadcast.is_broadcasting() {
+            self.stop_output(true, StopReason::Manual).await?;
+        }
+        let passed = results.iter().filter(|r| r.passed).count();
+        if passed == cycles as usize {
+            self.log_info(&format!("Burn-in complete ({} cycles)", cycles)).await;
+        } else {
+            self.log_error(&format!("Burn-in stopped after {} of {} cycles", passed, cycles)).await;
+        }
+        Ok(results)
+    }
+
+    // Wait out one burn-in period, failing early on abort, a new watchdog trip, a device
+    // fault or (while `on`) output that is no longer live
+    async fn burn_in_hold(&self, duration: Duration, on: bool, last_trigger: Option<u64>) -> Result<(), String> {
+        let deadline = Instant::now() + duration;
+        loop {
+            if *self.burn_in_abort.read().await {
+                return Err("Burn-in aborted".to_string());
+            }
+            if self.watchdog_triggers.read().await.back().copied() != last_trigger {
+                return Err("Watchdog triggered".to_string());
+            }
+            {
+                let state = self.state.read().await;
+                if state.watchdog == WatchdogState::Triggered {
+                    return Err("Watchdog triggered".to_string());
+                }
+                if state.fault_active {
+                    return Err(format!("Device fault: {}", state.last_fault.as_deref().unwrap_or("unknown")));
+                }
+                if on && !state.broadcast.is_broadcasting() {
+                    return Err("Output dropped".to_string());
+                }
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(());
+            }
+            sleep((deadline - now).min(Duration::from_millis(Config::ABORT_CHECK_MS))).await;
+        }
+    }
+
+    // ABORT BURN IN - The running cycle fails and output is switched off
+    pub async fn abort_burn_in(&self) {
+        *self.burn_in_abort.write().await = true;
+    }
+
+    // MUTE ALL - Amplitudes to 0 on every channel (output state untouched)
+    pub async fn mute_all(&self) -> Result<(), String> {
+        let saved: Vec<(u8, f32)> = {
+            let state = self.state.read().await;
+            if state.muted {
+                return Err("Already muted".to_string());
+            }
+            state.channels.iter().map(|c| (c.id, c.amplitude)).collect()
+        };
+
+        // Save first so a partial failure can still be unmuted
+        *self.muted_amplitudes.write().await = saved.clone();
+        self.state.write().await.muted = true;
+
+        for (ch, _) in &saved {
+            self.set_amplitude(*ch, 0.0).await?;
+        }
+
+        self.event_bus.emit(EventType::DeviceStateUpdated);
+        self.log_info("All channels muted").await;
+        Ok(())
+    }
+
+    // UNMUTE ALL - Restore the amplitudes saved by mute_all
+    pub async fn unmute_all(&self) -> Result<(), String> {
+        if !self.state.read().await.muted {
+            return Err("Not muted".to_string());
+        }
+
+        let saved = self.muted_amplitudes.read().await.clone();
+        for (ch, amplitude) in saved {
+            self.set_amplitude(ch, amplitude).await?;
+        }
+
+        self.muted_amplitudes.write().await.clear();
+        self.state.write().await.muted = false;
+
+        self.event_bus.emit(EventType::DeviceStateUpdated);
+        self.log_info("All channels unmuted").await;
+        Ok(())
+    }
+
+    // SET PILOT - Carrier-only channel for frequency coordination: output on at the pilot
+    // amplitude and flagged in state. The channel's own level is restored when the pilot
+    // ends (`on: false` switches it off) or the channel is next set normally.
+    pub async fn set_pilot(&self, ch: u8, on: bool) -> Result<(), String> {
+        let channel = self.state.read().await.channels.iter()
+            .find(|c| c.id == ch)
+            .cloned()
+            .ok_or_else(|| format!("Invalid channel: {}", ch))?;
+        let saved = self.state.read().await.pilot_saved_amplitudes.get(&ch).copied();
+
+        if on {
+            let level = *self.pilot_amplitude.read().await;
+            let normal = saved.unwrap_or(channel.amplitude);
+            self.set_channel(ch, channel.frequency, true, Some(level), false).await?;
+            {
+                let mut state = self.state.write().await;
+                state.pilot_saved_amplitudes.insert(ch, normal);
+                if let Some(channel) = state.channels.iter_mut().find(|c| c.id == ch) {
+                    channel.pilot = true;
+                }
+            }
+            self.event_bus.emit(EventType::ChannelUpdated(ch));
+            self.log_info(&format!("CH{} pilot carrier at {} Hz, amplitude {}", ch, channel.frequency, level)).await;
+        } else {
+            if !channel.pilot {
+                return Err(format!("CH{} is not a pilot", ch));
+            }
+            self.set_channel(ch, channel.frequency, false, saved, false).await?;
+            self.log_info(&format!("CH{} pilot ended", ch)).await;
+        }
+        Ok(())
+    }
+
+    // SET SOURCE MODE
+    pub async fn set_source(&self, source: SourceMode) -> Result<(), String> {
+        if source == SourceMode::Adc {
+            self.check_adc_capable(&self.enabled_channel_ids().await).await?;
+        }
+
+        let cmd = format!("{} {}",
+            ScpiCommands::SOURCE_MODE,
+            match source {
+                SourceMode::Bram => "BRAM",
+                SourceMode::Adc => "ADC",
+            }
+        );
+
+        self.send_command(&cmd).await?;
+
+        // Update local state
+        self.state.write().await.set_source(source);
+
+        // Emit event
+        self.event_bus.emit(EventType::SourceChanged(source));
+
+        Ok(())
+    }
+
+    // SWITCH SOURCE LIVE - Change source mid-broadcast without touching output state
+    // (optionally ramping enabled channels down/up around the switch to avoid a glitch)
+    pub async fn switch_source_live(&self, source: SourceMode, ramp: bool) -> Result<(), String> {
+        if self.state.read().await.connection != ConnectionState::Connected {
+            return Err("Not connected".to_string());
+        }
+        if source == SourceMode::Adc {
+            self.check_adc_capable(&self.enabled_channel_ids().await).await?;
+        }
+
+        let levels: Vec<(u8, f32)> = if ramp {
+            let state = self.state.read().await;
+            state.channels.iter().filter(|c| c.enabled).map(|c| (c.id, c.amplitude)).collect()
+        } else {
+            Vec::new()
+        };
+
+        let result = async {
+            self.ramp_amplitudes(&levels, true).await?;
+            self.send_command(&format!("{} {}", ScpiCommands::SOURCE_MODE, source.as_str())).await?;
+            let reported = self.query(ScpiCommands::SOURCE_STATUS).await?;
+            if reported.trim().to_uppercase() != source.as_str() {
+                return Err(format!("Device rejected source {} (reports '{}')", source.as_str(), reported.trim()));
+            }
+            Ok(())
+        }.await;
+
+        // Restore levels whether or not the ramp-down and switch took; the first error wins
+        let restored = self.ramp_amplitudes(&levels, false).await;
+        if let Err(e) = result {
+            if let Err(restore_error) = restored {
+                self.log_error(&format!("Failed to restore levels after source switch: {}", restore_error)).await;
+            }
+            self.log_error(&e).await;
+            return Err(e);
+        }
+        if let Err(e) = restored {
+            self.log_error(&format!("Failed to restore levels after source switch: {}", e)).await;
+            return Err(e);
+        }
+
+        self.state.write().await.set_source(source);
+        self.event_bus.emit(EventType::SourceChanged(source));
+        self.log_info(&format!("Source switched live to {}", source.as_str())).await;
+        Ok(())
+    }
+
+    // Step each (channel, level) from full to 0 (`down`) or from 0 back to full
+    async fn ramp_amplitudes(&self, levels: &[(u8, f32)], down: bool) -> Result<(), String> {
+        if levels.is_empty() {
+            return Ok(());
+        }
+
+        for step in 1..=Config::SOURCE_RAMP_STEPS {
+            let fraction = step as f32 / Config::SOURCE_RAMP_STEPS as f32;
+            let scale = if down { 1.0 - fraction } else { fraction };
+            for &(ch, level) in levels {
+                self.set_amplitude(ch, level * scale).await?;
+            }
+            sleep(Duration::from_millis(Config::SOURCE_RAMP_STEP_MS)).await;
+        }
+        Ok(())
+    }
+
+    // CAPTURE SCENE - Snapshot of the current channel plan and source
+    pub async fn capture_scene(&self, name: &str) -> Scene {
+        let state = self.state.read().await;
+        Scene {
+            name: name.to_string(),
+            source: state.source,
+            channels: state.channels.clone(),
+        }
+    }
+
+    // APPLY SCENE
+    pub async fn apply_scene(&self, scene: &Scene) -> Result<(), String> {
+        self.set_source(scene.source).await?;
+        for channel in &scene.channels {
+            self.set_channel(channel.id, channel.frequency, channel.enabled, Some(channel.amplitude), false).await?;
+        }
+        self.log_info(&format!("Applied scene '{}'", scene.name)).await;
+        Ok(())
+    }
+
+    // SWAP PLAN - Move to `new_plan` sending only what differs, in one write: disables,
+    // then retunes/level changes, then enables. Channels left out of the plan are disabled;
+    // unchanged channels are not touched, so carriers common to both plans stay up.
+    // Returns the channels that changed.
+    pub async fn swap_plan(&self, new_plan: Vec<Channel>) -> Result<Vec<u8>, String> {
+        let mut ids: Vec<u8> = new_plan.iter().map(|c| c.id).collect();
+        ids.sort();
+        if ids.windows(2).any(|w| w[0] == w[1]) {
+            return Err("Duplicate channel in plan".to_string());
+        }
+        for channel in &new_plan {
+            self.check_channel_setting(channel.id, channel.frequency, channel.enabled, Some(channel.amplitude)).await?;
+        }
+
+        let current = self.state.read().await.channels.clone();
+        let target: Vec<Channel> = current.iter().map(|c| {
+            new_plan.iter().find(|n| n.id == c.id).cloned()
+                .unwrap_or_else(|| Channel { enabled: false, ..c.clone() })
+        }).collect();
+
+        // A live broadcast must stay inside the amplitude budget throughout
+        if self.state.read().await.broadcast.is_broadcasting() {
+            let total: f32 = target.iter().filter(|c| c.enabled).map(|c| c.amplitude).sum();
+            let max_total = *self.max_total_amplitude.read().await;
+            if total > max_total + 1e-4 {
+                return Err(format!("New plan total amplitude {:.2} exceeds budget {:.2}", total, max_total));
+            }
+        }
+
+        let (mut disables, mut updates, mut enables) = (Vec::new(), Vec::new(), Vec::new());
+        let mut changed = Vec::new();
+        for (old, new) in current.iter().zip(&target) {
+            let mut settings = Vec::new();
+            if old.frequency != new.frequency {
+                settings.push(format!("CH{}:FREQ {}", new.id, new.frequency));
+            }
+            if old.amplitude != new.amplitude {
+                settings.push(format!("{}{} {}", ScpiCommands::AMP_PREFIX, new.id, new.amplitude));
+            }
+            if old.phase != new.phase {
+                settings.push(format!("{}{} {}", ScpiCommands::PHASE_PREFIX, new.id, new.phase));
+            }
+            match (old.enabled, new.enabled) {
+                (true, false) => {
+                    disables.push(format!("CH{}:OUTPUT OFF", new.id));
+                    updates.extend(settings);
+                }
+                (false, true) => {
+                    // Tune and level before the carrier comes up
+                    enables.extend(settings);
+                    enables.push(format!("CH{}:OUTPUT ON", new.id));
+                }
+                _ if settings.is_empty() => continue,
+                _ => updates.extend(settings),
+            }
+            changed.push(new.id);
+        }
+
+        if changed.is_empty() {
+            return Ok(changed);
+        }
+
+        let batch: Vec<String> = disables.into_iter().chain(updates).chain(enables).collect();
+        self.send_batch(&batch).await?;
+
+        {
+            let mut state = self.state.write().await;
+            for channel in state.channels.iter_mut() {
+                if let Some(new) = target.iter().find(|t| t.id == channel.id && changed.contains(&t.id)) {
+                    channel.frequency = new.frequency;
+                    channel.amplitude = new.amplitude;
+                    channel.phase = new.phase;
+                    channel.enabled = new.enabled;
+                }
+            }
+            for &ch in &changed {
+                state.end_pilot(ch);
+            }
+        }
+        for &ch in &changed {
+            self.reconcile_guard.write().await.mark(ch);
+            self.event_bus.emit(EventType::ChannelUpdated(ch));
+        }
+
+        self.log_info(&format!("Swapped channel plan: {} channels changed ({} commands)", changed.len(), batch.len())).await;
+        Ok(changed)
+    }
+
+    // QUEUE CHANNEL CHANGE - Stage an edit for flush_pending_changes; a second change to
+    // the same channel is merged into the first. Returns the queue length.
+    pub async fn queue_channel_change(&self, change: ChannelChange) -> Result<usize, String> {
+        if !(1..=12).contains(&change.channel_id) {
+            return Err(format!("Invalid channel: {}", change.channel_id));
+        }
+        if let Some(freq) = change.frequency {
+            self.check_channel_setting(change.channel_id, freq, false, None).await?;
+        }
+
+        let mut pending = self.pending_changes.write().await;
+        match pending.iter_mut().find(|p| p.channel_id == change.channel_id) {
+            Some(queued) => {
+                queued.frequency = change.frequency.or(queued.frequency);
+                queued.enabled = change.enabled.or(queued.enabled);
+            }
+            None => pending.push(change),
+        }
+        Ok(pending.len())
+    }
+
+    // PENDING CHANGES - What flush_pending_changes would apply, in queue order
+    pub async fn pending_changes(&self) -> Vec<ChannelChange> {
+        self.pending_changes.read().await.clone()
+    }
+
+    // CLEAR PENDING CHANGES - Discard the queue without sending; returns how many were dropped
+    pub async fn clear_pending_changes(&self) -> usize {
+        let mut pending = self.pending_changes.write().await;
+        let count = pending.len();
+        pending.clear();
+        count
+    }
+
+    // FLUSH PENDING CHANGES - Apply the whole queue as one plan swap; the queue is kept if
+    // the swap is refused
+    pub async fn flush_pending_changes(&self) -> Result<Vec<u8>, String> {
+        let pending = self.pending_changes().await;
+        let mut plan = self.state.read().await.channels.clone();
+        for change in pending {
+            if let Some(channel) = plan.iter_mut().find(|c| c.id == change.channel_id) {
+                channel.frequency = change.frequency.unwrap_or(channel.frequency);
+                channel.enabled = change.enabled.unwrap_or(channel.enabled);
+            }
+        }
+
+        let changed = self.swap_plan(plan).await?;
+        self.pending_changes.write().await.clear();
+        Ok(changed)
+    }
+
+    // STATIONS - Replace the call-sign table
+    pub async fn set_stations(&self, table: HashMap<String, u32>) {
+        let count = table.len();
+        *self.stations.write().await = table;
+        self.log_info(&format!("Loaded {} stations", count)).await;
+    }
+
+    // LIST STATIONS - Sorted by frequency
+    pub async fn list_stations(&self) -> Vec<(String, u32)> {
+        let mut list: Vec<(String, u32)> = self.stations.read().await
+            .iter()
+            .map(|(call_sign, &freq)| (call_sign.clone(), freq))
+            .collect();
+        list.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        list
+    }
+
+    // TUNE BY STATION - Look up the call sign and apply its frequency (enable state unchanged)
+    pub async fn tune_by_station(&self, ch: u8, call_sign: &str) -> Result<u32, String> {
+        let freq = self.stations.read().await
+            .get(&stations::normalize(call_sign))
+            .copied()
+            .ok_or_else(|| format!("Unknown station: '{}'", call_sign.trim()))?;
+
+        let enabled = self.state.read().await.channels.iter()
+            .find(|c| c.id == ch)
+            .map(|c| c.enabled)
+            .ok_or_else(|| format!("Invalid channel: {}", ch))?;
+
+        self.set_channel(ch, freq, enabled, None, false).await?;
+        self.log_info(&format!("CH{} tuned to {} ({} Hz)", ch, stations::normalize(call_sign), freq)).await;
+        Ok(freq)
+    }
+
+    // ROTATE PLAN - Shift which channels are enabled by `step` slots (wrapping at 12)
+    // Frequencies and amplitudes stay with their channel; only the enables move
+    pub async fn rotate_plan(&self, step: u8) -> Result<(), String> {
+        let count = Config::NUM_CHANNELS as usize;
+        let channels = self.state.read().await.channels.clone();
+        let shift = step as usize % count;
+        if shift == 0 {
+            return Ok(());
+        }
+
+        let rotated: Vec<bool> = (0..count)
+            .map(|i| channels[(i + count - shift) % count].enabled)
+            .collect();
+
+        // Enabling a channel left at 0 would go live silent (same rule as set_channel)
+        let muted = self.state.read().await.muted;
+        let default = *self.default_amplitude.read().await;
+        let plan: Vec<Channel> = channels.iter()
+            .zip(rotated)
+            .map(|(channel, enabled)| {
+                let silent = enabled && !channel.enabled && channel.amplitude == 0.0 && !muted;
+                let amplitude = if silent { default } else { channel.amplitude };
+                Channel { enabled, amplitude, ..channel.clone() }
+            })
+            .collect();
+
+        // One batch (disables first), so a failure can't leave the device half-rotated
+        self.swap_plan(plan).await?;
+        self.log_info(&format!("Rotated channel plan by {}", shift)).await;
+        Ok(())
+    }
+
+    // TEST TONE SEQUENCE - Step one channel through `freqs` with its output on, dwelling at each
+    // The channel's frequency, enable and amplitude are restored afterwards, also on abort or error
+    pub async fn test_tone_sequence(&self, ch: u8, freqs: Vec<u32>, dwell_ms: u64) -> Result<(), String> {
+        if freqs.is_empty() {
+            return Err("No test frequencies given".to_string());
+        }
+        if dwell_ms > Config::MAX_TONE_DWELL_MS {
+            return Err(format!("Dwell {} ms too long (max {})", dwell_ms, Config::MAX_TONE_DWELL_MS));
+        }
+        if self.state.read().await.connection != ConnectionState::Connected {
+            return Err("Not connected".to_string());
+        }
+        let original = self.state.read().await.channels.iter()
+            .find(|c| c.id == ch)
+            .cloned()
+            .ok_or_else(|| format!("Invalid channel: {}", ch))?;
+
+        *self.tone_abort.write().await = false;
+        self.log_info(&format!("CH{} test tone sequence: {} frequencies, {} ms dwell", ch, freqs.len(), dwell_ms)).await;
+
+        let mut result = Ok(());
+        for freq in freqs {
+            if let Err(e) = self.set_channel(ch, freq, true, None, false).await {
+                result = Err(e);
+                break;
+            }
+            if !Self::sleep_unless_aborted(&self.tone_abort, Duration::from_millis(dwell_ms)).await {
+                result = Err("Test tone sequence aborted".to_string());
+                break;
+            }
+        }
+
+        // A sequence error is reported ahead of a restore error
+        let restored = self.set_channel(ch, original.frequency, original.enabled, Some(original.amplitude), false).await;
+        if let Err(e) = &restored {
+            self.log_error(&format!("CH{} test tone sequence could not restore the channel: {}", ch, e)).await;
+        }
+        match (&result, &restored) {
+            (Ok(()), Ok(())) => self.log_info(&format!("CH{} test tone sequence complete - restored", ch)).await,
+            (Err(e), Ok(())) => self.log_warning(&format!("CH{} test tone sequence stopped ({}) - restored", ch, e)).await,
+            (Err(e), Err(_)) => self.log_warning(&format!("CH{} test tone sequence stopped ({})", ch, e)).await,
+            (Ok(()), Err(_)) => {}
+        }
+        result.and(restored)
+    }
+
+    // Sleep for `duration`, waking early once `abort` is set (false = aborted)
+    async fn sleep_unless_aborted(abort: &Arc<RwLock<bool>>, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+        loop {
+            if *abort.read().await {
+                return false;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return true;
+            }
+            sleep((deadline - now).min(Duration::from_millis(Config::ABORT_CHECK_MS))).await;
+        }
+    }
+
+    // ABORT TEST TONE - The running sequence restores the channel and returns an error
+    pub async fn abort_test_tone(&self) {
+        *self.tone_abort.write().await = true;
+    }
+
+    // RAMP FREQUENCY - Walk a channel to `target` in `step_hz` increments, `step_ms` apart,
+    // so the PLL never sees a large jump. The target is clamped to the global band and any
+    // per-channel limits; enable state is left as it is. Returns the frequency reached.
+    pub async fn ramp_frequency(&self, ch: u8, target: u32, step_hz: u32, step_ms: u64) -> Result<u32, String> {
+        if step_hz == 0 {
+            return Err("Ramp step must be at least 1 Hz".to_string());
+        }
+        if step_ms > Config::MAX_RAMP_STEP_MS {
+            return Err(format!("Ramp step interval {} ms too long (max {})", step_ms, Config::MAX_RAMP_STEP_MS));
+        }
+        let (mut current, enabled) = self.state.read().await.channels.iter()
+            .find(|c| c.id == ch)
+            .map(|c| (c.frequency, c.enabled))
+            .ok_or_else(|| format!("Invalid channel: {}", ch))?;
+
+        let (min, max) = match self.channel_bounds.read().await.get(&ch) {
+            Some(bounds) => (bounds.min, bounds.max),
+            None => (Config::MIN_FREQUENCY, Config::MAX_FREQUENCY),
+        };
+        let clamped = target.clamp(min, max);
+        if clamped != target {
+            self.log_warning(&format!("CH{} ramp target {} Hz clamped to {} Hz", ch, target, clamped)).await;
+        }
+
+        // Only an abort_ramp issued after this point stops this ramp
+        let generation = *self.ramp_generation.read().await;
+        self.log_info(&format!("CH{} ramping {} -> {} Hz ({} Hz every {} ms)", ch, current, clamped, step_hz, step_ms)).await;
+
+        while current != clamped {
+            let next = if clamped > current {
+                current.saturating_add(step_hz).min(clamped)
+            } else {
+                current.saturating_sub(step_hz).max(clamped)
+            };
+            self.set_channel(ch, next, enabled, None, false).await?;
+            current = next;
+
+            if current != clamped && !self.sleep_unless_ramp_aborted(generation, Duration::from_millis(step_ms)).await {
+                self.log_warning(&format!("CH{} ramp aborted at {} Hz", ch, current)).await;
+                return Err(format!("Ramp aborted at {} Hz", current));
+            }
+        }
+
+        self.log_info(&format!("CH{} ramp complete at {} Hz", ch, current)).await;
+        Ok(current)
+    }
+
+    // Sleep for `duration`, waking early once abort_ramp has moved past `generation` (false = aborted)
+    async fn sleep_unless_ramp_aborted(&self, generation: u64, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+        loop {
+            if *self.ramp_generation.read().await != generation {
+                return false;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return true;
+            }
+            sleep((deadline - now).min(Duration::from_millis(Config::ABORT_CHECK_MS))).await;
+        }
+    }
+
+    // ABORT RAMP - Every ramp running now stops; the channel stays at the last frequency applied
+    pub async fn abort_ramp(&self) {
+        *self.ramp_generation.write().await += 1;
+    }
+
+    // EXPORT PLAN CODE - Enabled channels and frequencies as a short string for sharing
+    pub async fn export_plan_code(&self) -> String {
+        plan_code::encode(&self.state.read().await.channels)
+    }
+
+    // IMPORT PLAN CODE - Code is fully validated before anything is sent; the coded
+    // channels are enabled at their frequencies and every other channel is disabled
+    pub async fn import_plan_code(&self, code: &str) -> Result<Vec<(u8, u32)>, String> {
+        let plan = plan_code::decode(code)?;
+        if self.state.read().await.connection != ConnectionState::Connected {
+            return Err("Not connected".to_string());
+        }
+
+        // Enabling a channel left at 0 would go live silent (same rule as set_channel)
+        let channels = self.state.read().await.channels.clone();
+        let muted = self.state.read().await.muted;
+        let default = *self.default_amplitude.read().await;
+        let new_plan: Vec<Channel> = channels.iter()
+            .map(|channel| match plan.iter().find(|(id, _)| *id == channel.id) {
+                Some(&(_, frequency)) => {
+                    let silent = !channel.enabled && channel.amplitude == 0.0 && !muted;
+                    let amplitude = if silent { default } else { channel.amplitude };
+                    Channel { enabled: true, frequency, amplitude, ..channel.clone() }
+                }
+                None => Channel { enabled: false, ..channel.clone() },
+            })
+            .collect();
+
+        // Validated as a whole and sent in one batch, so a rejection leaves the old plan up
+        self.swap_plan(new_plan).await?;
+
+        self.log_info(&format!("Imported plan code: {} channels", plan.len())).await;
+        Ok(plan)
+    }
+
+    // IMPORT CHANNELS CSV - All rows validated first; applied as one batch only if every row is valid
+    pub async fn import_channels_csv(&self, path: &Path) -> Result<CsvImportReport, String> {
+        let text = tokio::fs::read_to_string(path).await
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+        let parsed = channel_csv::parse(&text);
+        if parsed.is_empty() {
+            return Err(format!("No channel rows in {}", path.display()));
+        }
+
+        // Device-side limits (channel bounds, ADC capability) as well as the file format
+        let mut checked = Vec::with_capacity(parsed.len());
+        for (line, row) in parsed {
+            let row = match row {
+                Ok(channel) => {
+                    let id = channel.id;
+                    self.check_channel_setting(id, channel.frequency, channel.enabled, Some(channel.amplitude)).await
+                        .map(|()| channel)
+                        .map_err(|e| (Some(id), e))
+                }
+                Err(e) => Err((None, e)),
+            };
+            checked.push((line, row));
+        }
+
+        let valid = checked.iter().all(|(_, row)| row.is_ok());
+        let mut rows: Vec<CsvRowResult> = checked.iter().map(|(line, row)| match row {
+            Ok(channel) => CsvRowResult {
+                line: *line,
+                channel: Some(channel.id),
+                ok: true,
+                message: if valid { "Applied".to_string() } else { "Valid (not applied)".to_string() },
+            },
+            Err((channel, e)) => CsvRowResult { line: *line, channel: *channel, ok: false, message: e.clone() },
+        }).collect();
+
+        if !valid {
+            let bad = rows.iter().filter(|r| !r.ok).count();
+            self.log_warning(&format!("CSV import rejected: {} invalid rows in {}", bad, path.display())).await;
+            return Ok(CsvImportReport { applied: false, rows });
+        }
+
+        // One batch, so a late failure can't leave the earlier rows applied
+        let mut plan = self.state.read().await.channels.clone();
+        for (_, row) in &checked {
+            let channel = row.as_ref().expect("validated above");
+            if let Some(slot) = plan.iter_mut().find(|c| c.id == channel.id) {
+                *slot = channel.clone();
+            }
+        }
+        if let Err(e) = self.swap_plan(plan).await {
+            for row in &mut rows {
+                row.message = format!("Not applied ({})", e);
+            }
+            self.log_error(&format!("CSV import from {} failed: {}", path.display(), e)).await;
+            return Ok(CsvImportReport { applied: false, rows });
+        }
+
+        self.log_info(&format!("Imported {} channels from {}", rows.len(), path.display())).await;
+        Ok(CsvImportReport { applied: true, rows })
+    }
+
+    // VERIFY DEVICE FINGERPRINT - *IDN? must start with `expected_idn`, so a proxy or a
+    // different transmitter on the expected port is never driven. Returns the identity.
+    pub async fn verify_device_fingerprint(&self, expected_idn: &str, disconnect_on_mismatch: bool) -> Result<String, String> {
+        let expected = expected_idn.trim();
+        if expected.is_empty() {
+            return Err("Expected identity is empty".to_string());
+        }
+
+        let identity = self.query(ScpiCommands::IDENTITY).await?.trim().to_string();
+        if identity.starts_with(expected) {
+            self.log_info(&format!("Device fingerprint verified: {}", identity)).await;
+            return Ok(identity);
+        }
+
+        self.log_error(&format!("Wrong device: identity '{}' does not match '{}'", identity, expected)).await;
+        if disconnect_on_mismatch {
+            // Detach without sending anything more to a transmitter we don't own
+            self.disconnect(false).await?;
+        }
+        Err(format!("Device identity '{}' does not match expected '{}'", identity, expected))
+    }
+
+    // CHECK PROTOCOL VERSION - SYSTEM:PROTO? if supported, else the *IDN? firmware field
+    pub async fn check_protocol_version(&self) -> ProtocolCheck {
+        if let Ok(response) = self.query(ScpiCommands::PROTOCOL_QUERY).await {
+            if parse_protocol_version(&response).is_some() {
+                self.state.write().await.protocol_version = Some(response.trim().to_string());
+            }
+        }
+
+        let (device, expected) = {
+            let state = self.state.read().await;
+            (state.protocol_version.clone(), state.expected_protocol_version.clone())
+        };
+        let check = ProtocolCheck::evaluate(device.as_deref(), &expected);
+
+        let reported = device.as_deref().unwrap_or("unknown");
+        match check.status {
+            ProtocolStatus::Match => {}
+            ProtocolStatus::MinorMismatch => self.log_warning(&format!(
+                "Protocol version {} differs from expected {} (minor)", reported, expected)).await,
+            ProtocolStatus::MajorMismatch => self.log_error(&format!(
+                "Protocol version {} incompatible with expected {}", reported, expected)).await,
+            ProtocolStatus::Unknown => self.log_warning("Device protocol version unknown").await,
+        }
+        check
+    }
+
+    // ACTIVE CLIENTS - Control clients connected to the device (SYSTEM:CLIENTS?)
+    // More than one means someone else may be sending conflicting commands
+    pub async fn query_active_clients(&self) -> Result<u32, String> {
+        let response = self.query(ScpiCommands::CLIENTS_QUERY).await?;
+        let count: u32 = response.trim().parse()
+            .map_err(|_| format!("Invalid client count: '{}'", response.trim()))?;
+
+        self.state.write().await.active_clients = Some(count);
+        if count > 1 {
+            self.log_warning(&format!(
+                "{} control clients connected - commands from others may conflict", count
+            )).await;
+        }
+        Ok(count)
+    }
+
+    // CLOCK SKEW - Device UNIX time (SYSTEM:TIME?) minus host time, in whole seconds
+    // Positive = device clock ahead. Only the sign and size matter for log correlation,
+    // so the query's round-trip time is not compensated.
+    pub async fn query_clock_skew(&self) -> Result<i64, String> {
+        let response = self.query(ScpiCommands::TIME_QUERY).await?;
+        let device_time: f64 = response.trim().parse()
+            .ok()
+            .filter(|t: &f64| t.is_finite() && *t >= 0.0)
+            .ok_or_else(|| format!("Invalid device time: '{}'", response.trim()))?;
+        let host_time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs_f64();
+
+        let skew = (device_time - host_time).round() as i64;
+        self.state.write().await.clock_skew_secs = Some(skew);
+        if skew.abs() > Config::MAX_CLOCK_SKEW_SECS {
+            self.log_warning(&format!(
+                "Device clock {} host by {} s - log timestamps won't line up",
+                if skew > 0 { "ahead of" } else { "behind" }, skew.abs()
+            )).await;
+        }
+        Ok(skew)
+    }
+
+    // READ VSWR - Antenna match on one channel (firmware with MEAS:VSWR? only).
+    // Above the limit output is stopped if broadcasting.
+    pub async fn read_vswr(&self, ch: u8) -> Result<f32, String> {
+        if !(1..=Config::NUM_CHANNELS).contains(&ch) {
+            return Err(format!("Invalid channel: {}", ch));
+        }
+        let response = self.query(&format!("{} {}", ScpiCommands::VSWR_QUERY, ch)).await?;
+        let vswr: f32 = response.trim().parse()
+            .ok()
+            .filter(|v: &f32| v.is_finite() && *v >= 1.0)
+            .ok_or_else(|| format!("Invalid VSWR reading for CH{}: '{}' (not supported by firmware?)", ch, response.trim()))?;
+
+        let broadcasting = {
+            let mut state = self.state.write().await;
+            state.vswr.insert(ch, vswr);
+            state.broadcast.is_broadcasting()
+        };
+
+        let max_vswr = *self.max_vswr.read().await;
+        if vswr > max_vswr {
+            self.log_warning(&format!("CH{} VSWR {:.2} exceeds limit {:.2} - check antenna", ch, vswr, max_vswr)).await;
+            if broadcasting {
+                self.stop_output(true, StopReason::Vswr).await?;
+            }
+        }
+        Ok(vswr)
+    }
+
+    // DIFF SCENE - What apply_scene would change, one line per difference
+    pub async fn diff_scene(&self, scene: &Scene) -> Vec<String> {
+        let state = self.state.read().await;
+        let mut diffs = Vec::new();
+
+        if state.source != scene.source {
+            diffs.push(format!("Source: {} -> {}", state.source.as_str(), scene.source.as_str()));
+        }
+
+        for target in &scene.channels {
+            let Some(current) = state.channels.iter().find(|c| c.id == target.id) else {
+                diffs.push(format!("CH{}: not present on device", target.id));
+                continue;
+            };
+
+            if current.frequency != target.frequency {
+                diffs.push(format!("CH{} frequency: {:.1} kHz -> {:.1} kHz",
+                    target.id, current.frequency as f64 / 1000.0, target.frequency as f64 / 1000.0));
+            }
+            if current.enabled != target.enabled {
+                let label = |enabled: bool| if enabled { "enabled" } else { "disabled" };
+                diffs.push(format!("CH{}: {} -> {}", target.id, label(current.enabled), label(target.enabled)));
+            }
+            if (current.amplitude - target.amplitude).abs() > 1e-4 {
+                diffs.push(format!("CH{} level: {:.2} -> {:.2}", target.id, current.amplitude, target.amplitude));
+            }
+        }
+
+        diffs
+    }
+
+    // BRAM MESSAGE INFO
+    pub async fn bram_message_info(&self, index: u8) -> Result<BramInfo, String> {
+        if index >= Config::MAX_BRAM_MESSAGES {
+            return Err(format!("BRAM index {} out of range (0-{})", index, Config::MAX_BRAM_MESSAGES - 1));
+        }
+
+        let response = self.query(&format!("{} {}", ScpiCommands::BRAM_INFO, index)).await?;
+        BramInfo::parse(index, &response)
+    }
+
+    // SWITCH TO BRAM MESSAGE - Preload the clip, wait for BRAM:READY?, then switch source
+    // and select it, so the carrier never sits on an empty buffer. Output is not touched.
+    pub async fn switch_to_bram_message(&self, index: u8) -> Result<(), String> {
+        if index >= Config::MAX_BRAM_MESSAGES {
+            return Err(format!("BRAM index {} out of range (0-{})", index, Config::MAX_BRAM_MESSAGES - 1));
+        }
+        if self.state.read().await.connection != ConnectionState::Connected {
+            return Err("Not connected".to_string());
+        }
+
+        self.send_command(&format!("{} {}", ScpiCommands::BRAM_PRELOAD, index)).await?;
+        if !self.wait_bram_ready(Duration::from_millis(Config::BRAM_READY_TIMEOUT_MS)).await {
+            let e = format!("BRAM message {} not ready after {} ms", index, Config::BRAM_READY_TIMEOUT_MS);
+            self.log_error(&e).await;
+            return Err(e);
+        }
+
+        let previous = self.state.read().await.source;
+        if previous != SourceMode::Bram {
+            self.send_command(ScpiCommands::SOURCE_BRAM).await?;
+        }
+        self.send_command(&format!("{} {}", ScpiCommands::BRAM_SELECT, index)).await?;
+
+        {
+            let mut state = self.state.write().await;
+            state.source = SourceMode::Bram;
+            state.bram_message = Some(index);
+        }
+        if previous != SourceMode::Bram {
+            self.event_bus.emit(EventType::SourceChanged(SourceMode::Bram));
+        }
+        self.log_info(&format!("Switched to preloaded BRAM message {}", index)).await;
+        Ok(())
+    }
+
+    // Poll BRAM:READY? until the preloaded clip is buffered or `within` expires
+    async fn wait_bram_ready(&self, within: Duration) -> bool {
+        let deadline = Instant::now() + within;
+
+        loop {
+            if let Ok(response) = self.query(ScpiCommands::BRAM_READY_QUERY).await {
+                let value = response.trim();
+                if value == "1" || value.eq_ignore_ascii_case("READY") {
+                    return true;
+                }
+            }
+
+            if Instant::now() >= deadline {
+                return false;
+            }
+            sleep(Duration::from_millis(Config::BRAM_READY_POLL_MS)).await;
+        }
+    }
+
+    // LIST AUDIO INPUTS (ADC mode)
+    pub async fn list_audio_inputs(&self) -> Result<Vec<String>, String> {
+        let response = self.query(ScpiCommands::SOURCE_INPUT_LIST).await?;
+        Ok(response
+            .trim()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect())
+    }
+
+    // SET AUDIO INPUT (ADC mode only)
+    pub async fn set_audio_input(&self, input: &str) -> Result<(), String> {
+        if self.state.read().await.source != SourceMode::Adc {
+            return Err("Audio input can only be selected in ADC mode".to_string());
+        }
+        self.check_adc_capable(&self.enabled_channel_ids().await).await?;
+
+        let input = input.trim();
+        if input.is_empty() || !input.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("Invalid audio input: '{}'", input));
+        }
+
+        self.send_command(&format!("{} {}", ScpiCommands::SOURCE_INPUT, input)).await?;
+
+        self.state.write().await.audio_input = Some(input.to_string());
+        self.log_info(&format!("Audio input set to {}", input)).await;
+        Ok(())
+    }
+
+    // OUTPUT LOAD - Termination the output stage is configured for (not while on air:
+    // the output level shifts with the load)
+    pub async fn set_output_load(&self, ohms: u32) -> Result<(), String> {
+        if !Config::OUTPUT_LOADS_OHMS.contains(&ohms) {
+            return Err(format!("Unsupported output load {} ohm (allowed: {:?})", ohms, Config::OUTPUT_LOADS_OHMS));
+        }
+        if self.state.read().await.broadcast.is_broadcasting() {
+            return Err("Cannot change output load while broadcasting".to_string());
+        }
+
+        self.send_command(&format!("{} {}", ScpiCommands::OUTPUT_LOAD, ohms)).await?;
+
+        self.state.write().await.output_load_ohms = Some(ohms);
+        self.log_info(&format!("Output load set to {} ohm", ohms)).await;
+        Ok(())
+    }
+
+    pub async fn query_output_load(&self) -> Result<u32, String> {
+        let response = self.query(ScpiCommands::OUTPUT_LOAD_QUERY).await?;
+        let ohms: u32 = response.trim().parse()
+            .map_err(|_| format!("Invalid output load: '{}' (not supported by firmware?)", response.trim()))?;
+        self.state.write().await.output_load_ohms = Some(ohms);
+        Ok(ohms)
+    }
+
+    // ENABLE PRESET CHANNELS
+    pub async fn enable_preset(&self, count: u8) -> Result<(), String> {
+        // Frequency presets (100kHz spacing)
+        let freqs: [u32; 12] = [
+            540_000, 640_000, 740_000, 840_000, 940_000, 1_040_000,
+            1_140_000, 1_240_000, 1_340_000, 1_440_000, 1_540_000, 1_640_000,
+        ];
+
+        // Distribution patterns
+        let channels = ChannelPresets::for_count(count);
+
+        // Disable all channels first
+        for ch in 1..=12u8 {
+            self.set_channel(ch, freqs[(ch - 1) as usize], false, None, false).await?;
+        }
+
+        // Enable selected channels
+        for &ch in &channels {
+            let freq = freqs[(ch - 1) as usize];
+            self.set_channel(ch, freq, true, None, false).await?;
+        }
+
+        self.log_info(&format!("Enabled {} channel preset", count)).await;
+        Ok(())
+    }
+
+        // GET STATE (for UI)
+        pub async fn get_state(&self) -> DeviceState {
+            self.state.read().await.clone()
+        }
+
+        // GET AUDIT LOG
+        pub async fn get_audit_log(&self) -> Vec<AuditEntry> {
+            self.audit.log.read().await.clone()
+        }
+
+        // AUDIT SUMMARY (in-memory log only)
+        pub async fn audit_summary(&self) -> AuditSummary {
+            AuditSummary::from_entries(&self.audit.log.read().await)
+        }
+
+        // GET AUDIT LOG (single level, e.g. "NOTE")
+        pub async fn get_audit_log_level(&self, level: &str) -> Vec<AuditEntry> {
+            self.audit.log.read().await
+                .iter()
+                .filter(|e| e.level.eq_ignore_ascii_case(level))
+                .cloned()
+                .collect()
+        }
+
+        // GET RECENT EVENTS OF A TYPE (oldest first)
+        pub fn recent_events_of_type(&self, type_name: &str, limit: usize) -> Vec<EventType> {
+            self.event_bus.recent_of_type(type_name, limit)
+        }
+
+        // EVENT BUS HEALTH
+        pub fn event_bus_health(&self) -> EventBusHealth {
+            self.event_bus.health()
+        }
+
+        // GET CHANNELS GROUPED (enabled, disabled), each sorted by id
+        pub async fn channels_grouped(&self) -> (Vec<Channel>, Vec<Channel>) {
+            let mut channels = self.state.read().await.channels.clone();
+            channels.sort_by_key(|c| c.id);
+            channels.into_iter().partition(|c| c.enabled)
+        }
+
+        // GET PLAN BANDWIDTH (enabled channels)
+        pub async fn plan_bandwidth(&self) -> PlanBandwidth {
+            PlanBandwidth::from_channels(&self.state.read().await.channels)
+        }
+
+        // PRESET DEVIATIONS - (channel, current, preset) for channels off their preset frequency
+        pub async fn preset_deviations(&self) -> Vec<(u8, u32, u32)> {
+            self.state.read().await.channels
+                .iter()
+                .map(|c| (c.id, c.frequency, FrequencyPresets::for_channel(c.id)))
+                .filter(|&(_, current, preset)| current != preset)
+                .collect()
+        }
+
+        // NEAREST COLLISION - Closest other enabled channel within the interference
+        // distance of a candidate frequency for `ch` (nothing is applied)
+        pub async fn nearest_collision(&self, ch: u8, freq: u32) -> Option<(u8, u32)> {
+            let state = self.state.read().await;
+            state.channels
+                .iter()
+                .filter(|c| c.enabled && c.id != ch)
+                .map(|c| (c.id, c.frequency.abs_diff(freq)))
+                .filter(|&(_, gap)| gap < Config::MIN_CHANNEL_SPACING_HZ)
+                .min_by_key(|&(_, gap)| gap)
+        }
+
+        // STATUS LINE - e.g. "CONNECTED • BROADCASTING • WD:OK • ERR 1/3 • 45°C • 6ch"
+        // Disconnected is reported alone - the rest would be stale
+        pub async fn status_line(&self) -> String {
+            let state = self.state.read().await;
+            if state.connection != ConnectionState::Connected {
+                return state.connection.display().to_string();
+            }
+
+            let mut parts = vec![
+                state.connection.display().to_string(),
+                state.broadcast.display().to_string(),
+                format!("WD:{}", state.watchdog.display()),
+            ];
+            if state.consecutive_poll_errors > 0 {
+                parts.push(format!("ERR {}/{}", state.consecutive_poll_errors, Config::MAX_CONSECUTIVE_ERRORS));
+            }
+            if let Some(temp) = state.fpga_temperature {
+                parts.push(format!("{:.0}°C", temp));
+            }
+            parts.push(format!("{}ch", state.channels.iter().filter(|c| c.enabled).count()));
+            parts.join(" • ")
+        }
+
+        // ACTIVE CHANNELS (cached state - no device round-trip)
+        pub async fn has_active_channels(&self) -> bool {
+            self.state.read().await.channels.iter().any(|c| c.enabled)
+        }
+
+        pub async fn active_channel_count(&self) -> usize {
+            self.state.read().await.channels.iter().filter(|c| c.enabled).count()
+        }
+
+        // SESSION STATS (since the last connect)
+        pub async fn session_stats(&self) -> SessionStats {
+            self.state.read().await.session.snapshot()
+        }
+
+        // LAST STOP REASON (None until a broadcast has stopped)
+        pub async fn last_stop_reason(&self) -> Option<StopReason> {
+            self.state.read().await.last_stop_reason
+        }
+
+        // GET SERIAL (cached from the last init)
+        pub async fn serial(&self) -> Option<String> {
+            self.state.read().await.serial.clone()
+        }
+
+        // GET STATUS AGE (seconds since the last successful status poll)
+        pub async fn status_age_secs(&self) -> Option<u64> {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            self.state.read().await.status_age_secs(now)
+        }
+
+        // GET WATCHDOG TRIGGER HISTORY (unix ms, oldest first)
+        pub async fn watchdog_trigger_history(&self) -> Vec<u64> {
+            self.watchdog_triggers.read().await.iter().copied().collect()
+        }
+
+        // GET WATCHDOG MARGIN (worst feed interval in the last minute vs the timeout)
+        pub async fn watchdog_margin(&self) -> WatchdogMargin {
+            let window = Duration::from_secs(Config::WATCHDOG_MARGIN_WINDOW_SECS);
+            let worst_interval_ms = self.watchdog_feeds.read().await.iter()
+                .filter(|(t, _)| t.elapsed() <= window)
+                .map(|(_, interval)| *interval)
+                .max()
+                .unwrap_or(0);
+            let timeout_ms = self.watchdog_timeout.read().await.as_millis() as u64;
+            WatchdogMargin {
+                worst_interval_ms,
+                timeout_ms,
+                margin_ms: timeout_ms as i64 - worst_interval_ms as i64,
+                near_miss: *self.watchdog_near_miss.read().await,
+            }
+        }
+
+        // GET LATENCY TREND (last `limit` samples, oldest first)
+        pub async fn latency_trend(&self, limit: usize) -> Vec<LatencySample> {
+            let trend = self.latency_trend.read().await;
+            trend.iter().skip(trend.len().saturating_sub(limit)).cloned().collect()
+        }
+
+        // FREQUENCY UNIT PREFERENCE
+        pub async fn frequency_unit(&self) -> FrequencyUnit {
+            *self.frequency_unit.read().await
+        }
+
+        pub async fn set_frequency_unit(&self, unit: FrequencyUnit) {
+            *self.frequency_unit.write().await = unit;
+        }
+
+        // TEMPERATURE UNIT PREFERENCE
+        pub async fn temperature_unit(&self) -> TemperatureUnit {
+            *self.temperature_unit.read().await
+        }
+
+        pub async fn set_temperature_unit(&self, unit: TemperatureUnit) {
+            *self.temperature_unit.write().await = unit;
+        }
+
+        // TRANSITION DISPLAY PREFERENCE
+        pub async fn show_transitions(&self) -> bool {
+            *self.show_transitions.read().await
+        }
+
+        pub async fn set_show_transitions(&self, show: bool) {
+            *self.show_transitions.write().await = show;
+        }
+
+        // DISPLAYED BROADCAST STATE - real state, or its settled target when transitions are hidden
+        pub async fn displayed_broadcast(&self) -> BroadcastState {
+            let broadcast = self.state.read().await.broadcast;
+            if self.show_transitions().await { broadcast } else { broadcast.settled() }
+        }
+
+        // GET TEMPERATURE (None until the device has reported one)
+        pub async fn temperature(&self) -> Option<TemperatureReading> {
+            let temp_c = self.state.read().await.fpga_temperature?;
+            Some(TemperatureReading::from_celsius(temp_c, self.temperature_unit().await))
+        }
+
+        // GET LAST CONNECT TIMING (None until a connect has completed)
+        pub async fn last_connect_timing(&self) -> Option<ConnectTiming> {
+            self.last_connect_timing.read().await.clone()
+        }
+
+        // GET ACTIVE ENDPOINT (changes after a failover)
+        pub async fn active_endpoint(&self) -> Option<Endpoint> {
+            let ip = self.current_ip.read().await.clone()?;
+            let port = (*self.current_port.read().await)?;
+            Some(Endpoint { ip, port })
+        }
+
+        // GET CONNECTED ADDRESS (resolved socket address of the live link)
+        pub async fn connected_addr(&self) -> Option<SocketAddr> {
+            *self.connected_addr.read().await
+        }
+
+        // GET CONNECTION TARGET (None while not connected)
+        pub async fn connection_target(&self) -> Option<ConnectionTarget> {
+            let resolved_addr = self.connected_addr().await?;
+            let active = self.active_endpoint().await?;
+            let requested = self.endpoints.read().await.first().cloned().unwrap_or_else(|| active.clone());
+            Some(ConnectionTarget {
+                failed_over: active != requested,
+                requested,
+                active,
+                resolved_addr,
+            })
+        }
+
+        // GET CHANNEL CAPABILITIES
+        pub async fn get_channel_capabilities(&self) -> Vec<ChannelCapability> {
+            self.state.read().await.channel_capabilities.clone()
+        }
+
+        // IS CONNECTED
+        pub async fn is_connected(&self) -> bool {
+            self.state.read().await.connection == ConnectionState::Connected
+        }
+    // ARM (for state machine)
+    pub async fn arm(&self) -> Result<(), String> {
+        let state = self.state.read().await;
+        if state.connection != ConnectionState::Connected {
+            return Err("Not connected".to_string());
+        }
+        drop(state);
+
+        self.log_info("System armed").await;
+        Ok(())
+    }
+
+    // START EMERGENCY (bypasses arm; `override_license` also bypasses the license window)
+    pub async fn start_emergency(&self, override_license: bool) -> Result<(), String> {
+        if override_license {
+            if let Err(e) = self.check_license_window().await {
+                self.log_warning(&format!("Emergency override: {}", e)).await;
+            }
+        } else {
+            self.check_license_window().await?;
+        }
+        self.check_output_interval().await?;
+
+        self.log_info("EMERGENCY BROADCAST").await;
+
+        self.send_command(ScpiCommands::OUTPUT_ON).await?;
+        self.mark_output_change().await;
+        *self.license_override.write().await = override_license;
+
+        {
+            let mut state = self.state.write().await;
+            state.set_broadcast(BroadcastState::Broadcasting);
+            state.session.emergency_activations += 1;
+        }
+
+        self.event_bus.emit(EventType::BroadcastStarted);
+        Ok(())
+    }
+
+    // STOP EMERGENCY (always allowed, regardless of output interval)
+    pub async fn stop_emergency(&self) -> Result<(), String> {
+        self.log_info("Stopping emergency broadcast").await;
+        self.stop_output(true, StopReason::Emergency).await
+    }
+
+    // RUN SEQUENCE - Execute scripted steps in order, checking each broadcast step against
+    // the state machine (arm before start, etc.) and confirming output changes with the device.
+    // A failed mandatory step aborts the run; the rest are reported as skipped.
+    pub async fn run_sequence(&self, steps: Vec<SequenceStep>) -> Vec<StepResult> {
+        self.log_info(&format!("Running sequence of {} steps", steps.len())).await;
+
+        // The manager itself doesn't track arming, so the script keeps its own machine
+        let mut machine = self.state.read().await.broadcast;
+        let mut results = Vec::with_capacity(steps.len());
+        let mut aborted = false;
+
+        for (index, step) in steps.into_iter().enumerate() {
+            if aborted {
+                results.push(StepResult {
+                    index,
+                    action: step.action,
+                    status: StepStatus::Skipped,
+                    message: "Skipped".to_string(),
+                    elapsed_ms: 0,
+                });
+                continue;
+            }
+
+            let started = Instant::now();
+            let outcome = self.run_sequence_step(&step.action, &mut machine).await;
+            let (status, message) = match outcome {
+                Ok(message) => (StepStatus::Passed, message),
+                Err(e) => (StepStatus::Failed, e),
+            };
+
+            if status == StepStatus::Failed {
+                self.log_warning(&format!("Sequence step {} failed: {}", index + 1, message)).await;
+                aborted = !step.optional;
+            }
+            results.push(StepResult {
+                index,
+                action: step.action,
+                status,
+                message,
+                elapsed_ms: started.elapsed().as_millis() as u64,
+            });
+        }
+
+        let passed = results.iter().filter(|r| r.status == StepStatus::Passed).count();
+        self.log_info(&format!("Sequence finished: {}/{} steps passed", passed, results.len())).await;
+        results
+    }
+
+    async fn run_sequence_step(&self, action: &SequenceAction, machine: &mut BroadcastState) -> Result<String, String> {
+        match action {
+            SequenceAction::Arm => {
+                *machine = machine.request_arm()?;
+                self.arm().await?;
+                *machine = machine.confirm_armed();
+                Ok("Armed".to_string())
+            }
+            SequenceAction::Start => {
+                machine.request_start()?;
+                self.start_broadcast().await?;
+                *machine = BroadcastState::Broadcasting;
+                Ok("Broadcast started (confirmed)".to_string())
+            }
+            SequenceAction::Stop => {
+                if *machine == BroadcastState::Emergency {
+                    machine.request_stop_emergency()?;
+                    self.stop_emergency().await?;
+                } else {
+                    machine.request_stop()?;
+                    self.stop_broadcast().await?;
+                }
+                *machine = BroadcastState::Idle;
+                if !self.confirm_output(false, Duration::from_millis(Config::OUTPUT_CONFIRM_TIMEOUT_MS)).await {
+                    return Err("Stop not confirmed by device".to_string());
+                }
+                Ok("Broadcast stopped (confirmed)".to_string())
+            }
+            SequenceAction::Emergency => {
+                machine.request_emergency()?;
+                self.start_emergency(false).await?;
+                *machine = BroadcastState::Emergency;
+                Ok("Emergency broadcast started".to_string())
+            }
+            SequenceAction::Wait { ms } => {
+                if *ms > Config::MAX_SEQUENCE_WAIT_MS {
+                    return Err(format!("Wait {} ms too long (max {})", ms, Config::MAX_SEQUENCE_WAIT_MS));
+                }
+                sleep(Duration::from_millis(*ms)).await;
+                Ok(format!("Waited {} ms", ms))
+            }
+            SequenceAction::SetChannel { ch, frequency, enabled } => {
+                self.set_channel(*ch, *frequency, *enabled, None, true).await?;
+                Ok(format!("CH{} set to {} Hz, enabled={} (verified)", ch, frequency, enabled))
+            }
+        }
+    }
+
+    // PREFLIGHT - Every reason a broadcast shouldn't start right now (empty = good to go)
+    pub async fn preflight(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let state = self.state.read().await.clone();
+
+        if state.connection != ConnectionState::Connected {
+            problems.push("Not connected".to_string());
+        }
+
+        let enabled: Vec<&Channel> = state.channels.iter().filter(|c| c.enabled).collect();
+        if enabled.is_empty() {
+            problems.push("No channels enabled".to_string());
+        }
+        for (i, a) in enabled.iter().enumerate() {
+            for b in &enabled[i + 1..] {
+                let gap = a.frequency.abs_diff(b.frequency);
+                if gap < Config::MIN_CHANNEL_SPACING_HZ {
+                    problems.push(format!("CH{} and CH{} only {} Hz apart", a.id, b.id, gap));
+                }
+            }
+        }
+
+        let thresholds = *self.temp_thresholds.read().await;
+        match state.fpga_temperature {
+            Some(temp) if state.over_temperature || temp >= thresholds.high => {
+                problems.push(format!("FPGA over temperature: {:.1} C", temp));
+            }
+            _ => {}
+        }
+
+        let budget = self.amplitude_budget().await;
+        if !budget.within_budget {
+            problems.push(format!("Total amplitude {:.2} exceeds budget {:.2}", budget.total, budget.max_total));
+        }
+
+        if let Err(e) = self.check_license_window().await {
+            problems.push(e);
+        }
+
+        problems
+    }
+
+    // LICENSE WINDOW
+    pub async fn set_license_window(&self, window: Option<LicenseWindow>) {
+        *self.license_window.write().await = window;
+        match window {
+            Some(w) => self.log_info(&format!(
+                "License window set: {} - {}", w.start.format("%H:%M"), w.end.format("%H:%M")
+            )).await,
+            None => self.log_info("License window cleared").await,
+        }
+    }
+
+    async fn local_time(&self) -> NaiveTime {
+        self.clock_override.read().await.unwrap_or_else(|| chrono::Local::now().time())
+    }
+
+    async fn check_license_window(&self) -> Result<(), String> {
+        if let Some(window) = *self.license_window.read().await {
+            let now = self.local_time().await;
+            if !window.contains(now) {
+                return Err(format!(
+                    "Outside license window ({} - {}), local time {}",
+                    window.start.format("%H:%M"), window.end.format("%H:%M"), now.format("%H:%M")
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // OUTPUT INTERVAL SAFEGUARD
+    async fn check_output_interval(&self) -> Result<(), String> {
+        let min_interval = *self.min_output_interval.read().await;
+        if let Some(last) = *self.last_output_change.read().await {
+            let elapsed = last.elapsed();
+            if elapsed < min_interval {
+                return Err(format!(
+                    "Output changed {} ms ago; wait {} ms (minimum interval {} ms)",
+                    elapsed.as_millis(),
+                    (min_interval - elapsed).as_millis(),
+                    min_interval.as_millis()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    async fn mark_output_change(&self) {
+        *self.last_output_change.write().await = Some(Instant::now());
+    }
+
+    pub async fn set_min_output_interval(&self, ms: u64) {
+        *self.min_output_interval.write().await = Duration::from_millis(ms);
+        self.log_info(&format!("Minimum output interval set to {} ms", ms)).await;
+    }
+
+    // AMPLITUDE BUDGET
+    pub async fn amplitude_budget(&self) -> AmplitudeBudget {
+        let max_total = *self.max_total_amplitude.read().await;
+        let state = self.state.read().await;
+        let enabled: Vec<f32> = state.channels.iter().filter(|c| c.enabled).map(|c| c.amplitude).collect();
+        let total: f32 = enabled.iter().sum();
+
+        AmplitudeBudget {
+            total,
+            max_total,
+            enabled_channels: enabled.len(),
+            within_budget: total <= max_total + 1e-4,  // Tolerate f32 rounding at the limit
+        }
+    }
+
+    pub async fn set_default_amplitude(&self, amplitude: f32) -> Result<(), String> {
+        if !(amplitude > 0.0 && amplitude <= 1.0) {
+            return Err(format!("Default amplitude {} out of range (0.0-1.0]", amplitude));
+        }
+        *self.default_amplitude.write().await = amplitude;
+        self.log_info(&format!("Default amplitude set to {}", amplitude)).await;
+        Ok(())
+    }
+
+    // Applies to the next set_pilot; channels already piloting keep their level
+    pub async fn set_pilot_amplitude(&self, amplitude: f32) -> Result<(), String> {
+        if !(amplitude > 0.0 && amplitude <= Config::MAX_PILOT_AMPLITUDE) {
+            return Err(format!("Pilot amplitude {} out of range (0.0-{}]", amplitude, Config::MAX_PILOT_AMPLITUDE));
+        }
+        *self.pilot_amplitude.write().await = amplitude;
+        self.log_info(&format!("Pilot amplitude set to {}", amplitude)).await;
+        Ok(())
+    }
+
+    // Watchdog must be fed at least twice per timeout period
+    pub async fn set_poll_interval(&self, ms: u64) -> Result<(), String> {
+        let watchdog_ms = self.watchdog_timeout.read().await.as_millis() as u64;
+        if ms < Config::MIN_POLL_INTERVAL_MS || ms.saturating_mul(2) > watchdog_ms {
+            return Err(format!(
+                "Poll interval {} ms out of range ({}-{} ms for a {} ms watchdog)",
+                ms, Config::MIN_POLL_INTERVAL_MS, watchdog_ms / 2, watchdog_ms
+            ));
+        }
+        *self.poll_interval.write().await = Duration::from_millis(ms);
+        self.log_info(&format!("Poll interval set to {} ms", ms)).await;
+        Ok(())
+    }
+
+    // Firmware revisions differ (WATCHDOG:RESET vs WDT:KICK); picked up on the next poll
+    pub async fn set_watchdog_command(&self, command: &str) -> Result<(), String> {
+        let command = command.trim();
+        if command.is_empty() {
+            return Err("Watchdog command cannot be empty".to_string());
+        }
+        if command.chars().any(|c| c.is_control()) {
+            return Err("Watchdog command cannot contain control characters".to_string());
+        }
+        *self.watchdog_command.write().await = command.to_string();
+        self.log_info(&format!("Watchdog command set to {}", command)).await;
+        Ok(())
+    }
+
+    // EFFECTIVE CONFIG
+    pub async fn effective_config(&self) -> EffectiveConfig {
+        EffectiveConfig {
+            poll_interval_ms: self.poll_interval.read().await.as_millis() as u64,
+            watchdog_timeout_ms: self.watchdog_timeout.read().await.as_millis() as u64,
+            watchdog_command: self.watchdog_command.read().await.clone(),
+            connection_timeout_secs: Config::CONNECTION_TIMEOUT_SECS,
+            command_timeout_secs: Config::COMMAND_TIMEOUT_SECS,
+            min_output_interval_ms: self.min_output_interval.read().await.as_millis() as u64,
+            max_total_amplitude: *self.max_total_amplitude.read().await,
+            max_vswr: *self.max_vswr.read().await,
+            default_amplitude: *self.default_amplitude.read().await,
+            pilot_amplitude: *self.pilot_amplitude.read().await,
+            temperature: *self.temp_thresholds.read().await,
+            channel_bounds: self.channel_bounds.read().await.clone(),
+            retry: self.retry_config.read().await.clone(),
+            console_verbose: *self.console_verbose.read().await,
+            plain_output: console::is_plain_output(),
+            test_mode: *self.test_mode.read().await,
+            reconcile_guard: self.reconcile_guard.read().await.enabled,
+            reconcile_window_ms: self.reconcile_guard.read().await.window.as_millis() as u64,
+            show_transitions: *self.show_transitions.read().await,
+        }
+    }
+
+    // RECONCILE GUARD - `enabled: false` lets every status poll overwrite local channel state
+    pub async fn set_reconcile_guard(&self, enabled: bool, window_ms: u64) -> Result<(), String> {
+        if window_ms > Config::MAX_RECONCILE_WINDOW_MS {
+            return Err(format!("Reconcile window {} ms too long (max {} ms)", window_ms, Config::MAX_RECONCILE_WINDOW_MS));
+        }
+        {
+            let mut guard = self.reconcile_guard.write().await;
+            guard.enabled = enabled;
+            guard.window = Duration::from_millis(window_ms);
+        }
+        if enabled {
+            self.log_info(&format!("Status polls hold off recently changed channels for {} ms", window_ms)).await;
+        } else {
+            self.log_info("Status polls always overwrite local channel state").await;
+        }
+        Ok(())
+    }
+
+    pub async fn set_temperature_thresholds(&self, high: f32, low: f32) -> Result<(), String> {
+        if !high.is_finite() || !low.is_finite() || low >= high {
+            return Err(format!("Invalid temperature thresholds: high {} must be above low {}", high, low));
+        }
+        *self.temp_thresholds.write().await = TemperatureThresholds { high, low };
+        self.log_info(&format!("Temperature thresholds: trip {:.1} C, clear below {:.1} C", high, low)).await;
+        Ok(())
+    }
+
+    // SET CHANNEL BOUNDS - `None` removes the override (global band only)
+    pub async fn set_channel_bounds(&self, ch: u8, bounds: Option<ChannelBounds>) -> Result<(), String> {
+        if !(1..=12).contains(&ch) {
+            return Err(format!("Invalid channel: {}", ch));
+        }
+
+        let Some(bounds) = bounds else {
+            self.channel_bounds.write().await.remove(&ch);
+            self.log_info(&format!("CH{} frequency limits cleared", ch)).await;
+            return Ok(());
+        };
+
+        if bounds.min >= bounds.max
+            || bounds.min < Config::MIN_FREQUENCY
+            || bounds.max > Config::MAX_FREQUENCY
+        {
+            return Err(format!("Invalid CH{} limits {}-{} (must be ascending within {}-{})",
+                ch, bounds.min, bounds.max, Config::MIN_FREQUENCY, Config::MAX_FREQUENCY));
+        }
+
+        self.channel_bounds.write().await.insert(ch, bounds);
+        self.log_info(&format!("CH{} frequency limited to {}-{} Hz", ch, bounds.min, bounds.max)).await;
+
+        // Existing setting is left alone, but the operator should know it's now out of range
+        let current = self.state.read().await.channels.iter().find(|c| c.id == ch).map(|c| c.frequency);
+        if let Some(freq) = current.filter(|f| !bounds.contains(*f)) {
+            self.log_warning(&format!("CH{} currently at {} Hz - outside its new limits", ch, freq)).await;
+        }
+        Ok(())
+    }
+
+    pub async fn set_max_total_amplitude(&self, max_total: f32) -> Result<(), String> {
+        if !max_total.is_finite() || max_total <= 0.0 {
+            return Err(format!("Invalid amplitude budget: {}", max_total));
+        }
+        *self.max_total_amplitude.write().await = max_total;
+        self.log_info(&format!("Amplitude budget set to {:.2}", max_total)).await;
+        Ok(())
+    }
+
+    pub async fn set_max_vswr(&self, max_vswr: f32) -> Result<(), String> {
+        if !max_vswr.is_finite() || max_vswr <= 1.0 {
+            return Err(format!("Invalid VSWR limit: {} (must be above 1.0)", max_vswr));
+        }
+        *self.max_vswr.write().await = max_vswr;
+        self.log_info(&format!("VSWR limit set to {:.2}", max_vswr)).await;
+        Ok(())
+    }
+
+    // STATE MACHINE SELF-TEST - Field check of the broadcast transition rules (result is audited)
+    pub async fn run_state_machine_selftest(&self) -> Vec<SelftestResult> {
+        let results = state_machine::run_selftest();
+        let failed: Vec<&str> = results.iter().filter(|r| !r.passed).map(|r| r.scenario.as_str()).collect();
+        if failed.is_empty() {
+            self.log_info(&format!("State machine self-test passed ({} scenarios)", results.len())).await;
+        } else {
+            self.log_error(&format!("State machine self-test FAILED: {}", failed.join("; "))).await;
+        }
+        results
+    }
+
+    // POLL TASK HEALTH - a live loop ticks every poll interval
+    // (a panicked task never decrements poll_tasks, so the tick age is what catches it)
+    pub async fn poll_task_healthy(&self) -> bool {
+        if *self.poll_tasks.read().await == 0 {
+            return false;
+        }
+        self.last_poll_tick.read().await.elapsed() <= *self.poll_interval.read().await * 3
+    }
+
+    // BENCH LINK - Rapid *IDN? round-trips (refused while broadcasting unless forced,
+    // since the burst competes with the watchdog feed for the socket)
+    pub async fn bench_link(&self, iterations: u32, force: bool) -> Result<LinkBench, String> {
+        if !(1..=Config::MAX_BENCH_ITERATIONS).contains(&iterations) {
+            return Err(format!("Iterations must be 1-{}", Config::MAX_BENCH_ITERATIONS));
+        }
+        if self.state.read().await.broadcast.is_broadcasting() && !force {
+            return Err("Refusing to benchmark while broadcasting (use force)".to_string());
+        }
+
+        let mut samples = Vec::with_capacity(iterations as usize);
+        let started = Instant::now();
+        for i in 1..=iterations {
+            let sent = Instant::now();
+            self.query(ScpiCommands::IDENTITY).await
+                .map_err(|e| format!("Benchmark failed at iteration {}: {}", i, e))?;
+            samples.push(sent.elapsed().as_secs_f64() * 1000.0);
+        }
+        let total_secs = started.elapsed().as_secs_f64();
+
+        let bench = LinkBench {
+            iterations,
+            min_ms: samples.iter().copied().fold(f64::INFINITY, f64::min),
+            max_ms: samples.iter().copied().fold(0.0, f64::max),
+            avg_ms: samples.iter().sum::<f64>() / samples.len() as f64,
+            requests_per_sec: if total_secs > 0.0 { iterations as f64 / total_secs } else { 0.0 },
+        };
+        self.log_info(&format!(
+            "Link benchmark: {} round-trips, avg {:.2} ms, {:.0} req/s",
+            iterations, bench.avg_ms, bench.requests_per_sec
+        )).await;
+        Ok(bench)
+    }
+
+    // RUN DIAGNOSTICS - each check is independent; failures are reported, not fatal
+    pub async fn run_diagnostics(&self) -> DiagnosticReport {
+        let mut checks = Vec::new();
+        let mut check = |name: &str, passed: bool, detail: String| {
+            checks.push(DiagnosticCheck { name: name.to_string(), passed, detail });
+        };
+
+        // Reachability
+        let reachable = self.stream.read().await.is_some();
+        check("reachability", reachable,
+            if reachable { "Socket open".to_string() } else { "Not connected".to_string() });
+
+        // Poll task (watchdog feed + status)
+        let poll_age_ms = self.last_poll_tick.read().await.elapsed().as_millis();
+        let poll_healthy = self.poll_task_healthy().await;
+        check("poll_task", poll_healthy,
+            if poll_healthy { format!("Last tick {} ms ago", poll_age_ms) } else { "Poll task not running".to_string() });
+
+        // Identity + latency
+        let started = Instant::now();
+        let identity = self.query(ScpiCommands::IDENTITY).await;
+        let latency_ms = identity.as_ref().ok().map(|_| started.elapsed().as_millis() as u64);
+        match identity {
+            Ok(idn) => check("identity", true, idn.trim().to_string()),
+            Err(e) => check("identity", false, e),
+        }
+        match latency_ms {
+            Some(ms) => check("latency", true, format!("{} ms", ms)),
+            None => check("latency", false, "No round-trip".to_string()),
+        }
+
+        // Temperature
+        match self.query(ScpiCommands::TEMP_QUERY).await {
+            Ok(r) => match r.trim().parse::<f32>() {
+                Ok(temp) => check("temperature", true, format!("{:.1} C", temp)),
+                Err(_) => check("temperature", false, format!("Unparseable: {}", r.trim())),
+            },
+            Err(e) => check("temperature", false, e),
+        }
+
+        // Watchdog
+        match self.query(ScpiCommands::WATCHDOG_STATUS).await {
+            Ok(r) => {
+                let wd = WatchdogState::from_status(r.trim());
+                check("watchdog", wd != WatchdogState::Triggered, wd.display().to_string());
+            }
+            Err(e) => check("watchdog", false, e),
+        }
+
+        // Error queue ("0,No error" when empty)
+        match self.query(ScpiCommands::ERROR_QUERY).await {
+            Ok(r) => {
+                let r = r.trim();
+                check("error_queue", r.is_empty() || r.starts_with('0'), r.to_string());
+            }
+            Err(e) => check("error_queue", false, e),
+        }
+
+        // Channel sync drift (device vs local frequencies)
+        let local = self.state.read().await.channels.clone();
+        let mut drift = Vec::new();
+        let mut sync_error = None;
+        for channel in &local {
+            match self.query(&format!("{}{}?", ScpiCommands::FREQ_QUERY_PREFIX, channel.id)).await {
+                Ok(r) => match r.trim().parse::<u32>() {
+                    Ok(freq) if freq != channel.frequency => drift.push(format!(
+                        "CH{}: local {} Hz, device {} Hz", channel.id, channel.frequency, freq)),
+                    Ok(_) => {}
+                    Err(_) => drift.push(format!("CH{}: unparseable '{}'", channel.id, r.trim())),
+                },
+                Err(e) => {
+                    sync_error = Some(e);
+                    break;
+                }
+            }
+        }
+        match sync_error {
+            Some(e) => check("channel_sync", false, e),
+            None if drift.is_empty() => check("channel_sync", true, "All channels in sync".to_string()),
+            None => check("channel_sync", false, drift.join("; ")),
+        }
+
+        let all_passed = checks.iter().all(|c| c.passed);
+        DiagnosticReport {
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            checks,
+            latency_ms,
+            all_passed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use parking_lot::Mutex;
+    use tokio::net::TcpListener;
+
+    fn test_manager() -> NetworkManager {
+        NetworkManager::new(EventBus::new())
+    }
+
+    // MOCK DEVICE - answers each received line via `respond`, records everything
+    struct MockDevice {
+        addr: std::net::SocketAddr,
+        received: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MockDevice {
+        async fn spawn<F>(respond: F) -> Self
+        where
+            F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+        {
+            Self::spawn_on("127.0.0.1:0", respond).await
+        }
+
+        async fn spawn_on<F>(bind: &str, respond: F) -> Self
+        where
+            F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+        {
+            let listener = TcpListener::bind(bind).await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let received = Arc::new(Mutex::new(Vec::new()));
+            let respond = Arc::new(respond);
+
+            let log = received.clone();
+            tokio::spawn(async move {
+                while let Ok((socket, _)) = listener.accept().await {
+                    let respond = respond.clone();
+                    let log = log.clone();
+                    tokio::spawn(async move {
+                        let (read_half, mut write_half) = socket.into_split();
+                        let mut lines = BufReader::new(read_half).lines();
+                        while let Ok(Some(line)) = lines.next_line().await {
+                            let line = line.trim().to_string();
+                            log.lock().push(line.clone());
+                            if let Some(response) = respond(&line) {
+                                let _ = write_half.write_all(format!("{}\n", response).as_bytes()).await;
+                            }
+                        }
+                    });
+                }
+            });
+
+            Self { addr, received }
+        }
+
+        fn received(&self) -> Vec<String> {
+            self.received.lock().clone()
+        }
+    }
+
+    // Manager wired to the mock without spawning the poll task
+    async fn connected_manager(mock: &MockDevice) -> NetworkManager {
+        let manager = test_manager();
+        *manager.stream.write().await = Some(BufReader::new(TcpStream::connect(mock.addr).await.unwrap()));
+        manager.state.write().await.connection = ConnectionState::Connected;
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_console_verbose_toggle() {
+        let manager = test_manager();
+        assert_eq!(manager.is_console_verbose().await, cfg!(debug_assertions));
+
+        manager.set_console_verbose(false).await;
+        assert!(!manager.is_console_verbose().await);
+        manager.log_info("quiet entry").await;
+
+        manager.set_console_verbose(true).await;
+        assert!(manager.is_console_verbose().await);
+        manager.log_info("loud entry").await;
+
+        // In-memory audit log records regardless of console verbosity
+        let log = manager.get_audit_log().await;
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].message, "quiet entry");
+        assert_eq!(log[1].message, "loud entry");
+    }
+
+    #[tokio::test]
+    async fn test_recent_events_of_type() {
+        let manager = test_manager();
+        manager.event_bus.emit(EventType::WatchdogWarning);
+        manager.event_bus.emit(EventType::ChannelUpdated(3));
+        manager.event_bus.emit(EventType::WatchdogTriggered);
+        manager.event_bus.emit(EventType::ChannelUpdated(5));
+        manager.event_bus.emit(EventType::DeviceStateUpdated);
+        manager.event_bus.emit(EventType::ChannelUpdated(7));
+
+        let updates = manager.recent_events_of_type("ChannelUpdated", 10);
+        assert_eq!(updates.len(), 3);
+        assert!(matches!(updates[0], EventType::ChannelUpdated(3)));
+        assert!(matches!(updates[1], EventType::ChannelUpdated(5)));
+        assert!(matches!(updates[2], EventType::ChannelUpdated(7)));
+
+        // Limit keeps the most recent matches
+        let last_two = manager.recent_events_of_type("ChannelUpdated", 2);
+        assert_eq!(last_two.len(), 2);
+        assert!(matches!(last_two[0], EventType::ChannelUpdated(5)));
+        assert!(matches!(last_two[1], EventType::ChannelUpdated(7)));
+
+        let triggered = manager.recent_events_of_type("WatchdogTriggered", 10);
+        assert_eq!(triggered.len(), 1);
+        assert!(manager.recent_events_of_type("ReconnectFailed", 10).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resync_after_write_failure() {
+        // Mock pushes a stale line on connect, then answers *IDN?
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let _ = socket.write_all(b"STALE\n").await;
+                    let (read_half, mut write_half) = socket.split();
+                    let mut lines = BufReader::new(read_half).lines();
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        if line.trim() == "*IDN?" {
+                            let _ = write_half.write_all(b"RedPitaya,STEMlab125-10,MOCK,v1.0\n").await;
+                        }
+                    }
+                });
+            }
+        });
+
+        let manager = test_manager();
+
+        // Simulate a failed write: our write half is already shut down
+        let mut broken = TcpStream::connect(addr).await.unwrap();
+        broken.shutdown().await.unwrap();
+        *manager.stream.write().await = Some(BufReader::new(broken));
+        assert!(manager.send_command("*IDN?").await.is_err());
+        assert!(*manager.needs_resync.read().await);
+
+        // Next command on a healthy socket discards the stale line first
+        *manager.stream.write().await = Some(BufReader::new(TcpStream::connect(addr).await.unwrap()));
+        sleep(Duration::from_millis(50)).await;
+        let response = manager.query("*IDN?").await.unwrap();
+        assert!(response.starts_with("RedPitaya"));
+        assert!(!*manager.needs_resync.read().await);
+        assert!(manager.get_audit_log().await.iter().any(|e| e.message.starts_with("Resynced")));
+    }
+
+    #[tokio::test]
+    async fn test_retry_config_used_by_next_connect() {
+        let manager = test_manager();
+
+        let invalid = RetryConfig { multiplier: 0.5, ..RetryConfig::default() };
+        assert!(manager.set_retry_config(invalid).await.is_err());
+        assert_eq!(manager.get_retry_config().await.max_attempts, RetryConfig::default().max_attempts);
+
+        let fast = RetryConfig {
+            max_attempts: 2,
+            initial_delay_ms: 10,
+            max_delay_ms: 20,
+            multiplier: 1.0,
+        };
+        manager.set_retry_config(fast).await.unwrap();
+
+        // Grab a free port, then close it so connects are refused
+        let port = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let err = manager.connect("127.0.0.1", port).await.unwrap_err();
+        assert_eq!(err, "Connection failed after 2 attempts");
+    }
+
+    #[tokio::test]
+    async fn test_start_broadcast_confirmed() {
+        let output = Arc::new(Mutex::new(false));
+        let device = output.clone();
+        let mock = MockDevice::spawn(move |cmd| match cmd {
+            "OUTPUT:STATE ON" => { *device.lock() = true; None }
+            "OUTPUT:STATE OFF" => { *device.lock() = false; None }
+            "OUTPUT:STATE?" => Some(if *device.lock() { "1" } else { "0" }.to_string()),
+            _ => None,
+        }).await;
+
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.channels[0].enabled = true;
+
+        manager.start_broadcast().await.unwrap();
+        assert_eq!(manager.get_state().await.broadcast, BroadcastState::Broadcasting);
+        assert!(*output.lock());
+        assert_eq!(manager.recent_events_of_type("BroadcastStarted", 10).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_start_broadcast_timeout_reverts() {
+        // Device never reports output going live
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "OUTPUT:STATE?" => Some("0".to_string()),
+            _ => None,
+        }).await;
+
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.channels[0].enabled = true;
+
+        let err = manager.start_broadcast().await.unwrap_err();
+        assert!(err.contains("not confirmed"));
+        assert_eq!(manager.get_state().await.broadcast, BroadcastState::Idle);
+        assert!(manager.recent_events_of_type("BroadcastStarted", 10).is_empty());
+
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(mock.received().last().map(String::as_str), Some("OUTPUT:STATE OFF"));
+    }
+
+    #[tokio::test]
+    async fn test_list_and_select_audio_input() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "SOURCE:INPUT:LIST?" => Some("LINE1, LINE2,MIC".to_string()),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+
+        let inputs = manager.list_audio_inputs().await.unwrap();
+        assert_eq!(inputs, vec!["LINE1", "LINE2", "MIC"]);
+
+        manager.state.write().await.source = SourceMode::Adc;
+        manager.set_audio_input("LINE2").await.unwrap();
+        assert_eq!(manager.get_state().await.audio_input.as_deref(), Some("LINE2"));
+
+        sleep(Duration::from_millis(50)).await;
+        assert!(mock.received().contains(&"SOURCE:INPUT LINE2".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_audio_input_rejected_in_bram_mode() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+
+        assert_eq!(manager.get_state().await.source, SourceMode::Bram);
+        assert!(manager.set_audio_input("LINE1").await.is_err());
+        assert!(manager.get_state().await.audio_input.is_none());
+
+        sleep(Duration::from_millis(50)).await;
+        assert!(mock.received().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_diagnostics_report_partial_failures() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "*IDN?" => Some("RedPitaya,STEMlab125-10,MOCK,v1.0".to_string()),
+            "SYSTEM:TEMP?" => Some("N/A".to_string()),
+            "WATCHDOG:STATUS?" => Some("OK".to_string()),
+            "SYSTEM:ERROR?" => Some("-100,Command error".to_string()),
+            "FREQ:CH3?" => Some("745000".to_string()),
+            c if c.starts_with("FREQ:CH") => {
+                let ch: u32 = c["FREQ:CH".len()..c.len() - 1].parse().unwrap();
+                Some((540_000 + (ch - 1) * 100_000).to_string())
+            }
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+
+        let report = manager.run_diagnostics().await;
+        let get = |name: &str| report.checks.iter().find(|c| c.name == name).unwrap().clone();
+
+        assert!(get("reachability").passed);
+        assert!(get("identity").passed);
+        assert!(get("latency").passed);
+        assert!(report.latency_ms.is_some());
+        assert!(!get("temperature").passed);
+        assert!(get("watchdog").passed);
+        assert!(!get("error_queue").passed);
+        let sync = get("channel_sync");
+        assert!(!sync.passed);
+        assert!(sync.detail.contains("CH3"));
+        assert!(!report.all_passed);
+    }
+
+    #[tokio::test]
+    async fn test_diagnostics_report_when_disconnected() {
+        let manager = test_manager();
+        let report = manager.run_diagnostics().await;
+
+        assert_eq!(report.checks.len(), 8);
+        assert!(report.checks.iter().all(|c| !c.passed));
+        assert!(report.latency_ms.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_trip_requires_test_mode() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+
+        let err = manager.test_watchdog_trip(10_000).await.unwrap_err();
+        assert!(err.contains("test mode"));
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_trip_fires_event_and_stops_output() {
+        // Mock trips if no WATCHDOG:RESET arrives within 1s
+        let last_reset = Arc::new(Mutex::new(std::time::Instant::now()));
+        let device = last_reset.clone();
+        let mock = MockDevice::spawn(move |cmd| match cmd {
+            "WATCHDOG:RESET" => { *device.lock() = std::time::Instant::now(); None }
+            "STATUS?" => Some(if device.lock().elapsed() > Duration::from_millis(1000) {
+                "BROADCAST:0,WATCHDOG:2".to_string()
+            } else {
+                "BROADCAST:1,WATCHDOG:0".to_string()
+            }),
+            _ => None,
+        }).await;
+
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.broadcast = BroadcastState::Broadcasting;
+        *manager.watchdog_timeout.write().await = Duration::from_millis(1000);
+        *manager.is_running.write().await = true;
+        manager.spawn_poll_task();
+        manager.set_test_mode(true).await;
+
+        // Pause must exceed the timeout
+        assert!(manager.test_watchdog_trip(500).await.is_err());
+
+        let tripped = manager.test_watchdog_trip(1500).await.unwrap();
+        *manager.is_running.write().await = false;
+
+        assert!(tripped);
+        assert!(!manager.recent_events_of_type("WatchdogTriggered", 10).is_empty());
+        assert_eq!(manager.get_state().await.broadcast, BroadcastState::Idle);
+        assert!(!*manager.watchdog_paused.read().await);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_connect_spawns_one_poll_task() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "*IDN?" => Some("RedPitaya,STEMlab125-10,MOCK,v1.0".to_string()),
+            "STATUS?" => Some("BROADCAST:0,WATCHDOG:0".to_string()),
+            c if c.ends_with('?') => Some("540000".to_string()),
+            _ => None,
+        }).await;
+        let manager = test_manager();
+        let port = mock.addr.port();
+
+        let (first, second) = tokio::join!(
+            manager.connect("127.0.0.1", port),
+            manager.connect("127.0.0.1", port),
+        );
+        assert!(first.is_ok() != second.is_ok(), "exactly one connect should win");
+
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(*manager.poll_tasks.read().await, 1);
+
+        // A later connect is still refused while connected
+        assert_eq!(manager.connect("127.0.0.1", port).await.unwrap_err(), "Already connected");
+
+        manager.disconnect(true).await.unwrap();
+        sleep(Duration::from_millis(Config::POLL_INTERVAL_MS + 200)).await;
+        assert_eq!(*manager.poll_tasks.read().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_channels_grouped() {
+        let manager = test_manager();
+        {
+            let mut state = manager.state.write().await;
+            state.channels.reverse();  // Grouping must not depend on stored order
+            for channel in state.channels.iter_mut() {
+                channel.enabled = [2, 5, 11].contains(&channel.id);
+            }
+        }
+
+        let (enabled, disabled) = manager.channels_grouped().await;
+        let ids = |chs: &[Channel]| chs.iter().map(|c| c.id).collect::<Vec<_>>();
+        assert_eq!(ids(&enabled), vec![2, 5, 11]);
+        assert_eq!(ids(&disabled), vec![1, 3, 4, 6, 7, 8, 9, 10, 12]);
+    }
+
+    // Mock that tracks output state and confirms OUTPUT:STATE?
+    async fn output_mock() -> MockDevice {
+        let output = Arc::new(Mutex::new(false));
+        MockDevice::spawn(move |cmd| match cmd {
+            "OUTPUT:STATE ON" => { *output.lock() = true; None }
+            "OUTPUT:STATE OFF" => { *output.lock() = false; None }
+            "OUTPUT:STATE?" => Some(if *output.lock() { "1" } else { "0" }.to_string()),
+            _ => None,
+        }).await
+    }
+
+    #[tokio::test]
+    async fn test_output_toggle_too_soon_rejected() {
+        let mock = output_mock().await;
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.channels[0].enabled = true;
+        manager.set_min_output_interval(10_000).await;
+
+        manager.start_broadcast().await.unwrap();
+        let err = manager.stop_broadcast().await.unwrap_err();
+        assert!(err.contains("minimum interval"), "{}", err);
+        assert_eq!(manager.get_state().await.broadcast, BroadcastState::Broadcasting);
+
+        // Emergency stop always wins
+        manager.stop_emergency().await.unwrap();
+        assert_eq!(manager.get_state().await.broadcast, BroadcastState::Idle);
+
+        // ...but a restart right after is still rejected
+        assert!(manager.start_broadcast().await.is_err());
+        assert!(manager.start_emergency(false).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bram_message_info() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "BRAM:INFO? 2" => Some("evacuate_now,3.25,16384".to_string()),
+            c if c.starts_with("BRAM:INFO?") => Some("ERROR:OUT_OF_RANGE".to_string()),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+
+        let info = manager.bram_message_info(2).await.unwrap();
+        assert_eq!(info.index, 2);
+        assert_eq!(info.name, "evacuate_now");
+        assert_eq!(info.duration_secs, 3.25);
+        assert_eq!(info.samples, 16384);
+
+        // Device has no message at this slot
+        let err = manager.bram_message_info(9).await.unwrap_err();
+        assert!(err.contains("not available"), "{}", err);
+
+        // Beyond the 4-bit index - rejected without a round-trip
+        assert!(manager.bram_message_info(16).await.unwrap_err().contains("out of range"));
+        sleep(Duration::from_millis(50)).await;
+        assert!(!mock.received().contains(&"BRAM:INFO? 16".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_replay_events_in_order_with_spacing() {
+        let manager = test_manager();
+        let events = vec![
+            EventType::ConnectSuccess,
+            EventType::BroadcastStarted,
+            EventType::WatchdogTriggered,
+        ];
+
+        // Refused outside test mode
+        assert!(manager.replay_events(events.clone(), 10).await.is_err());
+
+        manager.set_test_mode(true).await;
+        let mut rx = manager.event_bus.subscribe();
+        let listener = tokio::spawn(async move {
+            let mut received = Vec::new();
+            while received.len() < 3 {
+                let event = rx.recv().await.unwrap();
+                received.push((event.type_name(), Instant::now()));
+            }
+            received
+        });
+        manager.replay_events(events, 50).await.unwrap();
+        let received = listener.await.unwrap();
+
+        let names: Vec<&str> = received.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["ConnectSuccess", "BroadcastStarted", "WatchdogTriggered"]);
+        for pair in received.windows(2) {
+            assert!(pair[1].1 - pair[0].1 >= Duration::from_millis(45));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remember_endpoint_roundtrip() {
+        let path = std::env::temp_dir().join(format!("amradio_endpoint_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let manager = test_manager().with_settings_path(path.clone());
+
+        // Missing file - defaults
+        assert_eq!(manager.last_endpoint(), Endpoint::default());
+
+        manager.remember_endpoint("10.0.0.7", 5025).await.unwrap();
+        assert_eq!(manager.last_endpoint(), Endpoint { ip: "10.0.0.7".to_string(), port: 5025 });
+        assert!(manager.remember_endpoint("", 5025).await.is_err());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_fatal_fault_stops_broadcast() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.broadcast = BroadcastState::Broadcasting;
+
+        manager.parse_status_response("BROADCAST:1,FAULT:OVERCURRENT,TEMP:51.0").await;
+
+        let state = manager.get_state().await;
+        assert_eq!(state.broadcast, BroadcastState::Idle);
+        assert_eq!(state.last_fault.as_deref(), Some("FAULT:OVERCURRENT"));
+        assert!(state.fault_active);
+        assert_eq!(state.last_stop_reason, Some(StopReason::Fault));
+        assert_eq!(manager.recent_events_of_type("DeviceFault", 10).len(), 1);
+        assert!(manager.get_audit_log().await.iter()
+            .any(|e| e.level == "ERROR" && e.message.contains("OVERCURRENT")));
+
+        sleep(Duration::from_millis(50)).await;
+        assert!(mock.received().contains(&"OUTPUT:STATE OFF".to_string()));
+
+        // Same fault on the next poll is not re-reported
+        manager.parse_status_response("FAULT:OVERCURRENT").await;
+        assert_eq!(manager.recent_events_of_type("DeviceFault", 10).len(), 1);
+
+        // Cleared fault keeps the record
+        manager.parse_status_response("FAULT:NONE").await;
+        let state = manager.get_state().await;
+        assert!(!state.fault_active);
+        assert_eq!(state.last_fault.as_deref(), Some("FAULT:OVERCURRENT"));
+    }
+
+    #[tokio::test]
+    async fn test_non_fatal_error_keeps_broadcasting() {
+        let manager = test_manager();
+        manager.state.write().await.broadcast = BroadcastState::Broadcasting;
+
+        manager.parse_status_response("ERROR:CRC").await;
+
+        let state = manager.get_state().await;
+        assert_eq!(state.broadcast, BroadcastState::Broadcasting);
+        assert_eq!(state.last_fault.as_deref(), Some("ERROR:CRC"));
+        assert_eq!(manager.recent_events_of_type("DeviceFault", 10).len(), 1);
+    }
+
+    async fn manager_with_enabled(freqs: &[(u8, u32)]) -> NetworkManager {
+        let manager = test_manager();
+        {
+            let mut state = manager.state.write().await;
+            for &(id, freq) in freqs {
+                let channel = state.channels.iter_mut().find(|c| c.id == id).unwrap();
+                channel.enabled = true;
+                channel.frequency = freq;
+            }
+        }
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_plan_bandwidth() {
+        let two = manager_with_enabled(&[(1, 540_000), (2, 840_000)]).await.plan_bandwidth().await;
+        assert_eq!(two.span_hz, 300_000);
+        assert_eq!(two.channel_count, 2);
+        assert!((two.utilization_pct - 25.0).abs() < 0.01);
+
+        let three = manager_with_enabled(&[(1, 1_000_000), (5, 600_000), (9, 1_500_000)])
+            .await.plan_bandwidth().await;
+        assert_eq!(three.span_hz, 900_000);
+        assert_eq!(three.channel_count, 3);
+        assert!((three.utilization_pct - 75.0).abs() < 0.01);
+
+        let single = manager_with_enabled(&[(4, 840_000)]).await.plan_bandwidth().await;
+        assert_eq!(single.span_hz, 0);
+        assert_eq!(single.channel_count, 1);
+
+        let none = test_manager().plan_bandwidth().await;
+        assert_eq!(none.span_hz, 0);
+        assert_eq!(none.channel_count, 0);
+        assert_eq!(none.utilization_pct, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_reliability_stats_failed_then_successful_reconnect() {
+        let manager = test_manager();
+        assert!(manager.reliability_stats().await.success_rate.is_none());
+
+        // Reserve a port, then close it so the first attempt is refused
+        let port = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        *manager.current_ip.write().await = Some("127.0.0.1".to_string());
+        *manager.current_port.write().await = Some(port);
+
+        let reconnect = {
+            let manager = manager.clone();
+            tokio::spawn(async move { manager.handle_connection_lost().await })
+        };
+
+        // Device comes back while the second attempt is waiting out its delay
+        while manager.recent_events_of_type("ReconnectAttempt", 10).len() < 2 {
+            sleep(Duration::from_millis(20)).await;
+        }
+        let _device = MockDevice::spawn_on(&format!("127.0.0.1:{}", port), any_query_mock()).await;
+        reconnect.await.unwrap();
+
+        let stats = manager.reliability_stats().await;
+        assert_eq!(stats.reconnect_attempts_total, 2);
+        assert_eq!(stats.reconnect_successes, 1);
+        assert_eq!(stats.success_rate, Some(0.5));
+
+        // Explicit disconnect starts a fresh session
+        manager.disconnect(true).await.unwrap();
+        let stats = manager.reliability_stats().await;
+        assert_eq!(stats.reconnect_attempts_total, 0);
+        assert!(stats.success_rate.is_none());
+    }
+
+    #[test]
+    fn test_validate_device_ip() {
+        assert_eq!(NetworkManager::validate_device_ip("192.168.0.101"), Ok(Ipv4Addr::new(192, 168, 0, 101)));
+        assert!(NetworkManager::validate_device_ip("10.0.0.2").is_ok());
+
+        for bad in [
+            "", "192.168.0", "192.168.0.256", "192.168.0.1.5", "192.168.000.1",
+            " 192.168.0.1", "fpga.local", "::1", "0.0.0.0", "255.255.255.255",
+            "224.0.0.1", "127.0.0.1",
+        ] {
+            assert!(NetworkManager::validate_device_ip(bad).is_err(), "accepted {:?}", bad);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_set_device_ip_rejects_invalid_before_sending() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+
+        let err = manager.set_device_ip("192.168.1.300").await.unwrap_err();
+        assert!(err.contains("Invalid IPv4"), "{}", err);
+
+        sleep(Duration::from_millis(50)).await;
+        assert!(mock.received().is_empty());
+        assert_eq!(manager.state.read().await.connection, ConnectionState::Connected);
+    }
+
+    async fn wait_for_event(manager: &NetworkManager, type_name: &str) {
+        timeout(Duration::from_secs(5), async {
+            while manager.recent_events_of_type(type_name, 1).is_empty() {
+                sleep(Duration::from_millis(20)).await;
+            }
+        }).await.unwrap_or_else(|_| panic!("no {} event", type_name));
+    }
+
+    #[tokio::test]
+    async fn test_begin_connect_returns_before_outcome() {
+        let mock = MockDevice::spawn(|_| Some("0".to_string())).await;
+        let manager = test_manager();
+
+        manager.begin_connect("127.0.0.1", mock.addr.port()).await.unwrap();
+
+        // Nothing has resolved yet - the attempt runs in the background
+        assert!(manager.recent_events_of_type("ConnectSuccess", 1).is_empty());
+        assert!(*manager.connecting.read().await);
+        assert!(manager.begin_connect("127.0.0.1", mock.addr.port()).await.is_err());
+
+        wait_for_event(&manager, "ConnectSuccess").await;
+        assert_eq!(manager.recent_events_of_type("Connecting", 10).len(), 1);
+        assert_eq!(manager.state.read().await.connection, ConnectionState::Connected);
+        manager.disconnect(true).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_begin_connect_reports_failure() {
+        let manager = test_manager();
+        manager.set_retry_config(RetryConfig {
+            max_attempts: 1,
+            initial_delay_ms: 10,
+            max_delay_ms: 10,
+            multiplier: 1.0,
+        }).await.unwrap();
+
+        let port = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        manager.begin_connect("127.0.0.1", port).await.unwrap();
+
+        wait_for_event(&manager, "ConnectFailed").await;
+        assert!(manager.recent_events_of_type("ConnectSuccess", 1).is_empty());
+        sleep(Duration::from_millis(50)).await;
+        assert!(!*manager.connecting.read().await);
+    }
+
+    #[test]
+    fn test_parse_serial() {
+        assert_eq!(parse_serial("RedPitaya,STEMlab125-10,MOCK,v1.0\n"), Some("MOCK".to_string()));
+        assert_eq!(parse_serial("RedPitaya, STEMlab125-10, SN0042 , v2.1"), Some("SN0042".to_string()));
+
+        // Truncated or malformed identities have no serial
+        assert_eq!(parse_serial("RedPitaya,STEMlab125-10,MOCK"), None);
+        assert_eq!(parse_serial("RedPitaya"), None);
+        assert_eq!(parse_serial("RedPitaya,STEMlab125-10,,v1.0"), None);
+        assert_eq!(parse_serial(""), None);
+    }
+
+    #[tokio::test]
+    async fn test_serial_cached_during_init() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "*IDN?" => Some("RedPitaya,STEMlab125-10,MOCK,v1.0".to_string()),
+            _ => Some("0".to_string()),
+        }).await;
+        let manager = connected_manager(&mock).await;
+        assert_eq!(manager.serial().await, None);
+
+        manager.initialize_device().await.unwrap();
+        assert_eq!(manager.serial().await, Some("MOCK".to_string()));
+    }
+
+    async fn set_amplitudes(manager: &NetworkManager, amplitudes: &[(u8, f32)]) {
+        let mut state = manager.state.write().await;
+        for &(id, amplitude) in amplitudes {
+            let channel = state.channels.iter_mut().find(|c| c.id == id).unwrap();
+            channel.enabled = true;
+            channel.amplitude = amplitude;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_amplitude_budget() {
+        let manager = test_manager();
+        manager.set_max_total_amplitude(1.5).await.unwrap();
+
+        // Below
+        set_amplitudes(&manager, &[(1, 0.5), (2, 0.25), (3, 0.25)]).await;
+        let budget = manager.amplitude_budget().await;
+        assert_eq!(budget.enabled_channels, 3);
+        assert!((budget.total - 1.0).abs() < 1e-6);
+        assert!(budget.within_budget);
+
+        // At
+        set_amplitudes(&manager, &[(4, 0.3), (5, 0.2)]).await;
+        let budget = manager.amplitude_budget().await;
+        assert_eq!(budget.enabled_channels, 5);
+        assert!(budget.within_budget);
+
+        // Above
+        set_amplitudes(&manager, &[(6, 0.1)]).await;
+        let budget = manager.amplitude_budget().await;
+        assert!((budget.total - 1.6).abs() < 1e-6);
+        assert!(!budget.within_budget);
+
+        assert!(manager.set_max_total_amplitude(0.0).await.is_err());
+        assert!(manager.set_max_total_amplitude(f32::NAN).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_start_broadcast_rejects_over_budget() {
+        let mock = output_mock().await;
+        let manager = connected_manager(&mock).await;
+        manager.set_max_total_amplitude(2.0).await.unwrap();
+        set_amplitudes(&manager, &[(1, 1.0), (2, 1.0), (3, 0.5)]).await;
+
+        let err = manager.start_broadcast().await.unwrap_err();
+        assert!(err.contains("2.50"), "{}", err);
+
+        sleep(Duration::from_millis(50)).await;
+        assert!(mock.received().is_empty());
+        assert_eq!(manager.state.read().await.broadcast, BroadcastState::Idle);
+    }
+
+    #[tokio::test]
+    async fn test_scpi_file_trace() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "*IDN?" => Some("RedPitaya,STEMlab125-10,MOCK,v1.0".to_string()),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+        let path = std::env::temp_dir().join(format!("scpi_trace_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        manager.send_command("FREQ:CH1 540000").await.unwrap();  // Before the trace - not recorded
+        manager.start_scpi_file_trace(&path).await.unwrap();
+        manager.send_command(ScpiCommands::SOURCE_BRAM).await.unwrap();
+        manager.query(ScpiCommands::IDENTITY).await.unwrap();
+        manager.stop_scpi_file_trace().await.unwrap();
+        manager.send_command("FREQ:CH2 640000").await.unwrap();  // After - not recorded
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 3, "{}", contents);
+        assert!(lines[0].ends_with(">> SOURCE:MODE BRAM"));
+        assert!(lines[1].ends_with(">> *IDN?"));
+        assert!(lines[2].ends_with("<< RedPitaya,STEMlab125-10,MOCK,v1.0"));
+        assert!(lines[0].starts_with(&chrono::Local::now().format("%Y-").to_string()));
+
+        assert!(manager.stop_scpi_file_trace().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_is_idempotent() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+
+        manager.disconnect(true).await.unwrap();
+        manager.disconnect(true).await.unwrap();
+
+        let log = manager.get_audit_log().await;
+        assert_eq!(log.iter().filter(|e| e.message == "Disconnecting...").count(), 1);
+        assert_eq!(manager.recent_events_of_type("Disconnected", 10).len(), 1);
+
+        // Never connected at all - silent no-op
+        let idle = test_manager();
+        idle.disconnect(true).await.unwrap();
+        assert!(idle.get_audit_log().await.is_empty());
+        assert!(idle.recent_events_of_type("ConnectionStateChanged", 10).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_after_failed_reconnect_clears_session() {
+        // Auto-reconnect gave up: already Disconnected, but the session info is still set
+        let manager = test_manager();
+        *manager.current_ip.write().await = Some("127.0.0.1".to_string());
+        *manager.current_port.write().await = Some(5000);
+        manager.reliability.write().await.reconnect_attempts_total = 3;
+
+        manager.disconnect(true).await.unwrap();
+
+        assert_eq!(*manager.current_ip.read().await, None);
+        assert_eq!(*manager.current_port.read().await, None);
+        assert_eq!(manager.reliability_stats().await.reconnect_attempts_total, 0);
+        assert!(manager.get_audit_log().await.is_empty());
+    }
+
+    // Mock that applies CHn:FREQ / CHn:OUTPUT and answers the matching queries
+    async fn channel_mock(freq_offset: u32) -> MockDevice {
+        let channels = Arc::new(Mutex::new(std::collections::HashMap::<String, String>::new()));
+        MockDevice::spawn(move |cmd| {
+            let mut channels = channels.lock();
+            if let Some((key, value)) = cmd.split_once(' ') {
+                channels.insert(key.to_string(), value.to_string());
+                return None;
+            }
+            let ch = cmd.trim_end_matches('?');
+            if let Some(n) = ch.strip_prefix("FREQ:CH") {
+                let freq: u32 = channels.get(&format!("CH{}:FREQ", n))?.parse().ok()?;
+                Some((freq + freq_offset).to_string())
+            } else if let Some(n) = ch.strip_prefix("OUTPUT:CH") {
+                channels.get(&format!("CH{}:OUTPUT", n)).cloned()
+            } else {
+                None
+            }
+        }).await
+    }
+
+    #[tokio::test]
+    async fn test_set_channel_verify_matching() {
+        let mock = channel_mock(0).await;
+        let manager = connected_manager(&mock).await;
+
+        manager.set_channel(3, 700_000, true, None, true).await.unwrap();
+
+        let channel = manager.get_state().await.channels[2].clone();
+        assert_eq!((channel.frequency, channel.enabled), (700_000, true));
+        assert_eq!(mock.received(), vec!["CH3:FREQ 700000", "CH3:OUTPUT ON", "FREQ:CH3?", "OUTPUT:CH3?"]);
+    }
+
+    #[tokio::test]
+    async fn test_set_channel_verify_mismatch() {
+        let mock = channel_mock(1_000).await;
+        let manager = connected_manager(&mock).await;
+        let before = manager.get_state().await.channels[2].clone();
+
+        let err = manager.set_channel(3, 700_000, true, None, true).await.unwrap_err();
+        assert!(err.contains("701000"), "{}", err);
+
+        // Local state is not updated with unconfirmed values
+        let after = manager.get_state().await.channels[2].clone();
+        assert_eq!((after.frequency, after.enabled), (before.frequency, before.enabled));
+    }
+
+    #[tokio::test]
+    async fn test_set_channel_fast_path_skips_queries() {
+        let mock = channel_mock(1_000).await;
+        let manager = connected_manager(&mock).await;
+
+        manager.set_channel(3, 700_000, true, None, false).await.unwrap();
+
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(mock.received(), vec!["CH3:FREQ 700000", "CH3:OUTPUT ON"]);
+    }
+
+    #[tokio::test]
+    async fn test_mute_unmute_restores_amplitudes() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        set_amplitudes(&manager, &[(1, 0.8), (2, 0.35), (7, 0.125)]).await;
+        let before: Vec<f32> = manager.get_state().await.channels.iter().map(|c| c.amplitude).collect();
+
+        manager.mute_all().await.unwrap();
+        let state = manager.get_state().await;
+        assert!(state.muted);
+        assert!(state.channels.iter().all(|c| c.amplitude == 0.0));
+        assert_eq!(state.channels.iter().filter(|c| c.enabled).count(), 3);  // Output state untouched
+        assert!(manager.mute_all().await.is_err());
+
+        manager.unmute_all().await.unwrap();
+        let state = manager.get_state().await;
+        assert!(!state.muted);
+        let after: Vec<f32> = state.channels.iter().map(|c| c.amplitude).collect();
+        assert_eq!(after, before);
+        assert!(manager.unmute_all().await.is_err());
+
+        sleep(Duration::from_millis(50)).await;
+        let received = mock.received();
+        assert_eq!(received.len(), 24);
+        assert!(received[..12].iter().all(|cmd| cmd.starts_with("AMP:CH") && cmd.ends_with(" 0")));
+        assert!(received.contains(&"AMP:CH2 0.35".to_string()));
+        assert!(!received.iter().any(|cmd| cmd.starts_with("OUTPUT") || cmd.contains(":OUTPUT")));
+    }
+
+    #[tokio::test]
+    async fn test_poll_task_health() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "STATUS?" => Some("BROADCAST:0,WATCHDOG:0".to_string()),
+            c if c.ends_with('?') => Some("540000".to_string()),
+            _ => None,
+        }).await;
+        let manager = test_manager();
+        assert!(!manager.poll_task_healthy().await);
+
+        manager.connect("127.0.0.1", mock.addr.port()).await.unwrap();
+        sleep(Duration::from_millis(Config::POLL_INTERVAL_MS + 200)).await;
+        assert!(manager.poll_task_healthy().await);
+
+        manager.disconnect(true).await.unwrap();
+        sleep(Duration::from_millis(Config::POLL_INTERVAL_MS + 200)).await;
+        assert!(!manager.poll_task_healthy().await);
+
+        // A task that died without cleaning up: counted as live, but no recent tick
+        *manager.poll_tasks.write().await = 1;
+        *manager.last_poll_tick.write().await = Instant::now() - Duration::from_millis(Config::POLL_INTERVAL_MS * 4);
+        assert!(!manager.poll_task_healthy().await);
+    }
+
+    #[tokio::test]
+    async fn test_import_channels_csv() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        let dir = std::env::temp_dir();
+
+        // Header + two rows - applied
+        let good = dir.join(format!("channels_good_{}.csv", std::process::id()));
+        std::fs::write(&good, "id,frequency,enabled,amplitude,phase\n2, 700000, 1, 0.5, 90\n5,900000,0,0.25,0\n").unwrap();
+        let report = manager.import_channels_csv(&good).await.unwrap();
+        let _ = std::fs::remove_file(&good);
+        assert!(report.applied);
+        assert_eq!(report.rows.len(), 2);
+        assert!(report.rows.iter().all(|r| r.ok));
+
+        let channel = manager.get_state().await.channels[1].clone();
+        assert_eq!((channel.frequency, channel.enabled, channel.amplitude, channel.phase), (700_000, true, 0.5, 90.0));
+        sleep(Duration::from_millis(50)).await;
+        assert!(mock.received().contains(&"PHASE:CH2 90".to_string()));
+
+        // Bad frequency row - nothing applied
+        let sent = mock.received().len();
+        let bad = dir.join(format!("channels_bad_{}.csv", std::process::id()));
+        std::fs::write(&bad, "3,800000,1,0.5,0\n4,2000000,1,0.5,0\n").unwrap();
+        let report = manager.import_channels_csv(&bad).await.unwrap();
+        let _ = std::fs::remove_file(&bad);
+        assert!(!report.applied);
+        assert!(report.rows[0].ok);
+        assert!(!report.rows[1].ok);
+        assert_eq!(report.rows[1].line, 2);
+
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(mock.received().len(), sent);
+        assert_eq!(manager.get_state().await.channels[2].frequency, 740_000);
+
+        // Well-formed, but the second row is outside CH6's limits - the first isn't applied either
+        manager.set_channel_bounds(6, Some(ChannelBounds { min: 530_000, max: 800_000 })).await.unwrap();
+        let limited = dir.join(format!("channels_limited_{}.csv", std::process::id()));
+        std::fs::write(&limited, "3,800000,1,0.5,0\n6,1200000,1,0.5,0\n").unwrap();
+        let report = manager.import_channels_csv(&limited).await.unwrap();
+        let _ = std::fs::remove_file(&limited);
+        assert!(!report.applied);
+        assert!(report.rows[0].ok);
+        assert_eq!((report.rows[1].ok, report.rows[1].channel), (false, Some(6)));
+        assert!(report.rows[1].message.contains("CH6 limits"), "{}", report.rows[1].message);
+
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(mock.received().len(), sent);
+        assert_eq!(manager.get_state().await.channels[2].frequency, 740_000);
+    }
+
+    #[test]
+    fn test_protocol_version_evaluate() {
+        assert_eq!(parse_protocol_version("v1.0"), Some((1, 0)));
+        assert_eq!(parse_protocol_version(" 2 "), Some((2, 0)));
+        assert_eq!(parse_protocol_version("MOCK"), None);
+
+        let matching = ProtocolCheck::evaluate(Some("v1.0"), "1.0");
+        assert_eq!(matching.status, ProtocolStatus::Match);
+        assert!(matching.compatible);
+
+        let minor = ProtocolCheck::evaluate(Some("1.3"), "1.0");
+        assert_eq!(minor.status, ProtocolStatus::MinorMismatch);
+        assert!(minor.compatible);
+
+        let major = ProtocolCheck::evaluate(Some("2.0"), "1.0");
+        assert_eq!(major.status, ProtocolStatus::MajorMismatch);
+        assert!(!major.compatible);
+        assert_eq!(major.device_version.as_deref(), Some("2.0"));
+        assert_eq!(major.expected_version, "1.0");
+
+        assert_eq!(ProtocolCheck::evaluate(None, "1.0").status, ProtocolStatus::Unknown);
+    }
+
+    #[tokio::test]
+    async fn test_check_protocol_version_warns_on_minor_mismatch() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "SYSTEM:PROTO?" => Some("1.4".to_string()),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+
+        let check = manager.check_protocol_version().await;
+        assert_eq!(check.status, ProtocolStatus::MinorMismatch);
+        assert_eq!(manager.get_state().await.protocol_version.as_deref(), Some("1.4"));
+
+        let log = manager.get_audit_log().await;
+        assert!(log.iter().any(|e| e.level == "WARNING" && e.message.contains("1.4")));
+    }
+
+    #[tokio::test]
+    async fn test_enable_zero_amplitude_channel_gets_default() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        manager.set_default_amplitude(0.75).await.unwrap();
+        manager.state.write().await.channels[1].amplitude = 0.0;
+
+        manager.set_channel(2, 640_000, true, None, false).await.unwrap();
+        assert_eq!(manager.get_state().await.channels[1].amplitude, 0.75);
+
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(mock.received(), vec!["CH2:FREQ 640000", "AMP:CH2 0.75", "CH2:OUTPUT ON"]);
+        assert!(manager.get_audit_log().await.iter().any(|e| e.level == "WARNING" && e.message.contains("CH2")));
+    }
+
+    #[tokio::test]
+    async fn test_enable_with_explicit_zero_amplitude_is_respected() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+
+        manager.set_channel(2, 640_000, true, Some(0.0), false).await.unwrap();
+        assert_eq!(manager.get_state().await.channels[1].amplitude, 0.0);
+
+        // While muted, silence is intentional - no bump
+        manager.state.write().await.muted = true;
+        manager.set_channel(2, 640_000, true, None, false).await.unwrap();
+        assert_eq!(manager.get_state().await.channels[1].amplitude, 0.0);
+
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(mock.received().iter().filter(|c| c.starts_with("AMP:CH2")).count(), 1);
+        assert!(manager.set_channel(2, 640_000, true, Some(1.5), false).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_trigger_history() {
+        let manager = test_manager();
+
+        for _ in 0..3 {
+            manager.parse_status_response("WATCHDOG:2").await;
+            manager.parse_status_response("WATCHDOG:2").await;  // Still tripped - not a new trigger
+            manager.parse_status_response("WATCHDOG:0").await;
+            sleep(Duration::from_millis(5)).await;
+        }
+
+        let history = manager.watchdog_trigger_history().await;
+        assert_eq!(history.len(), 3);
+        assert!(history.windows(2).all(|w| w[0] < w[1]), "{:?}", history);
+
+        // Bounded - oldest entries drop off
+        for _ in 0..Config::MAX_WATCHDOG_TRIGGER_HISTORY {
+            manager.parse_status_response("WATCHDOG:2").await;
+            manager.parse_status_response("WATCHDOG:0").await;
+        }
+        let capped = manager.watchdog_trigger_history().await;
+        assert_eq!(capped.len(), Config::MAX_WATCHDOG_TRIGGER_HISTORY);
+        assert!(capped[0] >= history[2]);
+    }
+
+    // Mock that reports `reported` for SOURCE:MODE? (None = echo what was set)
+    async fn source_mock(reported: Option<&'static str>) -> MockDevice {
+        let mode = Arc::new(Mutex::new("BRAM".to_string()));
+        MockDevice::spawn(move |cmd| {
+            if let Some(new_mode) = cmd.strip_prefix("SOURCE:MODE ") {
+                *mode.lock() = new_mode.to_string();
+                None
+            } else if cmd == "SOURCE:MODE?" {
+                Some(reported.map(str::to_string).unwrap_or_else(|| mode.lock().clone()))
+            } else {
+                None
+            }
+        }).await
+    }
+
+    #[tokio::test]
+    async fn test_switch_source_live_confirmed() {
+        let mock = source_mock(None).await;
+        let manager = connected_manager(&mock).await;
+        set_amplitudes(&manager, &[(1, 0.8), (2, 0.4)]).await;
+
+        manager.switch_source_live(SourceMode::Adc, true).await.unwrap();
+
+        let state = manager.get_state().await;
+        assert_eq!(state.source, SourceMode::Adc);
+        assert_eq!((state.channels[0].amplitude, state.channels[1].amplitude), (0.8, 0.4));
+
+        sleep(Duration::from_millis(50)).await;
+        let received = mock.received();
+        let switch = received.iter().position(|c| c == "SOURCE:MODE ADC").unwrap();
+        assert!(received[..switch].contains(&"AMP:CH1 0".to_string()));  // Ramped down first
+        assert_eq!(received.last().unwrap(), "AMP:CH2 0.4");
+        assert!(!received.iter().any(|c| c.contains("OUTPUT")));
+    }
+
+    #[tokio::test]
+    async fn test_switch_source_live_rejected_restores_amplitude() {
+        let mock = source_mock(Some("BRAM")).await;
+        let manager = connected_manager(&mock).await;
+        set_amplitudes(&manager, &[(3, 0.6)]).await;
+
+        let err = manager.switch_source_live(SourceMode::Adc, true).await.unwrap_err();
+        assert!(err.contains("rejected"), "{}", err);
+
+        let state = manager.get_state().await;
+        assert_eq!(state.source, SourceMode::Bram);
+        assert_eq!(state.channels[2].amplitude, 0.6);
+
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(mock.received().last().unwrap(), "AMP:CH3 0.6");
+    }
+
+    #[tokio::test]
+    async fn test_switch_source_live_ramp_failure_still_restores() {
+        // Link gone: the ramp-down fails, the restore is still attempted and its failure logged
+        let manager = test_manager();
+        manager.state.write().await.connection = ConnectionState::Connected;
+        set_amplitudes(&manager, &[(1, 0.8)]).await;
+
+        let err = manager.switch_source_live(SourceMode::Adc, true).await.unwrap_err();
+        assert_eq!(err, "Not connected");
+        assert_eq!(manager.get_state().await.source, SourceMode::Bram);
+        let errors = manager.get_audit_log_level("ERROR").await;
+        assert!(errors.iter().any(|e| e.message.contains("Failed to restore levels")));
+    }
+
+    #[tokio::test]
+    async fn test_switch_source_live_without_ramp() {
+        let mock = source_mock(None).await;
+        let manager = connected_manager(&mock).await;
+        set_amplitudes(&manager, &[(1, 0.8)]).await;
+
+        manager.switch_source_live(SourceMode::Adc, false).await.unwrap();
+
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(mock.received(), vec!["SOURCE:MODE ADC", "SOURCE:MODE?"]);
+    }
+
+    #[tokio::test]
+    async fn test_diff_scene() {
+        let manager = test_manager();
+        let mut scene = manager.capture_scene("evening").await;
+        assert!(manager.diff_scene(&scene).await.is_empty());
+
+        scene.source = SourceMode::Adc;
+        scene.channels[1].frequency = 700_000;
+        scene.channels[1].enabled = true;
+        scene.channels[4].amplitude = 0.5;
+
+        let diffs = manager.diff_scene(&scene).await;
+        assert_eq!(diffs, vec![
+            "Source: BRAM -> ADC",
+            "CH2 frequency: 640.0 kHz -> 700.0 kHz",
+            "CH2: disabled -> enabled",
+            "CH5 level: 1.00 -> 0.50",
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_temperature_hysteresis() {
+        let manager = test_manager();
+        manager.set_temperature_thresholds(70.0, 65.0).await.unwrap();
+        assert!(manager.set_temperature_thresholds(60.0, 65.0).await.is_err());
+
+        // Hovering inside the band around the high threshold
+        for temp in ["69.9", "70.0", "69.5", "70.2", "66.0", "71.0", "65.0"] {
+            manager.parse_status_response(&format!("TEMP:{}", temp)).await;
+        }
+        assert_eq!(manager.recent_events_of_type("OverTemperature", 10).len(), 1);
+        assert!(manager.recent_events_of_type("TemperatureNormal", 10).is_empty());
+        assert!(manager.get_state().await.over_temperature);
+
+        // Clears once below low, then hovers around it
+        for temp in ["64.9", "65.5", "64.0", "69.0", "64.5"] {
+            manager.parse_status_response(&format!("TEMP:{}", temp)).await;
+        }
+        assert_eq!(manager.recent_events_of_type("OverTemperature", 10).len(), 1);
+        assert_eq!(manager.recent_events_of_type("TemperatureNormal", 10).len(), 1);
+        assert!(!manager.get_state().await.over_temperature);
+
+        // A real second excursion trips again
+        manager.parse_status_response("TEMP:72.0").await;
+        assert_eq!(manager.recent_events_of_type("OverTemperature", 10).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_effective_config_reflects_runtime_changes() {
+        let manager = test_manager();
+        let config = manager.effective_config().await;
+        assert_eq!(config.poll_interval_ms, Config::POLL_INTERVAL_MS);
+        assert_eq!(config.watchdog_timeout_ms, Config::WATCHDOG_TIMEOUT_SECS * 1000);
+
+        manager.set_poll_interval(250).await.unwrap();
+        manager.set_temperature_thresholds(80.0, 72.0).await.unwrap();
+        manager.set_min_output_interval(500).await;
+
+        let config = manager.effective_config().await;
+        assert_eq!(config.poll_interval_ms, 250);
+        assert_eq!((config.temperature.high, config.temperature.low), (80.0, 72.0));
+        assert_eq!(config.min_output_interval_ms, 500);
+
+        // Too fast, or too slow to keep the watchdog fed
+        assert!(manager.set_poll_interval(10).await.is_err());
+        assert!(manager.set_poll_interval(Config::WATCHDOG_TIMEOUT_SECS * 1000).await.is_err());
+        assert!(manager.set_poll_interval(u64::MAX).await.is_err());
+        assert_eq!(manager.effective_config().await.poll_interval_ms, 250);
+    }
+
+    #[tokio::test]
+    async fn test_safe_shutdown() {
+        let mock = output_mock().await;
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.channels[0].enabled = true;
+        manager.state.write().await.channels[3].enabled = true;
+        manager.set_min_output_interval(10_000).await;
+        manager.start_broadcast().await.unwrap();
+
+        // Bypasses the output interval - shutdown is a safety action
+        manager.safe_shutdown().await.unwrap();
+
+        let state = manager.get_state().await;
+        assert_eq!(state.connection, ConnectionState::Disconnected);
+        assert_eq!(state.broadcast, BroadcastState::Idle);
+        assert!(state.channels.iter().all(|c| !c.enabled));
+
+        sleep(Duration::from_millis(50)).await;
+        let received = mock.received();
+        let off = received.iter().position(|c| c == "OUTPUT:STATE OFF").unwrap();
+        let first_disable = received.iter().position(|c| c == "CH1:OUTPUT OFF").unwrap();
+        assert!(off < first_disable);
+        assert_eq!(received.iter().filter(|c| c.ends_with(":OUTPUT OFF")).count(), 12);
+
+        let log: Vec<String> = manager.get_audit_log().await.into_iter().map(|e| e.message).collect();
+        let steps: Vec<&String> = log.iter().filter(|m| m.starts_with("Safe shutdown")).collect();
+        assert_eq!(steps.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_safe_shutdown_unconfirmed_stop_keeps_link() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "OUTPUT:STATE?" => Some("1".to_string()),  // Carrier stuck on
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+
+        let err = manager.safe_shutdown().await.unwrap_err();
+        assert!(err.contains("may still be live"), "{}", err);
+
+        assert_eq!(manager.get_state().await.connection, ConnectionState::Connected);
+        sleep(Duration::from_millis(50)).await;
+        assert!(!mock.received().iter().any(|c| c.starts_with("CH")));
+    }
+
+    #[tokio::test]
+    async fn test_add_note() {
+        let manager = test_manager();
+        manager.log_info("Shift start").await;
+        manager.add_note("  Handover:\tCH3 drifting,\nwatch temp\u{7}  ").await.unwrap();
+
+        let log = manager.get_audit_log().await;
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[1].level, "NOTE");
+        assert_eq!(log[1].message, "Handover: CH3 drifting, watch temp");
+
+        let notes = manager.get_audit_log_level("note").await;
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].message, log[1].message);
+
+        assert!(manager.add_note(" \n\t ").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_add_note_length_limited() {
+        let manager = test_manager();
+        let long = "é".repeat(Config::MAX_NOTE_LENGTH + 50);
+
+        manager.add_note(&long).await.unwrap();
+
+        let note = &manager.get_audit_log().await[0];
+        assert_eq!(note.message.chars().count(), Config::MAX_NOTE_LENGTH);
+    }
+
+    #[tokio::test]
+    async fn test_bench_link() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "*IDN?" => Some("RedPitaya,STEMlab125-10,MOCK,v1.0".to_string()),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+
+        let bench = manager.bench_link(5, false).await.unwrap();
+        assert_eq!(bench.iterations, 5);
+        assert!(bench.min_ms > 0.0);
+        assert!(bench.min_ms <= bench.avg_ms && bench.avg_ms <= bench.max_ms);
+        assert!(bench.requests_per_sec > 0.0);
+        assert_eq!(mock.received().len(), 5);
+
+        assert!(manager.bench_link(0, false).await.is_err());
+
+        // Guarded while broadcasting unless forced
+        manager.state.write().await.broadcast = BroadcastState::Broadcasting;
+        assert!(manager.bench_link(2, false).await.is_err());
+        assert!(manager.bench_link(2, true).await.is_ok());
+        assert_eq!(manager.get_state().await.broadcast, BroadcastState::Broadcasting);
+    }
+
+    #[tokio::test]
+    async fn test_resume_after_watchdog() {
+        let mock = output_mock().await;
+        let manager = connected_manager(&mock).await;
+        manager.set_min_output_interval(0).await;
+        set_amplitudes(&manager, &[(2, 0.5), (5, 0.25)]).await;
+        manager.start_broadcast().await.unwrap();
+
+        // Manual stop can't be resumed this way
+        manager.stop_broadcast().await.unwrap();
+        assert!(manager.resume_after_watchdog().await.unwrap_err().contains("not a watchdog"));
+
+        manager.start_broadcast().await.unwrap();
+        manager.parse_status_response("WATCHDOG:2").await;
+        let state = manager.get_state().await;
+        assert_eq!(state.broadcast, BroadcastState::Idle);
+        assert_eq!(state.last_stop_reason, Some(StopReason::Watchdog));
+
+        // Operator fiddles with the plan while waiting
+        manager.state.write().await.channels[1].enabled = false;
+
+        let err = manager.resume_after_watchdog().await.unwrap_err();
+        assert!(err.contains("TRIGGERED"), "{}", err);
+        assert_eq!(manager.get_state().await.broadcast, BroadcastState::Idle);
+
+        manager.parse_status_response("WATCHDOG:0").await;
+        manager.resume_after_watchdog().await.unwrap();
+
+        let state = manager.get_state().await;
+        assert_eq!(state.broadcast, BroadcastState::Broadcasting);
+        let enabled: Vec<u8> = state.channels.iter().filter(|c| c.enabled).map(|c| c.id).collect();
+        assert_eq!(enabled, vec![2, 5]);
+        assert_eq!(state.channels[1].amplitude, 0.5);
+    }
+
+    #[test]
+    fn test_channel_capability_parse() {
+        assert_eq!(
+            ChannelCapability::parse(3, " output, adc "),
+            Some(ChannelCapability { channel: 3, output: true, adc: true })
+        );
+        assert!(!ChannelCapability::parse(4, "OUTPUT").unwrap().adc);
+        assert_eq!(ChannelCapability::parse(5, "540000"), None);
+    }
+
+    #[tokio::test]
+    async fn test_channel_capabilities_enforced() {
+        // CH2 and CH7 are output-only
+        let mock = MockDevice::spawn(|cmd| {
+            let ch: u8 = cmd.strip_prefix("CH")?.strip_suffix(":CAP?")?.parse().ok()?;
+            Some(if ch == 2 || ch == 7 { "OUTPUT" } else { "OUTPUT,ADC" }.to_string())
+        }).await;
+        let manager = connected_manager(&mock).await;
+        manager.query_channel_capabilities().await;
+
+        let capabilities = manager.get_channel_capabilities().await;
+        assert_eq!(capabilities.len(), 12);
+        let output_only: Vec<u8> = capabilities.iter().filter(|c| !c.adc).map(|c| c.channel).collect();
+        assert_eq!(output_only, vec![2, 7]);
+
+        // ADC refused while an output-only channel is enabled
+        set_amplitudes(&manager, &[(1, 1.0), (2, 1.0)]).await;
+        let err = manager.set_source(SourceMode::Adc).await.unwrap_err();
+        assert!(err.contains("CH2"), "{}", err);
+        assert!(manager.switch_source_live(SourceMode::Adc, false).await.is_err());
+        assert_eq!(manager.get_state().await.source, SourceMode::Bram);
+
+        manager.state.write().await.channels[1].enabled = false;
+        manager.set_source(SourceMode::Adc).await.unwrap();
+
+        // ...and such a channel can't be enabled in ADC mode
+        assert!(manager.set_channel(7, 1_140_000, true, None, false).await.is_err());
+        manager.set_channel(3, 740_000, true, None, false).await.unwrap();
+        manager.set_audio_input("LINE1").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_channel_capabilities_unsupported_firmware() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        manager.query_channel_capabilities().await;
+
+        assert!(manager.get_channel_capabilities().await.is_empty());
+        // Only CH1 was asked before giving up
+        assert_eq!(mock.received(), vec!["CH1:CAP?".to_string()]);
+
+        set_amplitudes(&manager, &[(2, 1.0)]).await;
+        manager.set_source(SourceMode::Adc).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_nearest_collision() {
+        let manager = manager_with_enabled(&[(1, 600_000), (2, 640_000), (3, 605_000)]).await;
+        manager.state.write().await.channels[2].enabled = false;
+
+        // Clear of both neighbours
+        assert_eq!(manager.nearest_collision(5, 620_000).await, None);
+
+        // Below and above the candidate - nearest wins
+        assert_eq!(manager.nearest_collision(5, 615_000).await, Some((1, 15_000)));
+        assert_eq!(manager.nearest_collision(5, 628_000).await, Some((2, 12_000)));
+
+        // Disabled channels and the channel itself don't count
+        assert_eq!(manager.nearest_collision(1, 604_000).await, None);
+
+        // Enabled neighbours on both sides within range at once - the closer one wins
+        let manager = manager_with_enabled(&[(1, 600_000), (4, 630_000)]).await;
+        assert_eq!(manager.nearest_collision(5, 612_000).await, Some((1, 12_000)));
+        assert_eq!(manager.nearest_collision(5, 618_000).await, Some((4, 12_000)));
+        assert_eq!(manager.nearest_collision(5, 621_000).await, Some((4, 9_000)));
+    }
+
+    #[tokio::test]
+    async fn test_rotate_plan() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        set_amplitudes(&manager, &[(1, 0.5), (4, 0.5), (12, 0.5)]).await;
+
+        let enabled = |state: &DeviceState| -> Vec<u8> {
+            state.channels.iter().filter(|c| c.enabled).map(|c| c.id).collect()
+        };
+        let before = manager.get_state().await;
+
+        manager.rotate_plan(1).await.unwrap();
+        let after = manager.get_state().await;
+        assert_eq!(enabled(&after), vec![1, 2, 5]);  // 12 wraps round to 1
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(mock.received(), ["CH4:OUTPUT OFF", "CH12:OUTPUT OFF", "CH2:OUTPUT ON", "CH5:OUTPUT ON"]);
+
+        // Frequencies stay put
+        let freqs = |state: &DeviceState| -> Vec<u32> { state.channels.iter().map(|c| c.frequency).collect() };
+        assert_eq!(freqs(&after), freqs(&before));
+
+        sleep(Duration::from_millis(50)).await;
+        let sent = mock.received().len();
+        manager.rotate_plan(12).await.unwrap();
+        assert_eq!(enabled(&manager.get_state().await), vec![1, 2, 5]);
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(mock.received().len(), sent);
+    }
+
+    #[tokio::test]
+    async fn test_latency_trend_records_commands_in_order() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "*IDN?" => Some("RedPitaya,STEMlab125-10,MOCK,v1.0".to_string()),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+
+        manager.send_command("WATCHDOG:RESET").await.unwrap();
+        manager.query("*IDN?").await.unwrap();
+        manager.send_command("FREQ:CH1 540000").await.unwrap();
+
+        let trend = manager.latency_trend(10).await;
+        let commands: Vec<&str> = trend.iter().map(|s| s.command.as_str()).collect();
+        assert_eq!(commands, vec!["WATCHDOG:RESET", "*IDN?", "FREQ:CH1 540000"]);
+        assert!(trend.iter().all(|s| s.latency_ms >= 0.0));
+        assert!(trend.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+
+        // Limit keeps the newest
+        let last: Vec<String> = manager.latency_trend(2).await.into_iter().map(|s| s.command).collect();
+        assert_eq!(last, vec!["*IDN?", "FREQ:CH1 540000"]);
+
+        // A pipelined batch is one sample
+        manager.query_pipelined(&["*IDN?".to_string(), "*IDN?".to_string(), "*IDN?".to_string()]).await.unwrap();
+        let trend = manager.latency_trend(10).await;
+        assert_eq!(trend.len(), 4);
+        assert_eq!(trend[3].command, "*IDN? (+2 pipelined)");
+    }
+
+    #[test]
+    fn test_license_window_contains() {
+        let at = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+
+        let day = LicenseWindow::parse("06:00", "22:00").unwrap();
+        assert!(day.contains(at(6, 0)));
+        assert!(day.contains(at(21, 59)));
+        assert!(!day.contains(at(22, 0)));
+        assert!(!day.contains(at(3, 0)));
+
+        let night = LicenseWindow::parse("22:00", "06:00").unwrap();
+        assert!(night.contains(at(23, 30)));
+        assert!(night.contains(at(2, 0)));
+        assert!(!night.contains(at(12, 0)));
+
+        assert!(LicenseWindow::parse("25:00", "06:00").is_err());
+        assert!(LicenseWindow::parse("06:00", "06:00").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_license_window_blocks_start() {
+        let mock = output_mock().await;
+        let manager = connected_manager(&mock).await;
+        manager.set_min_output_interval(0).await;
+        manager.state.write().await.channels[0].enabled = true;
+        manager.set_license_window(Some(LicenseWindow::parse("06:00", "22:00").unwrap())).await;
+
+        // Out of window
+        *manager.clock_override.write().await = NaiveTime::from_hms_opt(3, 0, 0);
+        let err = manager.start_broadcast().await.unwrap_err();
+        assert!(err.contains("license window"), "{}", err);
+        assert!(manager.start_emergency(false).await.is_err());
+        assert_eq!(manager.get_state().await.broadcast, BroadcastState::Idle);
+
+        // Emergency override still goes out
+        manager.start_emergency(true).await.unwrap();
+        manager.stop_emergency().await.unwrap();
+
+        // In window
+        *manager.clock_override.write().await = NaiveTime::from_hms_opt(12, 0, 0);
+        manager.start_broadcast().await.unwrap();
+        assert_eq!(manager.get_state().await.broadcast, BroadcastState::Broadcasting);
+    }
+
+    #[tokio::test]
+    async fn test_license_window_closing_stops_broadcast() {
+        let output = Arc::new(Mutex::new(false));
+        let mock = MockDevice::spawn(move |cmd| match cmd {
+            "OUTPUT:STATE ON" => { *output.lock() = true; None }
+            "OUTPUT:STATE OFF" => { *output.lock() = false; None }
+            "OUTPUT:STATE?" => Some(if *output.lock() { "1" } else { "0" }.to_string()),
+            "STATUS?" => Some(format!("BROADCAST:{},WATCHDOG:0", if *output.lock() { 1 } else { 0 })),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.channels[0].enabled = true;
+        manager.set_license_window(Some(LicenseWindow::parse("06:00", "22:00").unwrap())).await;
+        *manager.clock_override.write().await = NaiveTime::from_hms_opt(21, 59, 0);
+        manager.start_broadcast().await.unwrap();
+
+        manager.set_poll_interval(50).await.unwrap();
+        *manager.is_running.write().await = true;
+        manager.spawn_poll_task();
+
+        *manager.clock_override.write().await = NaiveTime::from_hms_opt(22, 0, 0);
+        wait_for_event(&manager, "BroadcastStopped").await;
+        *manager.is_running.write().await = false;
+
+        assert_eq!(manager.get_state().await.broadcast, BroadcastState::Idle);
+        assert_eq!(manager.last_stop_reason().await, Some(StopReason::LicenseWindow));
+        assert!(mock.received().contains(&"OUTPUT:STATE OFF".to_string()));
+        assert!(manager.get_audit_log_level("WARNING").await.iter().any(|e| e.message.contains("License window closed")));
+    }
+
+    #[tokio::test]
+    async fn test_status_line() {
+        let manager = manager_with_enabled(&[(1, 540_000), (2, 640_000)]).await;
+        assert_eq!(manager.status_line().await, "DISCONNECTED");
+
+        {
+            let mut state = manager.state.write().await;
+            state.connection = ConnectionState::Connected;
+        }
+        assert_eq!(manager.status_line().await, "CONNECTED • IDLE • WD:OK • 2ch");
+
+        {
+            let mut state = manager.state.write().await;
+            state.broadcast = BroadcastState::Broadcasting;
+            state.watchdog = WatchdogState::Warning;
+            state.fpga_temperature = Some(45.4);
+        }
+        assert_eq!(manager.status_line().await, "CONNECTED • BROADCASTING • WD:WARNING • 45°C • 2ch");
+
+        manager.state.write().await.connection = ConnectionState::Reconnecting;
+        assert_eq!(manager.status_line().await, "RECONNECTING...");
+    }
+
+    #[tokio::test]
+    async fn test_inject_latency() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "*IDN?" => Some("RedPitaya,STEMlab125-10,MOCK,v1.0".to_string()),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+
+        assert!(manager.inject_latency(200).await.is_err());
+
+        manager.set_test_mode(true).await;
+        manager.inject_latency(200).await.unwrap();
+        let started = Instant::now();
+        manager.query("*IDN?").await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(200));
+
+        // Leaving test mode removes it
+        manager.set_test_mode(false).await;
+        let started = Instant::now();
+        manager.query("*IDN?").await.unwrap();
+        assert!(started.elapsed() < Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn test_verify_plan_reports_only_mismatch() {
+        // Device matches the presets with CH1/CH2 on, except CH5 is 10 kHz off
+        let mock = MockDevice::spawn(|cmd| {
+            let query = cmd.strip_suffix('?')?;
+            if let Some(n) = query.strip_prefix("FREQ:CH") {
+                let n: u32 = n.parse().ok()?;
+                let offset = if n == 5 { 10_000 } else { 0 };
+                Some((540_000 + (n - 1) * 100_000 + offset).to_string())
+            } else {
+                let n: u8 = query.strip_prefix("OUTPUT:CH")?.parse().ok()?;
+                Some(if n <= 2 { "ON" } else { "OFF" }.to_string())
+            }
+        }).await;
+        let manager = connected_manager(&mock).await;
+        set_amplitudes(&manager, &[(1, 1.0), (2, 1.0)]).await;
+
+        let mismatches = manager.verify_plan().await.unwrap();
+        assert_eq!(mismatches.len(), 1, "{:?}", mismatches);
+        assert_eq!(mismatches[0].0, 5);
+        assert!(mismatches[0].1.contains("950000"), "{}", mismatches[0].1);
+        assert_eq!(mock.received().len(), 24);
+
+        // Link still usable afterwards
+        assert_eq!(manager.verify_plan().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_stops_broadcast_by_default() {
+        let mock = output_mock().await;
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.channels[0].enabled = true;
+        manager.start_broadcast().await.unwrap();
+
+        manager.disconnect(true).await.unwrap();
+        sleep(Duration::from_millis(50)).await;
+        assert!(mock.received().contains(&"OUTPUT:STATE OFF".to_string()));
+        assert_eq!(manager.get_state().await.connection, ConnectionState::Disconnected);
+        assert_eq!(manager.last_stop_reason().await, Some(StopReason::Disconnect));
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_can_leave_broadcast_running() {
+        let mock = output_mock().await;
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.channels[0].enabled = true;
+        manager.start_broadcast().await.unwrap();
+
+        manager.disconnect(false).await.unwrap();
+        sleep(Duration::from_millis(50)).await;
+        assert!(!mock.received().contains(&"OUTPUT:STATE OFF".to_string()));
+        assert_eq!(manager.get_state().await.connection, ConnectionState::Disconnected);
+        assert!(manager.get_audit_log_level("WARNING").await.iter().any(|e| e.message.contains("output left ON")));
+    }
+
+    #[tokio::test]
+    async fn test_query_active_clients() {
+        let clients = Arc::new(Mutex::new("1"));
+        let reported = clients.clone();
+        let mock = MockDevice::spawn(move |cmd| match cmd {
+            "SYSTEM:CLIENTS?" => Some(reported.lock().to_string()),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+
+        assert_eq!(manager.query_active_clients().await.unwrap(), 1);
+        assert_eq!(manager.get_state().await.active_clients, Some(1));
+        assert!(manager.get_audit_log_level("WARNING").await.is_empty());
+
+        *clients.lock() = "3";
+        assert_eq!(manager.query_active_clients().await.unwrap(), 3);
+        assert_eq!(manager.get_state().await.active_clients, Some(3));
+        assert!(manager.get_audit_log_level("WARNING").await[0].message.contains("3 control clients"));
+    }
+
+    #[tokio::test]
+    async fn test_acknowledge_errors() {
+        let manager = test_manager();
+        manager.log_error("first").await;
+        manager.log_error("second").await;
+        manager.watchdog_triggers.write().await.push_back(1);
+        assert_eq!(manager.get_state().await.error_count, 2);
+
+        manager.acknowledge_errors().await;
+
+        assert_eq!(manager.get_state().await.error_count, 0);
+        assert!(manager.watchdog_trigger_history().await.is_empty());
+        let log = manager.get_audit_log_level("INFO").await;
+        assert!(log.last().unwrap().message.contains("acknowledged 2 errors and 1 watchdog trips"));
+    }
+
+    #[tokio::test]
+    async fn test_full_state_serializes_everything() {
+        let manager = test_manager();
+        {
+            let mut state = manager.state.write().await;
+            state.fpga_temperature = Some(48.5);
+            state.channels[0].amplitude = 0.25;
+        }
+        manager.log_error("boom").await;
+
+        let json = serde_json::to_value(manager.get_state().await).unwrap();
+        assert_eq!(json["fpga_temperature"], 48.5);
+        assert_eq!(json["error_count"], 1);
+        assert_eq!(json["channels"][0]["amplitude"], 0.25);
+        assert_eq!(json["channels"][0]["phase"], 0.0);
+        // Internal resume snapshot stays out
+        assert!(json.get("resume_channels").is_none());
+    }
+
+    #[test]
+    fn test_format_endpoint() {
+        assert_eq!(format_endpoint("192.168.0.100", 5000), "192.168.0.100:5000");
+        assert_eq!(format_endpoint("::1", 5000), "[::1]:5000");
+        assert_eq!(format_endpoint("[fe80::1]", 5000), "[fe80::1]:5000");
+        assert_eq!(format_endpoint("redpitaya.local", 5000), "redpitaya.local:5000");
+    }
+
+    fn any_query_mock() -> impl Fn(&str) -> Option<String> + Send + Sync + 'static {
+        |cmd| match cmd {
+            "*IDN?" => Some("RedPitaya,STEMlab125-10,MOCK,v1.0".to_string()),
+            "STATUS?" => Some("BROADCAST:0,WATCHDOG:0".to_string()),
+            c if c.ends_with('?') => Some("540000".to_string()),
+            _ => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connect_via_hostname() {
+        let mock = MockDevice::spawn(any_query_mock()).await;
+        let manager = test_manager();
+
+        manager.connect("localhost", mock.addr.port()).await.unwrap();
+        assert_eq!(manager.connected_addr().await, Some(mock.addr));
+
+        manager.disconnect(true).await.unwrap();
+        assert_eq!(manager.connected_addr().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_connect_via_ipv6_loopback() {
+        let mock = MockDevice::spawn_on("[::1]:0", any_query_mock()).await;
+        let manager = test_manager();
+
+        manager.connect("::1", mock.addr.port()).await.unwrap();
+        assert_eq!(manager.connected_addr().await, Some(mock.addr));
+        manager.disconnect(true).await.unwrap();
+
+        // Bracketed form works too
+        manager.connect("[::1]", mock.addr.port()).await.unwrap();
+        assert!(manager.connected_addr().await.unwrap().is_ipv6());
+        manager.disconnect(true).await.unwrap();
+    }
+
+    async fn preflight_ready_manager() -> NetworkManager {
+        let manager = manager_with_enabled(&[(1, 540_000), (2, 640_000)]).await;
+        manager.state.write().await.connection = ConnectionState::Connected;
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_preflight_all_clear() {
+        let manager = preflight_ready_manager().await;
+        manager.state.write().await.fpga_temperature = Some(45.0);
+        assert!(manager.preflight().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_preflight_each_failure() {
+        let only = |problems: Vec<String>, needle: &str| {
+            assert_eq!(problems.len(), 1, "{:?}", problems);
+            assert!(problems[0].contains(needle), "{:?}", problems);
+        };
+
+        let manager = preflight_ready_manager().await;
+        manager.state.write().await.connection = ConnectionState::Disconnected;
+        only(manager.preflight().await, "Not connected");
+
+        let manager = preflight_ready_manager().await;
+        for channel in manager.state.write().await.channels.iter_mut() {
+            channel.enabled = false;
+        }
+        only(manager.preflight().await, "No channels enabled");
+
+        let manager = preflight_ready_manager().await;
+        manager.state.write().await.channels[1].frequency = 550_000;
+        only(manager.preflight().await, "CH1 and CH2");
+
+        let manager = preflight_ready_manager().await;
+        manager.state.write().await.fpga_temperature = Some(Config::TEMP_HIGH_C + 1.0);
+        only(manager.preflight().await, "over temperature");
+
+        let manager = preflight_ready_manager().await;
+        manager.set_max_total_amplitude(1.5).await.unwrap();
+        only(manager.preflight().await, "exceeds budget");
+
+        let manager = preflight_ready_manager().await;
+        manager.set_license_window(Some(LicenseWindow::parse("06:00", "22:00").unwrap())).await;
+        *manager.clock_override.write().await = NaiveTime::from_hms_opt(23, 0, 0);
+        only(manager.preflight().await, "license window");
+    }
+
+    #[test]
+    fn test_frequency_unit_conversion() {
+        assert_eq!(FrequencyUnit::Hz.hz_to_unit(540_000), 540_000.0);
+        assert_eq!(FrequencyUnit::Khz.hz_to_unit(540_000), 540.0);
+        assert_eq!(FrequencyUnit::Khz.hz_to_unit(540_500), 540.5);
+
+        assert_eq!(FrequencyUnit::Hz.unit_to_hz(540_000.0), Ok(540_000));
+        assert_eq!(FrequencyUnit::Khz.unit_to_hz(540.0), Ok(540_000));
+        assert_eq!(FrequencyUnit::Khz.unit_to_hz(540.5), Ok(540_500));
+        assert_eq!(FrequencyUnit::Khz.unit_to_hz(1234.5678), Ok(1_234_568));  // Nearest Hz
+
+        // Round trip
+        for hz in [500_000, 540_500, 1_700_000] {
+            assert_eq!(FrequencyUnit::Khz.unit_to_hz(FrequencyUnit::Khz.hz_to_unit(hz)), Ok(hz));
+        }
+
+        assert!(FrequencyUnit::Khz.unit_to_hz(-1.0).is_err());
+        assert!(FrequencyUnit::Hz.unit_to_hz(f64::NAN).is_err());
+        assert_eq!(serde_json::to_string(&FrequencyUnit::Khz).unwrap(), "\"khz\"");
+    }
+
+    #[tokio::test]
+    async fn test_tune_by_station() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        manager.set_stations(stations::from_json(r#"{"TUNNEL1": 810000, "PA2": 1200000}"#).unwrap()).await;
+
+        assert_eq!(manager.tune_by_station(3, " tunnel1 ").await.unwrap(), 810_000);
+        assert_eq!(manager.get_state().await.channels[2].frequency, 810_000);
+        assert!(!manager.get_state().await.channels[2].enabled);
+
+        let err = manager.tune_by_station(3, "NOPE").await.unwrap_err();
+        assert!(err.contains("Unknown station"), "{}", err);
+        assert_eq!(manager.get_state().await.channels[2].frequency, 810_000);
+
+        let listed: Vec<String> = manager.list_stations().await.into_iter().map(|(c, _)| c).collect();
+        assert_eq!(listed, vec!["TUNNEL1", "PA2"]);
+    }
+
+    #[tokio::test]
+    async fn test_connect_timing_recorded() {
+        // Slow device: every query answer takes a moment, so init dominates
+        let mock = MockDevice::spawn(|cmd| {
+            std::thread::sleep(Duration::from_millis(5));
+            any_query_mock()(cmd)
+        }).await;
+        let manager = test_manager();
+        assert!(manager.last_connect_timing().await.is_none());
+
+        manager.connect("127.0.0.1", mock.addr.port()).await.unwrap();
+        manager.disconnect(true).await.unwrap();
+
+        let timing = manager.last_connect_timing().await.unwrap();
+        assert!(timing.init_ms > 0);
+        let phases = timing.tcp_connect_ms + timing.init_ms + timing.poll_spawn_ms;
+        assert!(phases <= timing.total_ms && timing.total_ms - phases <= 20, "{:?}", timing);
+    }
+
+    #[tokio::test]
+    async fn test_reboot_detected_from_uptime_drop() {
+        // Three polls of a live device, then it comes back from a reboot with output off
+        let polls = Arc::new(Mutex::new(0u64));
+        let mock = MockDevice::spawn(move |cmd| match cmd {
+            "STATUS?" => {
+                let mut n = polls.lock();
+                *n += 1;
+                Some(if *n <= 3 {
+                    format!("BROADCAST:1,WATCHDOG:0,UPTIME:{}", 100 + *n)
+                } else {
+                    format!("BROADCAST:0,WATCHDOG:0,UPTIME:{}", *n - 3)
+                })
+            }
+            "*IDN?" => Some("RedPitaya,STEMlab125-10,MOCK,v1.0".to_string()),
+            c if c.ends_with('?') => Some("540000".to_string()),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.broadcast = BroadcastState::Broadcasting;
+        manager.set_poll_interval(50).await.unwrap();
+        *manager.is_running.write().await = true;
+        manager.spawn_poll_task();
+
+        wait_for_event(&manager, "DeviceRebooted").await;
+        sleep(Duration::from_millis(200)).await;
+        *manager.is_running.write().await = false;
+
+        let state = manager.get_state().await;
+        assert_eq!(state.broadcast, BroadcastState::Idle);
+        assert_eq!(state.last_stop_reason, Some(StopReason::Fault));
+        assert_eq!(state.serial.as_deref(), Some("MOCK"));  // initialize_device re-ran
+        assert!(mock.received().contains(&"*IDN?".to_string()));
+        assert_eq!(manager.recent_events_of_type("DeviceRebooted", 10).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_channel_bounds_reject_globally_valid_frequency() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        manager.set_channel_bounds(1, Some(ChannelBounds { min: 540_000, max: 700_000 })).await.unwrap();
+
+        // Inside the AM band but outside CH1's antenna range
+        let err = manager.set_channel(1, 900_000, true, None, false).await.unwrap_err();
+        assert!(err.contains("outside CH1 limits"), "{}", err);
+
+        // Other channels still get the full band; CH1 accepts its sub-range
+        manager.set_channel(2, 900_000, true, None, false).await.unwrap();
+        manager.set_channel(1, 650_000, true, None, false).await.unwrap();
+        sleep(Duration::from_millis(50)).await;
+        assert!(!mock.received().contains(&"CH1:FREQ 900000".to_string()));
+
+        // Clearing the override restores the global limits
+        manager.set_channel_bounds(1, None).await.unwrap();
+        manager.set_channel(1, 900_000, true, None, false).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_channel_bounds_validation() {
+        let manager = test_manager();
+        let bounds = |min, max| Some(ChannelBounds { min, max });
+        assert!(manager.set_channel_bounds(13, bounds(540_000, 700_000)).await.is_err());
+        assert!(manager.set_channel_bounds(1, bounds(700_000, 540_000)).await.is_err());
+        assert!(manager.set_channel_bounds(1, bounds(400_000, 700_000)).await.is_err());
+        assert!(manager.set_channel_bounds(1, bounds(540_000, 1_800_000)).await.is_err());
+        assert!(manager.effective_config().await.channel_bounds.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tone_sequence_restores_channel() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        {
+            let mut state = manager.state.write().await;
+            let ch3 = state.channels.iter_mut().find(|c| c.id == 3).unwrap();
+            ch3.frequency = 700_000;
+            ch3.enabled = false;
+            ch3.amplitude = 0.4;
+        }
+
+        manager.test_tone_sequence(3, vec![900_000, 1_000_000], 20).await.unwrap();
+        sleep(Duration::from_millis(50)).await;
+
+        let ch3 = manager.get_state().await.channels.into_iter().find(|c| c.id == 3).unwrap();
+        assert_eq!((ch3.frequency, ch3.enabled, ch3.amplitude), (700_000, false, 0.4));
+        let sent = mock.received();
+        assert!(sent.contains(&"CH3:FREQ 900000".to_string()));
+        assert!(sent.contains(&"CH3:FREQ 1000000".to_string()));
+        assert_eq!(sent[sent.len() - 3..], ["CH3:FREQ 700000", "AMP:CH3 0.4", "CH3:OUTPUT OFF"]);
+    }
+
+    #[tokio::test]
+    async fn test_tone_sequence_abort_restores_channel() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+
+        let running = manager.clone();
+        let sequence = tokio::spawn(async move {
+            running.test_tone_sequence(2, vec![900_000, 1_000_000], 5_000).await
+        });
+        sleep(Duration::from_millis(100)).await;
+        manager.abort_test_tone().await;
+
+        let err = tokio::time::timeout(Duration::from_secs(1), sequence).await.unwrap().unwrap().unwrap_err();
+        assert!(err.contains("aborted"));
+        let ch2 = manager.get_state().await.channels.into_iter().find(|c| c.id == 2).unwrap();
+        assert_eq!((ch2.frequency, ch2.enabled), (640_000, false));
+        assert!(!mock.received().contains(&"CH2:FREQ 1000000".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_tone_sequence_failed_step_reports_step_error() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.channels[2].frequency = 700_000;
+        // Second step is outside the limits, and so is the original frequency to restore
+        manager.set_channel_bounds(3, Some(ChannelBounds { min: 800_000, max: 950_000 })).await.unwrap();
+
+        let err = manager.test_tone_sequence(3, vec![900_000, 1_000_000], 20).await.unwrap_err();
+        assert!(err.contains("1000000 outside CH3 limits"), "{}", err);
+        let errors = manager.get_audit_log_level("ERROR").await;
+        assert!(errors.iter().any(|e| e.message.contains("could not restore") && e.message.contains("700000")));
+        assert!(manager.get_audit_log_level("WARNING").await.iter().any(|e| e.message.contains("stopped")));
+    }
+
+    #[test]
+    fn test_status_age() {
+        let mut state = DeviceState::default();
+        assert_eq!(state.status_age_secs(1_700_000_000), None);
+
+        state.last_status_time = Some(1_700_000_000);
+        assert_eq!(state.status_age_secs(1_700_000_000), Some(0));
+        assert_eq!(state.status_age_secs(1_700_000_042), Some(42));
+        // Clock stepped backwards - never negative
+        assert_eq!(state.status_age_secs(1_699_999_990), Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_status_age_after_poll() {
+        let manager = test_manager();
+        assert_eq!(manager.status_age_secs().await, None);
+
+        NetworkManager::parse_status_static(
+            "BROADCAST:0", &manager.state, &manager.event_bus, &manager.audit,
+            &manager.watchdog_triggers, &manager.temp_thresholds, &manager.reconcile_guard,
+        ).await;
+        assert!(manager.status_age_secs().await.unwrap() <= 1);
+    }
+
+    #[tokio::test]
+    async fn test_connect_fails_over_to_secondary() {
+        // Primary: a port nobody listens on any more
+        let down = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        let secondary = MockDevice::spawn(any_query_mock()).await;
+        let manager = test_manager();
+        manager.set_retry_config(RetryConfig { max_attempts: 1, ..RetryConfig::default() }).await.unwrap();
+
+        let endpoints = [Endpoint::new("127.0.0.1", down), Endpoint::new("127.0.0.1", secondary.addr.port())];
+        let active = manager.connect_with_failover(&endpoints).await.unwrap();
+        *manager.is_running.write().await = false;
+
+        assert_eq!(active, endpoints[1]);
+        assert_eq!(manager.active_endpoint().await, Some(endpoints[1].clone()));
+        assert_eq!(manager.connected_addr().await, Some(secondary.addr));
+        assert!(secondary.received().contains(&"*IDN?".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_cycles_to_backup_endpoint() {
+        let manager = test_manager();
+        let down = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        let backup = MockDevice::spawn(any_query_mock()).await;
+        let backup_port = backup.addr.port();
+        *manager.endpoints.write().await = vec![Endpoint::new("127.0.0.1", down), Endpoint::new("127.0.0.1", backup_port)];
+        *manager.current_ip.write().await = Some("127.0.0.1".to_string());
+        *manager.current_port.write().await = Some(down);
+
+        manager.handle_connection_lost().await;
+
+        assert_eq!(manager.active_endpoint().await, Some(Endpoint::new("127.0.0.1", backup_port)));
+        assert_eq!(manager.state.read().await.connection, ConnectionState::Connected);
+        assert_eq!(manager.reliability_stats().await.reconnect_attempts_total, 1);
+
+        // Commands now go over the new link
+        manager.send_command("CH1:OUTPUT ON").await.unwrap();
+        sleep(Duration::from_millis(50)).await;
+        let sent = backup.received();
+        assert!(sent.contains(&"*IDN?".to_string()));
+        assert!(sent.contains(&"CH1:OUTPUT ON".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_plan_code_round_trip_between_managers() {
+        let source = manager_with_enabled(&[(2, 640_000), (9, 1_010_000)]).await;
+        let code = source.export_plan_code().await;
+
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.channels[0].enabled = true;  // CH1 not in the code
+
+        assert_eq!(manager.import_plan_code(&code).await.unwrap(), vec![(2, 640_000), (9, 1_010_000)]);
+        assert_eq!(manager.export_plan_code().await, code);
+        let enabled: Vec<u8> = manager.enabled_channel_ids().await;
+        assert_eq!(enabled, vec![2, 9]);
+
+        // Corrupted code: rejected before anything is sent
+        sleep(Duration::from_millis(50)).await;
+        let sent = mock.received().len();
+        let corrupted = code.replace("AM1", "AM2");
+        assert!(manager.import_plan_code(&corrupted).await.is_err());
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(mock.received().len(), sent);
+    }
+
+    #[tokio::test]
+    async fn test_import_plan_code_rejection_keeps_previous_plan() {
+        let source = manager_with_enabled(&[(2, 640_000), (9, 1_010_000)]).await;
+        let code = source.export_plan_code().await;
+
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.channels[0].enabled = true;
+        // Second coded channel is outside its limits - the first must not go out either
+        manager.set_channel_bounds(9, Some(ChannelBounds { min: 530_000, max: 900_000 })).await.unwrap();
+        let before = manager.export_plan_code().await;
+
+        let err = manager.import_plan_code(&code).await.unwrap_err();
+        assert!(err.contains("CH9 limits"), "{}", err);
+        assert_eq!(manager.export_plan_code().await, before);
+        assert_eq!(manager.enabled_channel_ids().await, vec![1]);
+        sleep(Duration::from_millis(50)).await;
+        assert!(mock.received().is_empty());
+    }
+
+    fn steps(json: &str) -> Vec<SequenceStep> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_run_sequence_passes() {
+        let mock = output_mock().await;
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.channels[0].enabled = true;
+        manager.set_min_output_interval(0).await;
+
+        let results = manager.run_sequence(steps(r#"[
+            {"action": "arm"},
+            {"action": "start"},
+            {"action": "wait", "ms": 20},
+            {"action": "stop"}
+        ]"#)).await;
+
+        assert_eq!(results.len(), 4);
+        assert!(results.iter().all(|r| r.status == StepStatus::Passed), "{:?}", results);
+        assert!(results[2].elapsed_ms >= 20);
+        assert_eq!(manager.get_state().await.broadcast, BroadcastState::Idle);
+    }
+
+    #[tokio::test]
+    async fn test_run_sequence_start_without_arm_aborts() {
+        let mock = output_mock().await;
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.channels[0].enabled = true;
+
+        let results = manager.run_sequence(steps(r#"[
+            {"action": "start"},
+            {"action": "wait", "ms": 10},
+            {"action": "stop"}
+        ]"#)).await;
+
+        assert_eq!(results[0].status, StepStatus::Failed);
+        assert!(results[0].message.contains("Must arm"), "{}", results[0].message);
+        assert_eq!(results[1].status, StepStatus::Skipped);
+        assert_eq!(results[2].status, StepStatus::Skipped);
+
+        // Nothing reached the device
+        sleep(Duration::from_millis(50)).await;
+        assert!(!mock.received().contains(&"OUTPUT:STATE ON".to_string()));
+        assert_eq!(manager.get_state().await.broadcast, BroadcastState::Idle);
+    }
+
+    #[tokio::test]
+    async fn test_run_sequence_optional_failure_continues() {
+        let mock = output_mock().await;
+        let manager = connected_manager(&mock).await;
+
+        let results = manager.run_sequence(steps(r#"[
+            {"action": "set_channel", "ch": 13, "frequency": 540000, "enabled": true, "optional": true},
+            {"action": "wait", "ms": 1}
+        ]"#)).await;
+
+        assert_eq!(results[0].status, StepStatus::Failed);
+        assert_eq!(results[1].status, StepStatus::Passed);
+    }
+
+    #[test]
+    fn test_celsius_to_fahrenheit() {
+        assert_eq!(celsius_to_fahrenheit(0.0), 32.0);
+        assert_eq!(celsius_to_fahrenheit(100.0), 212.0);
+        assert_eq!(celsius_to_fahrenheit(-40.0), -40.0);
+        assert_eq!(celsius_to_fahrenheit(45.2), 113.4);   // 113.36
+        assert_eq!(celsius_to_fahrenheit(70.0), 158.0);
+        assert_eq!(celsius_to_fahrenheit(36.65), 98.0);   // 97.97
+    }
+
+    #[tokio::test]
+    async fn test_temperature_unit_keeps_celsius_storage() {
+        let manager = test_manager();
+        assert!(manager.temperature().await.is_none());
+
+        manager.parse_status_response("TEMP:45.2").await;
+        manager.set_temperature_unit(TemperatureUnit::Fahrenheit).await;
+
+        let reading = manager.temperature().await.unwrap();
+        assert_eq!((reading.temp_c, reading.temp_f, reading.unit), (45.2, 113.4, TemperatureUnit::Fahrenheit));
+        assert_eq!(manager.get_state().await.fpga_temperature, Some(45.2));
+    }
+
+    #[tokio::test]
+    async fn test_clock_skew_from_device_time() {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+        let device_time = now - 120;  // Device clock two minutes behind
+        let mock = MockDevice::spawn(move |cmd| match cmd {
+            "SYSTEM:TIME?" => Some(format!("{}.4", device_time)),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+
+        let skew = manager.query_clock_skew().await.unwrap();
+        assert!((-121..=-119).contains(&skew), "{}", skew);
+        assert_eq!(manager.get_state().await.clock_skew_secs, Some(skew));
+        assert!(manager.get_audit_log().await.iter().any(|e| e.level == "WARNING" && e.message.contains("behind host")));
+    }
+
+    #[tokio::test]
+    async fn test_clock_skew_invalid_response() {
+        let mock = MockDevice::spawn(|cmd| (cmd == "SYSTEM:TIME?").then(|| "soon".to_string())).await;
+        let manager = connected_manager(&mock).await;
+
+        assert!(manager.query_clock_skew().await.unwrap_err().contains("Invalid device time"));
+        assert_eq!(manager.get_state().await.clock_skew_secs, None);
+    }
+
+    // Frequencies sent to `ch` via CHn:FREQ, in order
+    fn sent_frequencies(mock: &MockDevice, ch: u8) -> Vec<u32> {
+        let prefix = format!("CH{}:FREQ ", ch);
+        mock.received().iter().filter_map(|c| c.strip_prefix(&prefix)?.parse().ok()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_ramp_frequency_steps_monotonically() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+
+        // CH1 starts at 540 kHz
+        assert_eq!(manager.ramp_frequency(1, 585_000, 10_000, 1).await.unwrap(), 585_000);
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(sent_frequencies(&mock, 1), vec![550_000, 560_000, 570_000, 580_000, 585_000]);
+
+        // And back down
+        manager.ramp_frequency(1, 560_000, 10_000, 1).await.unwrap();
+        sleep(Duration::from_millis(50)).await;
+        let down = sent_frequencies(&mock, 1)[5..].to_vec();
+        assert!(down.windows(2).all(|w| w[1] < w[0]), "{:?}", down);
+        assert_eq!(down.last(), Some(&560_000));
+        assert_eq!(manager.get_state().await.channels[0].frequency, 560_000);
+    }
+
+    #[tokio::test]
+    async fn test_ramp_frequency_clamps_and_aborts() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        manager.set_channel_bounds(12, Some(ChannelBounds { min: 1_500_000, max: 1_660_000 })).await.unwrap();
+
+        // CH12 starts at 1640 kHz; 1.8 MHz is clamped to its 1660 kHz limit
+        assert_eq!(manager.ramp_frequency(12, 1_800_000, 15_000, 1).await.unwrap(), 1_660_000);
+        assert!(manager.ramp_frequency(12, 1_600_000, 0, 1).await.is_err());
+
+        let running = manager.clone();
+        let ramp = tokio::spawn(async move { running.ramp_frequency(12, 1_500_000, 10_000, 5_000).await });
+        sleep(Duration::from_millis(100)).await;
+        manager.abort_ramp().await;
+        let err = timeout(Duration::from_secs(1), ramp).await.unwrap().unwrap().unwrap_err();
+        assert!(err.contains("aborted at 1650000"), "{}", err);
+        assert_eq!(manager.get_state().await.channels[11].frequency, 1_650_000);
+    }
+
+    #[tokio::test]
+    async fn test_ramp_abort_survives_a_new_ramp_starting() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+
+        let running = manager.clone();
+        let first = tokio::spawn(async move { running.ramp_frequency(12, 1_500_000, 10_000, 5_000).await });
+        sleep(Duration::from_millis(100)).await;
+
+        // Abort meant for the first ramp, then a second starts before the first sees it
+        manager.abort_ramp().await;
+        let running = manager.clone();
+        let second = tokio::spawn(async move { running.ramp_frequency(1, 600_000, 10_000, 5_000).await });
+
+        let err = timeout(Duration::from_secs(1), first).await.unwrap().unwrap().unwrap_err();
+        assert!(err.contains("aborted"), "{}", err);
+        sleep(Duration::from_millis(100)).await;
+        assert!(!second.is_finished());
+
+        manager.abort_ramp().await;
+        assert!(timeout(Duration::from_secs(1), second).await.unwrap().unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_has_active_channels() {
+        let none = test_manager();
+        assert!(!none.has_active_channels().await);
+        assert_eq!(none.active_channel_count().await, 0);
+
+        let two = manager_with_enabled(&[(3, 740_000), (8, 1_240_000)]).await;
+        assert!(two.has_active_channels().await);
+        assert_eq!(two.active_channel_count().await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_audit_persistence_survives_restart() {
+        let path = std::env::temp_dir().join(format!("amradio_audit_restart_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(audit_file::rotated_path(&path));
+
+        let first = test_manager();
+        first.log_info("before persistence").await;
+        first.set_audit_persistence(Some(path.clone()), 1_000_000, false).await.unwrap();
+        first.log_warning("carrier left on").await;
+        first.parse_status_response("TEMP:80.0").await;  // Poll-path entries are persisted too
+
+        // "Restart": a fresh manager reloads the tail of the file
+        let second = test_manager();
+        second.log_info("new session").await;
+        assert_eq!(second.set_audit_persistence(Some(path.clone()), 1_000_000, true).await.unwrap(), 3);
+
+        let messages: Vec<String> = second.get_audit_log().await.into_iter().map(|e| e.message).collect();
+        assert!(messages[0].starts_with("Audit log persisted to"));
+        assert_eq!(messages[1], "carrier left on");
+        assert!(messages[2].contains("over temperature"));
+        assert_eq!(messages[3], "new session");
+        assert!(!messages.iter().any(|m| m == "before persistence"));
+
+        second.set_audit_persistence(None, 0, false).await.unwrap();
+        let _ = std::fs::remove_file(&path);
+    }
+
+    fn plan(channels: &[(u8, u32, bool)]) -> Vec<Channel> {
+        channels.iter().map(|&(id, frequency, enabled)| Channel { id, enabled, frequency, amplitude: 1.0, phase: 0.0, pilot: false }).collect()
+    }
+
+    #[tokio::test]
+    async fn test_swap_plan_only_touches_differences() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+
+        // Plan A: CH1, CH2, CH3 on at their presets
+        manager.swap_plan(plan(&[(1, 540_000, true), (2, 640_000, true), (3, 740_000, true)])).await.unwrap();
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(mock.received(), vec!["CH1:OUTPUT ON", "CH2:OUTPUT ON", "CH3:OUTPUT ON"]);
+
+        // Plan B shares CH1, retunes CH2, drops CH3, adds CH4
+        let before = mock.received().len();
+        let changed = manager.swap_plan(plan(&[(1, 540_000, true), (2, 700_000, true), (4, 840_000, true)])).await.unwrap();
+        sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(changed, vec![2, 3, 4]);
+        assert_eq!(mock.received()[before..], ["CH3:OUTPUT OFF", "CH2:FREQ 700000", "CH4:OUTPUT ON"]);
+        assert!(!mock.received()[before..].iter().any(|c| c.starts_with("CH1:")));
+
+        let state = manager.get_state().await;
+        let enabled: Vec<(u8, u32)> = state.channels.iter().filter(|c| c.enabled).map(|c| (c.id, c.frequency)).collect();
+        assert_eq!(enabled, vec![(1, 540_000), (2, 700_000), (4, 840_000)]);
+
+        // Same plan again: nothing to send
+        let before = mock.received().len();
+        assert!(manager.swap_plan(plan(&[(1, 540_000, true), (2, 700_000, true), (4, 840_000, true)])).await.unwrap().is_empty());
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(mock.received().len(), before);
+    }
 
-            println!("[RECONNECT] Attempt {}/{} to {}:{}",
-                attempt, Config::MAX_RECONNECT_ATTEMPTS, ip, port);
+    #[tokio::test]
+    async fn test_swap_plan_validates_before_sending() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
 
-            // Wait before retry
-            sleep(Duration::from_secs(Config::RECONNECT_DELAY_SECS)).await;
+        assert!(manager.swap_plan(plan(&[(1, 540_000, true), (1, 640_000, true)])).await.unwrap_err().contains("Duplicate"));
+        assert!(manager.swap_plan(plan(&[(1, 540_000, true), (2, 2_000_000, true)])).await.unwrap_err().contains("out of range"));
+        sleep(Duration::from_millis(50)).await;
+        assert!(mock.received().is_empty());
+    }
 
-            // Try to connect
-            let addr = format!("{}:{}", ip, port);
-            match timeout(
-                Duration::from_secs(Config::CONNECTION_TIMEOUT_SECS),
-                TcpStream::connect(&addr)
-            ).await {
-                Ok(Ok(_stream)) => {
-                    // Success!
-                    println!("[RECONNECT] Success!");
+    #[tokio::test]
+    async fn test_stop_reason_recorded_for_each_stop_path() {
+        let mock = output_mock().await;
+        let manager = connected_manager(&mock).await;
+        manager.set_min_output_interval(0).await;
+        manager.state.write().await.channels[0].enabled = true;
+        assert_eq!(manager.last_stop_reason().await, None);
+
+        manager.start_broadcast().await.unwrap();
+        manager.stop_broadcast().await.unwrap();
+        assert_eq!(manager.last_stop_reason().await, Some(StopReason::Manual));
+
+        manager.start_emergency(false).await.unwrap();
+        manager.stop_emergency().await.unwrap();
+        assert_eq!(manager.last_stop_reason().await, Some(StopReason::Emergency));
+
+        // Device cuts output while hot - TEMP arriving after BROADCAST still counts
+        manager.state.write().await.broadcast = BroadcastState::Broadcasting;
+        manager.parse_status_response("BROADCAST:0,WATCHDOG:0,TEMP:90.0").await;
+        assert_eq!(manager.last_stop_reason().await, Some(StopReason::OverTemp));
+        manager.parse_status_response("BROADCAST:0,WATCHDOG:0,TEMP:40.0").await;
+
+        // Output dropped with nothing wrong: someone else stopped it
+        manager.state.write().await.broadcast = BroadcastState::Broadcasting;
+        manager.parse_status_response("BROADCAST:0,WATCHDOG:0").await;
+        assert_eq!(manager.last_stop_reason().await, Some(StopReason::Manual));
+
+        // Watchdog takes precedence over the plain output-off report
+        manager.state.write().await.broadcast = BroadcastState::Broadcasting;
+        manager.parse_status_response("BROADCAST:0,WATCHDOG:TRIGGERED").await;
+        assert_eq!(manager.last_stop_reason().await, Some(StopReason::Watchdog));
+        manager.parse_status_response("BROADCAST:0,WATCHDOG:0").await;
+
+        // Link lost mid-broadcast (no endpoint to reconnect to)
+        manager.state.write().await.broadcast = BroadcastState::Broadcasting;
+        manager.handle_connection_lost().await;
+        assert_eq!(manager.last_stop_reason().await, Some(StopReason::Disconnect));
+    }
 
-                    // Note: In real code, we'd need to update the stream reference
-                    // This is simplified - real impl would need Arc<RwLock<Option<TcpStream>>>
+    #[tokio::test]
+    async fn test_poll_loop_sends_configured_watchdog_command() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "STATUS?" => Some("BROADCAST:0,WATCHDOG:0".to_string()),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+        manager.set_watchdog_command("WDT:KICK").await.unwrap();
+        manager.set_poll_interval(50).await.unwrap();
+        *manager.is_running.write().await = true;
+        manager.spawn_poll_task();
+
+        sleep(Duration::from_millis(200)).await;
+        assert!(mock.received().contains(&"WDT:KICK".to_string()));
+        assert!(!mock.received().contains(&"WATCHDOG:RESET".to_string()));
+
+        // Rejected strings leave the running loop on the last good one
+        assert!(manager.set_watchdog_command("").await.is_err());
+        assert!(manager.set_watchdog_command("  ").await.is_err());
+        assert!(manager.set_watchdog_command("WDT:KICK\nOUTPUT:STATE OFF").await.is_err());
+        assert_eq!(manager.effective_config().await.watchdog_command, "WDT:KICK");
+
+        // A change applies from the next iteration
+        manager.set_watchdog_command("WATCHDOG:RESET").await.unwrap();
+        sleep(Duration::from_millis(200)).await;
+        *manager.is_running.write().await = false;
+        assert!(mock.received().contains(&"WATCHDOG:RESET".to_string()));
+        assert!(!mock.received().iter().any(|c| c.contains("OUTPUT:STATE OFF")));
+    }
 
-                    let mut s = state.write().await;
-                    s.connection = ConnectionState::Connected;
-                    s.error_count = 0;
+    #[tokio::test]
+    async fn test_safe_vswr_is_recorded() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "MEAS:VSWR? 1" => Some("1.35".to_string()),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.broadcast = BroadcastState::Broadcasting;
+
+        assert_eq!(manager.read_vswr(1).await.unwrap(), 1.35);
+        let state = manager.get_state().await;
+        assert_eq!(state.vswr.get(&1), Some(&1.35));
+        assert_eq!(state.broadcast, BroadcastState::Broadcasting);
+        assert!(!mock.received().contains(&"OUTPUT:STATE OFF".to_string()));
+        assert!(manager.read_vswr(13).await.is_err());
+    }
 
-                    *reconnect_attempts.write().await = 0;
+    #[tokio::test]
+    async fn test_unsafe_vswr_stops_broadcast() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "MEAS:VSWR? 2" => Some("4.8".to_string()),
+            "MEAS:VSWR? 3" => Some("ERR".to_string()),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.broadcast = BroadcastState::Broadcasting;
+
+        assert_eq!(manager.read_vswr(2).await.unwrap(), 4.8);
+        sleep(Duration::from_millis(50)).await;
+        let state = manager.get_state().await;
+        assert_eq!(state.broadcast, BroadcastState::Idle);
+        assert_eq!(state.last_stop_reason, Some(StopReason::Vswr));
+        assert!(mock.received().contains(&"OUTPUT:STATE OFF".to_string()));
+        assert!(manager.get_audit_log_level("WARNING").await.iter().any(|e| e.message.contains("CH2 VSWR 4.80")));
+
+        // Raised limit: same reading no longer trips
+        manager.set_max_vswr(5.0).await.unwrap();
+        assert!(manager.set_max_vswr(0.5).await.is_err());
+        manager.state.write().await.broadcast = BroadcastState::Broadcasting;
+        manager.read_vswr(2).await.unwrap();
+        assert_eq!(manager.get_state().await.broadcast, BroadcastState::Broadcasting);
+
+        // Firmware without VSWR support
+        assert!(manager.read_vswr(3).await.unwrap_err().contains("not supported"));
+    }
 
-                    let _ = event_tx.send(EventType::ReconnectSuccess);
-                    let _ = event_tx.send(EventType::ConnectionStateChanged(ConnectionState::Connected));
+    #[tokio::test]
+    async fn test_hidden_ui_polls_status_slowly_but_feeds_watchdog() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "STATUS?" => Some("BROADCAST:0,WATCHDOG:0".to_string()),
+            _ => None,
+        }).await;
+        let count = |cmd: &str| mock.received().iter().filter(|c| *c == cmd).count();
+        let manager = connected_manager(&mock).await;
+        manager.set_poll_interval(50).await.unwrap();
+        *manager.is_running.write().await = true;
+        manager.spawn_poll_task();
+        sleep(Duration::from_millis(200)).await;
+        assert!(count("STATUS?") >= 2);
+
+        manager.set_ui_visible(false).await;
+        sleep(Duration::from_millis(60)).await;
+        let (status, resets) = (count("STATUS?"), count("WATCHDOG:RESET"));
+        sleep(Duration::from_millis(300)).await;
+        assert!(count("STATUS?") <= status + 1);
+        assert!(count("WATCHDOG:RESET") >= resets + 3);
+
+        manager.set_ui_visible(true).await;
+        let status = count("STATUS?");
+        sleep(Duration::from_millis(200)).await;
+        *manager.is_running.write().await = false;
+        assert!(count("STATUS?") >= status + 2);
+    }
 
-                    return;
-                }
-                _ => {
-                    println!("[RECONNECT] Attempt {} failed", attempt);
-                }
-            }
-        }
+    #[tokio::test]
+    async fn test_set_valid_output_load() {
+        let load = Arc::new(Mutex::new("1000000".to_string()));
+        let device = load.clone();
+        let mock = MockDevice::spawn(move |cmd| match cmd {
+            "OUTPUT:LOAD?" => Some(device.lock().clone()),
+            c if c.starts_with("OUTPUT:LOAD ") => { *device.lock() = c[12..].to_string(); None }
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+
+        assert_eq!(manager.query_output_load().await.unwrap(), 1_000_000);
+        manager.set_output_load(50).await.unwrap();
+        sleep(Duration::from_millis(50)).await;
+        assert!(mock.received().contains(&"OUTPUT:LOAD 50".to_string()));
+        assert_eq!(manager.get_state().await.output_load_ohms, Some(50));
+        assert_eq!(manager.query_output_load().await.unwrap(), 50);
+    }
 
-        // All attempts failed
-        println!("[RECONNECT] All attempts failed, giving up");
+    #[tokio::test]
+    async fn test_unsupported_output_load_rejected() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "OUTPUT:LOAD?" => Some("ERR".to_string()),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+
+        assert!(manager.set_output_load(75).await.unwrap_err().contains("Unsupported"));
+        manager.state.write().await.broadcast = BroadcastState::Broadcasting;
+        assert!(manager.set_output_load(50).await.unwrap_err().contains("broadcasting"));
+        assert!(manager.query_output_load().await.unwrap_err().contains("not supported"));
+
+        sleep(Duration::from_millis(50)).await;
+        assert!(!mock.received().iter().any(|c| c.starts_with("OUTPUT:LOAD ")));
+        assert_eq!(manager.get_state().await.output_load_ohms, None);
+    }
 
-        *is_running.write().await = false;
-        state.write().await.connection = ConnectionState::Disconnected;
+    #[tokio::test]
+    async fn test_watchdog_margin_captures_delayed_reset() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "STATUS?" => Some("BROADCAST:0,WATCHDOG:0".to_string()),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+        *manager.watchdog_timeout.write().await = Duration::from_millis(500);
+        manager.set_poll_interval(50).await.unwrap();
+        *manager.is_running.write().await = true;
+        manager.spawn_poll_task();
+
+        sleep(Duration::from_millis(200)).await;
+        let margin = manager.watchdog_margin().await;
+        assert!(margin.worst_interval_ms < 250, "{:?}", margin);
+        assert!(!margin.near_miss);
+
+        // Something hogs the link: the next reset goes out ~450 ms late
+        {
+            let _stream = manager.stream.write().await;
+            sleep(Duration::from_millis(450)).await;
+        }
+        sleep(Duration::from_millis(150)).await;
+        *manager.is_running.write().await = false;
+
+        let margin = manager.watchdog_margin().await;
+        assert!(margin.worst_interval_ms >= 400, "{:?}", margin);
+        assert_eq!(margin.timeout_ms, 500);
+        assert_eq!(margin.margin_ms, 500 - margin.worst_interval_ms as i64);
+        assert!(margin.near_miss);
+        assert!(manager.get_audit_log_level("WARNING").await.iter().any(|e| e.message.contains("Watchdog fed")));
+    }
 
-        let _ = event_tx.send(EventType::ReconnectFailed);
-        let _ = event_tx.send(EventType::ConnectionStateChanged(ConnectionState::Disconnected));
+    #[tokio::test]
+    async fn test_long_responses_survive_reused_read_buffer() {
+        let big = "X".repeat(3000);
+        let reply = big.clone();
+        // Status longer than one 1 KiB read, with the field we care about at the very end
+        let status = format!("BROADCAST:0,WATCHDOG:0,{}TEMP:47.5", "PAD:0,".repeat(300));
+        let mock = MockDevice::spawn(move |cmd| match cmd {
+            "BIG?" => Some(reply.clone()),
+            "SMALL?" => Some("small".to_string()),
+            "STATUS?" => Some(status.clone()),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+
+        for _ in 0..20 {
+            assert_eq!(manager.query("BIG?").await.unwrap().trim(), big);
+            assert_eq!(manager.query("SMALL?").await.unwrap().trim(), "small");
+        }
+        let responses = manager.query_pipelined(&["BIG?".to_string(), "SMALL?".to_string(), "BIG?".to_string()]).await.unwrap();
+        assert_eq!(responses, [big.as_str(), "small", big.as_str()]);
+
+        manager.set_poll_interval(50).await.unwrap();
+        *manager.is_running.write().await = true;
+        manager.spawn_poll_task();
+        sleep(Duration::from_millis(200)).await;
+        assert_eq!(manager.get_state().await.fpga_temperature, Some(47.5));
+
+        // Queries interleaved with the poll loop still line up
+        for _ in 0..5 {
+            assert_eq!(manager.query("SMALL?").await.unwrap().trim(), "small");
+        }
+        *manager.is_running.write().await = false;
     }
 
-    // PARSE STATUS RESPONSE
-    async fn parse_status_response(&self, response: &str) {
-        Self::parse_status_static(response, &self.state, &self.event_tx).await;
+    // The real mock server (mock_server.rs) on an ephemeral port, as the given model
+    fn spawn_simulator(model: crate::mock_server::MockModel) -> u16 {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || crate::mock_server::serve(listener, model));
+        port
     }
 
-    async fn parse_status_static(
-        response: &str,
-        state: &Arc<RwLock<DeviceState>>,
-        event_tx: &broadcast::Sender<EventType>,
-    ) {
-        // Example response: "BROADCAST:1,WATCHDOG:0,TEMP:45.2,CH1:ON,CH2:OFF,..."
-        let mut s = state.write().await;
+    #[tokio::test]
+    async fn test_client_against_simulated_stemlab_125_10() {
+        let port = spawn_simulator(crate::mock_server::MockModel::Stemlab125_10);
+        let manager = test_manager();
+        manager.connect("127.0.0.1", port).await.unwrap();
+
+        let state = manager.get_state().await;
+        assert_eq!(state.serial.as_deref(), Some("MOCK"));
+        assert_eq!(state.fpga_temperature, Some(41.5));  // Comma-separated status parsed
+        assert_eq!(state.channel_capabilities.len(), 12);
+        assert_eq!(manager.check_protocol_version().await.status, ProtocolStatus::Match);
+        assert_eq!(manager.read_vswr(1).await.unwrap(), 1.2);
+        assert_eq!(manager.query_output_load().await.unwrap(), 50);
+
+        manager.disconnect(true).await.unwrap();
+    }
 
-        for part in response.split(',') {
-            let kv: Vec<&str> = part.split(':').collect();
-            if kv.len() != 2 {
-                continue;
-            }
+    #[tokio::test]
+    async fn test_client_against_simulated_stemlab_125_14() {
+        let port = spawn_simulator(crate::mock_server::MockModel::Stemlab125_14);
+        let manager = test_manager();
+        manager.connect("127.0.0.1", port).await.unwrap();
 
-            let key = kv[0].trim();
-            let value = kv[1].trim();
+        let state = manager.get_state().await;
+        assert_eq!(state.serial.as_deref(), Some("MOCK14"));
+        assert_eq!(state.fpga_temperature, Some(41.5));  // Semicolon-separated status parsed
+        assert!(state.channel_capabilities.is_empty());  // No CH{n}:CAP? - nothing restricted
 
-            match key {
-                "BROADCAST" | "OUTPUT" => {
-                    let was_broadcasting = s.broadcast == BroadcastState::Broadcasting;
-                    s.broadcast = if value == "1" || value == "ON" {
-                        BroadcastState::Broadcasting
-                    } else {
-                        BroadcastState::Idle
-                    };
+        // No SYSTEM:PROTO? - falls back to the *IDN? firmware field
+        let check = manager.check_protocol_version().await;
+        assert_eq!(check.status, ProtocolStatus::MinorMismatch);
+        assert_eq!(manager.get_state().await.protocol_version.as_deref(), Some("v1.1"));
 
-                    // Emit event if changed
-                    let is_broadcasting = s.broadcast == BroadcastState::Broadcasting;
-                    if was_broadcasting != is_broadcasting {
-                        if is_broadcasting {
-                            let _ = event_tx.send(EventType::BroadcastStarted);
-                        } else {
-                            let _ = event_tx.send(EventType::BroadcastStopped);
-                        }
-                    }
-                }
-                "WATCHDOG" => {
-                    let old_state = s.watchdog;
-                    s.watchdog = match value {
-                        "0" | "OK" => WatchdogState::Ok,
-                        "1" | "WARNING" => WatchdogState::Warning,
-                        "2" | "TRIGGERED" | "FAIL" => WatchdogState::Triggered,
-                        _ => WatchdogState::Ok,
-                    };
+        assert!(manager.read_vswr(1).await.is_err());
 
-                    // Emit event if watchdog triggered
-                    if s.watchdog == WatchdogState::Triggered && old_state != WatchdogState::Triggered {
-                        let _ = event_tx.send(EventType::WatchdogTriggered);
+        // Link still in step after the unanswered queries
+        let idn = manager.query("*IDN?").await.unwrap();
+        assert!(idn.contains("STEMlab125-14"), "{}", idn);
+        manager.disconnect(true).await.unwrap();
+    }
 
-                        // Auto-stop broadcast on watchdog trigger
-                        s.broadcast = BroadcastState::Idle;
-                        let _ = event_tx.send(EventType::BroadcastStopped);
-                    } else if s.watchdog == WatchdogState::Warning && old_state == WatchdogState::Ok {
-                        let _ = event_tx.send(EventType::WatchdogWarning);
-                    }
-                }
-                "TEMP" | "TEMPERATURE" => {
-                    if let Ok(temp) = value.parse::<f32>() {
-                        s.fpga_temperature = Some(temp);
-                    }
-                }
-                "SOURCE" => {
-                    s.source = if value == "ADC" {
-                        SourceMode::Adc
-                    } else {
-                        SourceMode::Bram
-                    };
-                }
-                _ => {
-                    // Check for channel status: "CH1", "CH2", etc.
-                    if let Some(stripped) = key.strip_prefix("CH") {
-                        if let Ok(ch_num) = stripped.parse::<u8>() {
-                            if let Some(channel) = s.channels.iter_mut().find(|c| c.id == ch_num) {
-                                channel.enabled = value == "1" || value == "ON";
-                            }
-                        }
-                    }
-                }
-            }
-        }
+    #[tokio::test]
+    async fn test_pending_changes_queue_and_clear() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        assert!(manager.pending_changes().await.is_empty());
+
+        let change = |ch, frequency, enabled| ChannelChange { channel_id: ch, frequency, enabled };
+        manager.queue_channel_change(change(1, Some(600_000), None)).await.unwrap();
+        manager.queue_channel_change(change(2, None, Some(true))).await.unwrap();
+        // Second edit to CH1 merges into the first
+        assert_eq!(manager.queue_channel_change(change(1, None, Some(true))).await.unwrap(), 2);
+        assert!(manager.queue_channel_change(change(13, None, Some(true))).await.is_err());
+        assert!(manager.queue_channel_change(change(3, Some(2_000_000), None)).await.is_err());
+
+        assert_eq!(manager.pending_changes().await, vec![change(1, Some(600_000), Some(true)), change(2, None, Some(true))]);
+        sleep(Duration::from_millis(50)).await;
+        assert!(mock.received().is_empty());
+
+        assert_eq!(manager.clear_pending_changes().await, 2);
+        assert!(manager.pending_changes().await.is_empty());
+
+        // Flushing sends the queue and empties it
+        manager.queue_channel_change(change(4, Some(800_000), Some(true))).await.unwrap();
+        assert_eq!(manager.flush_pending_changes().await.unwrap(), vec![4]);
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(mock.received(), vec!["CH4:FREQ 800000", "CH4:OUTPUT ON"]);
+        assert!(manager.pending_changes().await.is_empty());
+    }
 
-        // Update timestamp
-        s.last_status_time = Some(
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs()
-        );
+    #[tokio::test]
+    async fn test_schedule_stop_at_stops_output() {
+        let mock = output_mock().await;
+        let manager = connected_manager(&mock).await;
+        manager.set_min_output_interval(0).await;
+        manager.state.write().await.channels[0].enabled = true;
+        manager.start_broadcast().await.unwrap();
+
+        let at = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() + 2;
+        manager.schedule_stop_at(at).await.unwrap();
+        assert_eq!(manager.get_state().await.scheduled_stop_at, Some(at));
+
+        wait_for_event(&manager, "StopCountdown").await;
+        wait_for_event(&manager, "BroadcastStopped").await;
+        let state = manager.get_state().await;
+        assert_eq!(state.broadcast, BroadcastState::Idle);
+        assert_eq!(state.last_stop_reason, Some(StopReason::Scheduled));
+        assert_eq!(state.scheduled_stop_at, None);
     }
 
-    // START BROADCAST
-    pub async fn start_broadcast(&self) -> Result<(), String> {
-        // Check connection
-        let state = self.state.read().await;
-        if state.connection != ConnectionState::Connected {
-            return Err("Not connected".to_string());
-        }
-        drop(state);
+    #[tokio::test]
+    async fn test_schedule_stop_rejects_past_and_cancels() {
+        let mock = output_mock().await;
+        let manager = connected_manager(&mock).await;
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+
+        assert!(manager.schedule_stop_at(now - 60).await.unwrap_err().contains("past"));
+        assert!(manager.schedule_stop_at(u64::MAX).await.unwrap_err().contains("out of range"));
+        assert_eq!(manager.get_state().await.scheduled_stop_at, None);
+
+        manager.set_min_output_interval(0).await;
+        manager.state.write().await.channels[0].enabled = true;
+        manager.start_broadcast().await.unwrap();
+        manager.schedule_stop_at(now + 1).await.unwrap();
+        assert!(manager.cancel_scheduled_stop().await);
+        assert!(!manager.cancel_scheduled_stop().await);
+
+        sleep(Duration::from_millis(2100)).await;
+        assert_eq!(manager.get_state().await.broadcast, BroadcastState::Broadcasting);
+    }
 
-        // Check if any channels are enabled
-        let active_count = {
-            let state = self.state.read().await;
-            state.channels.iter().filter(|c| c.enabled).count()
-        };
+    #[tokio::test]
+    async fn test_auto_offset_moves_up_to_free_slot() {
+        let manager = manager_with_enabled(&[(1, 600_000), (2, 700_000)]).await;
 
-        if active_count == 0 {
-            return Err("No active channels".to_string());
-        }
+        // Clear frequency is kept as is
+        assert_eq!(manager.auto_offset_frequency(5, 650_000).await.unwrap(), 650_000);
 
-        self.log_info(&format!("Starting broadcast on {} channels", active_count)).await;
+        // Duplicate of CH1: 610k still too close, 620k and 580k both clear - up wins
+        assert_eq!(manager.auto_offset_frequency(5, 600_000).await.unwrap(), 620_000);
 
-        // Send command to FPGA
-        self.send_command(ScpiCommands::OUTPUT_ON).await?;
+        // Top of the band: only downward slots remain
+        manager.state.write().await.channels[2] = Channel { id: 3, enabled: true, frequency: Config::MAX_FREQUENCY, amplitude: 1.0, phase: 0.0, pilot: false };
+        assert_eq!(manager.auto_offset_frequency(5, Config::MAX_FREQUENCY).await.unwrap(), Config::MAX_FREQUENCY - 20_000);
+    }
 
-        // Update state
-        {
-            let mut state = self.state.write().await;
-            state.broadcast = BroadcastState::Broadcasting;
-        }
+    #[tokio::test]
+    async fn test_auto_offset_fails_without_nearby_slot() {
+        // Carriers every 20 kHz from 540 kHz to 760 kHz; CH12 is confined to 640-660 kHz
+        let packed: Vec<(u8, u32)> = (1..=12).map(|id| (id, 540_000 + (id as u32 - 1) * 20_000)).collect();
+        let manager = manager_with_enabled(&packed).await;
+        manager.set_channel_bounds(12, Some(ChannelBounds { min: 640_000, max: 660_000 })).await.unwrap();
 
-        // Emit event
-        let _ = self.event_tx.send(EventType::BroadcastStarted);
+        let err = manager.auto_offset_frequency(12, 640_000).await.unwrap_err();
+        assert!(err.contains("no free slot"), "{}", err);
+    }
 
-        self.log_info("Broadcast started").await;
-        Ok(())
+    #[tokio::test]
+    async fn test_set_channel_auto_offset_moves_colliding_enable() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        set_amplitudes(&manager, &[(1, 0.5)]).await;  // CH1 on at 540 kHz
+
+        let applied = manager.set_channel_auto_offset(5, 540_000, true, Some(0.5), false).await.unwrap();
+        assert_eq!(applied, 560_000);
+        let ch5 = manager.get_state().await.channels[4].clone();
+        assert_eq!((ch5.frequency, ch5.enabled), (560_000, true));
+        sleep(Duration::from_millis(50)).await;
+        assert!(mock.received().contains(&"CH5:FREQ 560000".to_string()));
+
+        // Disabling is never offset
+        assert_eq!(manager.set_channel_auto_offset(6, 540_000, false, None, false).await.unwrap(), 540_000);
     }
 
-    // STOP BROADCAST
-    pub async fn stop_broadcast(&self) -> Result<(), String> {
-        self.log_info("Stopping broadcast").await;
+    #[tokio::test]
+    async fn test_power_on_self_test_passes_and_leaves_channels_off() {
+        let mock = channel_mock(0).await;
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.channels[0].enabled = true;
+        let before = manager.get_state().await.channels;
 
-        // Send command to FPGA
-        if let Err(e) = self.send_command(ScpiCommands::OUTPUT_OFF).await {
-            self.log_error(&format!("Failed to send stop command: {}", e)).await;
-            // Continue anyway to update local state
-        }
+        let results = manager.power_on_self_test().await.unwrap();
+        assert_eq!(results.len(), 12);
+        assert!(results.iter().all(|r| r.passed), "{:?}", results);
 
-        // Update state
-        {
-            let mut state = self.state.write().await;
-            state.broadcast = BroadcastState::Idle;
+        // Last output command per channel is OFF, and local state agrees
+        sleep(Duration::from_millis(50)).await;
+        let received = mock.received();
+        for ch in 1..=12 {
+            let last = received.iter().rev().find(|c| c.starts_with(&format!("CH{}:OUTPUT", ch))).unwrap();
+            assert_eq!(last, &format!("CH{}:OUTPUT OFF", ch));
         }
+        let after = manager.get_state().await.channels;
+        assert!(after.iter().all(|c| !c.enabled));
+        assert!(after.iter().zip(&before).all(|(a, b)| a.frequency == b.frequency && a.amplitude == b.amplitude));
 
-        // Emit event
-        let _ = self.event_tx.send(EventType::BroadcastStopped);
+        // Refused on air
+        manager.state.write().await.broadcast = BroadcastState::Broadcasting;
+        assert!(manager.power_on_self_test().await.is_err());
+    }
 
-        self.log_info("Broadcast stopped").await;
-        Ok(())
+    #[tokio::test]
+    async fn test_audit_summary_counts_levels() {
+        let manager = test_manager();
+        assert_eq!(manager.audit_summary().await, AuditSummary::default());
+
+        let seeded = [(100, "INFO"), (101, "WARNING"), (102, "ERROR"), (103, "NOTE"),
+            (104, "INFO"), (105, "ERROR"), (106, "WARNING"), (107, "INFO")];
+        *manager.audit.log.write().await = seeded.iter()
+            .map(|&(timestamp, level)| AuditEntry { timestamp, level: level.to_string(), message: String::new(), operator: None })
+            .collect();
+
+        let summary = manager.audit_summary().await;
+        assert_eq!(summary, AuditSummary {
+            total: 8, info: 3, warning: 2, error: 2, note: 1, last_error_timestamp: Some(105),
+        });
     }
 
-    // SET CHANNEL
-    pub async fn set_channel(&self, ch: u8, freq: u32, enabled: bool) -> Result<(), String> {
-        if !(1..=12).contains(&ch) {
-            return Err(format!("Invalid channel: {}", ch));
-        }
+    #[tokio::test]
+    async fn test_replay_restores_frequencies_after_reconnect() {
+        let mock = channel_mock(0).await;
+        let manager = connected_manager(&mock).await;
+        manager.set_channel(3, 700_000, true, None, false).await.unwrap();
+        manager.set_channel(5, 900_000, false, None, false).await.unwrap();
+        manager.send_command(ScpiCommands::WATCHDOG_RESET).await.unwrap();
+        manager.send_command(ScpiCommands::OUTPUT_ON).await.unwrap();
+        manager.query("FREQ:CH3?").await.unwrap();
+
+        // Link drops; the device comes back with nothing set and local state re-read
+        let restarted = channel_mock(0).await;
+        *manager.stream.write().await = Some(BufReader::new(TcpStream::connect(restarted.addr).await.unwrap()));
+        *manager.state.write().await = DeviceState { connection: ConnectionState::Connected, ..DeviceState::default() };
+
+        assert_eq!(manager.replay_last_commands(10).await.unwrap(), 4);
+        assert_eq!(manager.query("FREQ:CH3?").await.unwrap().trim(), "700000");
+        assert_eq!(manager.query("FREQ:CH5?").await.unwrap().trim(), "900000");
+        assert_eq!(restarted.received()[..4], ["CH3:FREQ 700000", "CH3:OUTPUT ON", "CH5:FREQ 900000", "CH5:OUTPUT OFF"]);
+
+        let state = manager.get_state().await;
+        assert_eq!((state.channels[2].frequency, state.channels[2].enabled), (700_000, true));
+        assert_eq!(state.channels[4].frequency, 900_000);
+
+        // Only the most recent N
+        assert_eq!(manager.replay_last_commands(1).await.unwrap(), 1);
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(restarted.received().last().unwrap(), "CH5:OUTPUT OFF");
+    }
 
-        if !(Config::MIN_FREQUENCY..=Config::MAX_FREQUENCY).contains(&freq) {
-            return Err(format!("Frequency {} out of range ({}-{})",
-                freq, Config::MIN_FREQUENCY, Config::MAX_FREQUENCY));
-        }
+    #[tokio::test]
+    async fn test_replay_refused_on_air_or_over_budget() {
+        let mock = channel_mock(0).await;
+        let manager = connected_manager(&mock).await;
+        manager.set_channel(3, 700_000, true, Some(0.8), false).await.unwrap();
+
+        manager.state.write().await.broadcast = BroadcastState::Broadcasting;
+        let err = manager.replay_last_commands(10).await.unwrap_err();
+        assert!(err.contains("broadcasting"), "{}", err);
+        manager.state.write().await.broadcast = BroadcastState::Idle;
+
+        // Budget lowered since the settings were recorded: nothing is re-sent
+        manager.set_max_total_amplitude(0.5).await.unwrap();
+        sleep(Duration::from_millis(50)).await;
+        let sent_before = mock.received().len();
+        let err = manager.replay_last_commands(10).await.unwrap_err();
+        assert!(err.contains("over budget"), "{}", err);
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(mock.received().len(), sent_before);
+    }
 
-        // Set frequency
-        let freq_cmd = format!("CH{}:FREQ {}", ch, freq);
-        self.send_command(&freq_cmd).await?;
+    #[tokio::test]
+    async fn test_operator_attributed_in_audit_and_csv() {
+        let manager = test_manager();
+        manager.add_note("before anyone signed in").await.unwrap();
+
+        assert!(manager.set_operator(" \t\n").await.is_err());
+        assert_eq!(manager.set_operator("  Jo\u{7}  Smith \n").await.unwrap(), "Jo Smith");
+        assert_eq!(manager.set_operator(&"x".repeat(200)).await.unwrap().len(), Config::MAX_OPERATOR_NAME_CHARS);
+        manager.set_operator("Jo Smith").await.unwrap();
+        manager.add_note("Tower check, all good").await.unwrap();
+
+        let log = manager.get_audit_log().await;
+        assert_eq!(log[0].operator, None);
+        assert_eq!(log.last().unwrap().operator.as_deref(), Some("Jo Smith"));
+        assert!(log.iter().skip(1).all(|e| e.operator.is_some()));
+
+        let path = std::env::temp_dir().join(format!("amradio_audit_export_{}.csv", std::process::id()));
+        assert_eq!(manager.export_audit_csv(&path).await.unwrap(), log.len());
+        let csv = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(csv.starts_with("timestamp,level,operator,message\n"));
+        assert!(csv.lines().last().unwrap().ends_with(",NOTE,Jo Smith,\"Tower check, all good\""), "{}", csv);
+    }
 
-        // Set enabled state
-        let state_cmd = format!("CH{}:OUTPUT {}", ch, if enabled { "ON" } else { "OFF" });
-        self.send_command(&state_cmd).await?;
+    #[tokio::test]
+    async fn test_device_fingerprint_match_and_mismatch() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "*IDN?" => Some("RedPitaya,STEMlab125-10,MOCK,v1.0".to_string()),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
 
-        // Update local state
-        {
-            let mut state = self.state.write().await;
-            if let Some(channel) = state.channels.iter_mut().find(|c| c.id == ch) {
-                channel.frequency = freq;
-                channel.enabled = enabled;
-            }
-        }
+        assert_eq!(manager.verify_device_fingerprint("RedPitaya,STEMlab125-10", true).await.unwrap(),
+            "RedPitaya,STEMlab125-10,MOCK,v1.0");
 
-        // Emit event
-        let _ = self.event_tx.send(EventType::ChannelUpdated(ch));
+        // Mismatch kept connected when asked, then dropped
+        let err = manager.verify_device_fingerprint("RedPitaya,STEMlab125-14", false).await.unwrap_err();
+        assert!(err.contains("STEMlab125-10,MOCK"), "{}", err);
+        assert_eq!(manager.get_state().await.connection, ConnectionState::Connected);
 
-        self.log_info(&format!("CH{} set to {} Hz, enabled={}", ch, freq, enabled)).await;
-        Ok(())
+        assert!(manager.verify_device_fingerprint("Keysight", true).await.is_err());
+        assert_eq!(manager.get_state().await.connection, ConnectionState::Disconnected);
     }
 
-    // SET SOURCE MODE
-    pub async fn set_source(&self, source: SourceMode) -> Result<(), String> {
-        let cmd = format!("{} {}",
-            ScpiCommands::SOURCE_MODE,
-            match source {
-                SourceMode::Bram => "BRAM",
-                SourceMode::Adc => "ADC",
+    #[tokio::test]
+    async fn test_consecutive_poll_errors_count_then_clear() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        manager.set_poll_interval(100).await.unwrap();
+        *manager.is_running.write().await = true;
+        manager.spawn_poll_task();
+
+        // Watchdog feed fails while the socket is gone
+        let saved = manager.stream.write().await.take();
+        timeout(Duration::from_secs(2), async {
+            while manager.get_state().await.consecutive_poll_errors == 0 {
+                sleep(Duration::from_millis(10)).await;
             }
-        );
-
-        self.send_command(&cmd).await?;
+        }).await.unwrap();
+        *manager.stream.write().await = saved;
+        assert_eq!(manager.get_state().await.consecutive_poll_errors, 1);
+        assert!(manager.status_line().await.contains(&format!("ERR 1/{}", Config::MAX_CONSECUTIVE_ERRORS)));
+
+        // Next successful feed clears it
+        sleep(Duration::from_millis(250)).await;
+        assert_eq!(manager.get_state().await.consecutive_poll_errors, 0);
+        assert_eq!(manager.get_state().await.connection, ConnectionState::Connected);
+        *manager.is_running.write().await = false;
+    }
 
-        // Update local state
-        self.state.write().await.source = source;
+    #[tokio::test]
+    async fn test_reconcile_guard_holds_recent_change() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        manager.set_reconcile_guard(true, 200).await.unwrap();
+
+        // Operator enables CH3; the device status hasn't caught up yet
+        manager.set_channel(3, 740_000, true, None, false).await.unwrap();
+        manager.parse_status_response("BROADCAST:0,CH3:OFF,CH4:ON").await;
+        let state = manager.get_state().await;
+        assert!(state.channels[2].enabled);
+        assert!(state.channels[3].enabled);  // Untouched channels still reconcile
+
+        // Window over: the device's view wins
+        sleep(Duration::from_millis(250)).await;
+        manager.parse_status_response("BROADCAST:0,CH3:OFF").await;
+        assert!(!manager.get_state().await.channels[2].enabled);
+
+        // Guard off: polls overwrite at once
+        manager.set_reconcile_guard(false, 200).await.unwrap();
+        manager.set_channel(5, 940_000, true, None, false).await.unwrap();
+        manager.parse_status_response("BROADCAST:0,CH5:OFF").await;
+        assert!(!manager.get_state().await.channels[4].enabled);
+
+        assert!(manager.set_reconcile_guard(true, Config::MAX_RECONCILE_WINDOW_MS + 1).await.is_err());
+    }
 
-        // Emit event
-        let _ = self.event_tx.send(EventType::SourceChanged(source));
+    #[tokio::test]
+    async fn test_session_stats_counts_cycles_and_peaks() {
+        let mock = output_mock().await;
+        let manager = connected_manager(&mock).await;
+        manager.set_min_output_interval(0).await;
+        {
+            let mut state = manager.state.write().await;
+            state.channels[0].enabled = true;
+            state.channels[1].enabled = true;
+        }
 
-        Ok(())
-    }
+        manager.start_broadcast().await.unwrap();
+        sleep(Duration::from_millis(60)).await;
+        assert!(manager.session_stats().await.broadcast_ms >= 50);  // Running stretch included
+        manager.stop_broadcast().await.unwrap();
 
-    // ENABLE PRESET CHANNELS
-    pub async fn enable_preset(&self, count: u8) -> Result<(), String> {
-        // Frequency presets (100kHz spacing)
-        let freqs: [u32; 12] = [
-            540_000, 640_000, 740_000, 840_000, 940_000, 1_040_000,
-            1_140_000, 1_240_000, 1_340_000, 1_440_000, 1_540_000, 1_640_000,
-        ];
+        let stats = manager.session_stats().await;
+        assert_eq!((stats.broadcast_cycles, stats.emergency_activations, stats.peak_active_channels), (1, 0, 2));
+        assert!(stats.broadcast_ms >= 50);
 
-        // Distribution patterns
-        let channels: Vec<u8> = match count {
-            1 => vec![1],
-            2 => vec![1, 7],
-            3 => vec![12, 4, 8],
-            4 => vec![12, 3, 6, 9],
-            6 => vec![12, 2, 4, 6, 8, 10],
-            8 => vec![12, 1, 3, 4, 6, 7, 9, 10],
-            12 => (1..=12).collect(),
-            _ => vec![1],
-        };
+        manager.start_emergency(false).await.unwrap();
+        manager.stop_emergency().await.unwrap();
+        let stats = manager.session_stats().await;
+        assert_eq!((stats.broadcast_cycles, stats.emergency_activations), (2, 1));
 
-        // Disable all channels first
-        for ch in 1..=12u8 {
-            self.set_channel(ch, freqs[(ch - 1) as usize], false).await?;
+        for temp in ["41.0", "48.5", "44.0"] {
+            manager.parse_status_response(&format!("BROADCAST:0,TEMP:{}", temp)).await;
         }
+        assert_eq!(manager.session_stats().await.peak_temperature, Some(48.5));
+    }
 
-        // Enable selected channels
-        for &ch in &channels {
-            let freq = freqs[(ch - 1) as usize];
-            self.set_channel(ch, freq, true).await?;
-        }
+    #[tokio::test]
+    async fn test_switch_to_bram_message_waits_for_ready() {
+        // Clip reports ready on the third poll
+        let polls = Arc::new(Mutex::new(0));
+        let mock = MockDevice::spawn(move |cmd| match cmd {
+            "BRAM:READY?" => {
+                let mut polls = polls.lock();
+                *polls += 1;
+                Some(if *polls >= 3 { "1" } else { "0" }.to_string())
+            }
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.source = SourceMode::Adc;
+
+        manager.switch_to_bram_message(4).await.unwrap();
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(mock.received(), vec![
+            "BRAM:PRELOAD 4", "BRAM:READY?", "BRAM:READY?", "BRAM:READY?", "SOURCE:MODE BRAM", "BRAM:SELECT 4",
+        ]);
+        assert!(!mock.received().iter().any(|c| c.starts_with("OUTPUT")));
+
+        let state = manager.get_state().await;
+        assert_eq!((state.source, state.bram_message), (SourceMode::Bram, Some(4)));
+        assert_eq!(manager.recent_events_of_type("SourceChanged", 1).len(), 1);
+    }
 
-        self.log_info(&format!("Enabled {} channel preset", count)).await;
-        Ok(())
+    #[tokio::test]
+    async fn test_switch_to_bram_message_times_out() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "BRAM:READY?" => Some("0".to_string()),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+
+        let err = manager.switch_to_bram_message(2).await.unwrap_err();
+        assert!(err.contains("not ready"), "{}", err);
+        sleep(Duration::from_millis(50)).await;
+        assert!(!mock.received().iter().any(|c| c.starts_with("BRAM:SELECT") || c.starts_with("SOURCE:")));
+        assert_eq!(manager.get_state().await.bram_message, None);
+
+        assert!(manager.switch_to_bram_message(Config::MAX_BRAM_MESSAGES).await.unwrap_err().contains("out of range"));
     }
 
-        // GET STATE (for UI)
-        pub async fn get_state(&self) -> DeviceState {
-            self.state.read().await.clone()
-        }
+    #[tokio::test]
+    async fn test_bram_message_cleared_when_source_leaves_bram() {
+        let mock = MockDevice::spawn(|cmd| match cmd {
+            "BRAM:READY?" => Some("1".to_string()),
+            _ => None,
+        }).await;
+        let manager = connected_manager(&mock).await;
+
+        manager.switch_to_bram_message(3).await.unwrap();
+        manager.set_source(SourceMode::Adc).await.unwrap();
+        let state = manager.get_state().await;
+        assert_eq!((state.source, state.bram_message), (SourceMode::Adc, None));
+
+        // A status report of ADC clears it the same way
+        let mut state = DeviceState::default();
+        state.set_source(SourceMode::Bram);
+        state.bram_message = Some(1);
+        state.set_source(SourceMode::Adc);
+        assert_eq!(state.bram_message, None);
+    }
 
-        // GET AUDIT LOG
-        pub async fn get_audit_log(&self) -> Vec<AuditEntry> {
-            self.audit_log.read().await.clone()
-        }
+    #[tokio::test]
+    async fn test_preset_deviations_lists_only_drifted_channels() {
+        let manager = test_manager();
+        assert!(manager.preset_deviations().await.is_empty());
 
-        // IS CONNECTED
-        pub async fn is_connected(&self) -> bool {
-            self.state.read().await.connection == ConnectionState::Connected
+        {
+            let mut state = manager.state.write().await;
+            state.channels[1].frequency = 650_000;
+            state.channels[6].frequency = 1_100_000;
+            state.channels[3].enabled = true;  // Enable state doesn't matter, only frequency
         }
-    // ARM (for state machine)
-    pub async fn arm(&self) -> Result<(), String> {
-        let state = self.state.read().await;
-        if state.connection != ConnectionState::Connected {
-            return Err("Not connected".to_string());
+        assert_eq!(manager.preset_deviations().await, vec![(2, 650_000, 640_000), (7, 1_100_000, 1_140_000)]);
+    }
+
+    #[tokio::test]
+    async fn test_show_transitions_collapses_display_only() {
+        let manager = test_manager();
+        for (real, settled) in [
+            (BroadcastState::Arming, BroadcastState::Idle),
+            (BroadcastState::Starting, BroadcastState::Broadcasting),
+            (BroadcastState::Stopping, BroadcastState::Idle),
+        ] {
+            manager.state.write().await.broadcast = real;
+
+            manager.set_show_transitions(true).await;
+            assert_eq!(manager.displayed_broadcast().await, real);
+
+            manager.set_show_transitions(false).await;
+            assert_eq!(manager.displayed_broadcast().await, settled);
+            assert_eq!(manager.get_state().await.broadcast, real);
         }
-        drop(state);
+    }
 
-        self.log_info("System armed").await;
-        Ok(())
+    #[tokio::test]
+    async fn test_burn_in_cycles_and_ends_off() {
+        let mock = output_mock().await;
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.channels[0].enabled = true;
+        manager.set_min_output_interval(20).await;
+
+        assert!(manager.burn_in(10, 50, 3).await.is_err());  // Shorter than the minimum interval
+
+        let results = manager.burn_in(50, 50, 3).await.unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|r| r.passed), "{:?}", results);
+        assert_eq!(results.iter().map(|r| r.cycle).collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(manager.get_state().await.broadcast, BroadcastState::Idle);
+
+        sleep(Duration::from_millis(50)).await;
+        let sent = mock.received();
+        assert_eq!(sent.iter().filter(|c| *c == "OUTPUT:STATE ON").count(), 3);
+        assert_eq!(sent.iter().filter(|c| *c == "OUTPUT:STATE OFF").count(), 3);
+        assert_eq!(sent.iter().rfind(|c| c.starts_with("OUTPUT:STATE ")).unwrap(), "OUTPUT:STATE OFF");
     }
 
-    // START EMERGENCY (bypasses arm)
-    pub async fn start_emergency(&self) -> Result<(), String> {
-        self.log_info("EMERGENCY BROADCAST").await;
+    #[tokio::test]
+    async fn test_burn_in_abort_stops_output() {
+        let mock = output_mock().await;
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.channels[0].enabled = true;
+        manager.set_min_output_interval(0).await;
+
+        let running = manager.clone();
+        let handle = tokio::spawn(async move { running.burn_in(5_000, 100, 5).await });
+        sleep(Duration::from_millis(200)).await;
+        manager.abort_burn_in().await;
+
+        let results = handle.await.unwrap().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!((results[0].passed, results[0].detail.as_str()), (false, "Burn-in aborted"));
+        assert_eq!(manager.get_state().await.broadcast, BroadcastState::Idle);
+    }
 
-        self.send_command(ScpiCommands::OUTPUT_ON).await?;
+    #[tokio::test]
+    async fn test_connection_target_reports_resolved_hostname() {
+        let mock = MockDevice::spawn(any_query_mock()).await;
+        let manager = test_manager();
+        assert!(manager.connection_target().await.is_none());
 
-        {
-            let mut state = self.state.write().await;
-            state.broadcast = BroadcastState::Broadcasting;
-        }
+        manager.connect("localhost", mock.addr.port()).await.unwrap();
+        let target = manager.connection_target().await.unwrap();
+        manager.disconnect(false).await.unwrap();
 
-        let _ = self.event_tx.send(EventType::BroadcastStarted);
-        Ok(())
+        assert_eq!(target.requested, Endpoint::new("localhost", mock.addr.port()));
+        assert_eq!(target.active, target.requested);
+        assert_eq!(target.resolved_addr, mock.addr);
+        assert!(!target.failed_over);
     }
 
-    // STOP EMERGENCY
-    pub async fn stop_emergency(&self) -> Result<(), String> {
-        self.log_info("Stopping emergency broadcast").await;
-        self.stop_broadcast().await
+    #[tokio::test]
+    async fn test_pilot_carries_pilot_amplitude_and_is_flagged() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        manager.state.write().await.channels[2].amplitude = 0.6;
+
+        manager.set_pilot(3, true).await.unwrap();
+        let ch3 = manager.get_state().await.channels[2].clone();
+        assert_eq!((ch3.enabled, ch3.amplitude, ch3.pilot), (true, Config::DEFAULT_PILOT_AMPLITUDE, true));
+        assert!(!manager.get_state().await.channels[1].pilot);
+        sleep(Duration::from_millis(50)).await;
+        assert!(mock.received().contains(&format!("AMP:CH3 {}", Config::DEFAULT_PILOT_AMPLITUDE)));
+
+        // Ending the pilot switches the channel off at its own level
+        manager.set_pilot(3, false).await.unwrap();
+        let ch3 = manager.get_state().await.channels[2].clone();
+        assert_eq!((ch3.enabled, ch3.amplitude, ch3.pilot), (false, 0.6, false));
+        assert!(manager.set_pilot(3, false).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pilot_cleared_by_normal_channel_setting() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        manager.set_pilot_amplitude(0.02).await.unwrap();
+        assert!(manager.set_pilot_amplitude(0.5).await.is_err());
+
+        manager.set_pilot(5, true).await.unwrap();
+        assert_eq!(manager.get_state().await.channels[4].amplitude, 0.02);
+
+        manager.set_channel(5, 1_000_000, true, None, false).await.unwrap();
+        let ch5 = manager.get_state().await.channels[4].clone();
+        assert_eq!((ch5.enabled, ch5.amplitude, ch5.pilot), (true, 1.0, false));
     }
-}
 
+    #[tokio::test]
+    async fn test_connection_target_reports_failover() {
+        let down = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        let backup = MockDevice::spawn(any_query_mock()).await;
+        let manager = test_manager();
+        manager.set_retry_config(RetryConfig { max_attempts: 1, ..RetryConfig::default() }).await.unwrap();
+
+        let endpoints = [Endpoint::new("127.0.0.1", down), Endpoint::new("127.0.0.1", backup.addr.port())];
+        manager.connect_with_failover(&endpoints).await.unwrap();
+        *manager.is_running.write().await = false;
+
+        let target = manager.connection_target().await.unwrap();
+        assert_eq!(target.requested, endpoints[0]);
+        assert_eq!(target.active, endpoints[1]);
+        assert_eq!(target.resolved_addr, backup.addr);
+        assert!(target.failed_over);
+    }
 
+    #[tokio::test]
+    async fn test_pilot_cleared_by_status_and_replay() {
+        let mock = MockDevice::spawn(|_| None).await;
+        let manager = connected_manager(&mock).await;
+        manager.set_reconcile_guard(false, 0).await.unwrap();
+
+        // Device reports the pilot's carrier off
+        manager.set_pilot(2, true).await.unwrap();
+        manager.parse_status_response("BROADCAST:0,WATCHDOG:0,CH2:OFF").await;
+        let state = manager.get_state().await;
+        assert!(!state.channels[1].pilot);
+        assert!(state.pilot_saved_amplitudes.is_empty());
+
+        // A replayed level change makes it an ordinary channel again
+        manager.set_pilot(2, true).await.unwrap();
+        manager.send_command("AMP:CH2 0.7").await.unwrap();
+        manager.replay_last_commands(1).await.unwrap();
+        let state = manager.get_state().await;
+        assert_eq!((state.channels[1].amplitude, state.channels[1].pilot), (0.7, false));
+        assert!(state.pilot_saved_amplitudes.is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+#![allow(dead_code)]
+// plan_code.rs
+// Compact shareable channel plan: "AM1" + one 5-char group per enabled channel
+// (hex channel id + Hz above MIN_FREQUENCY as 4 base-36 digits) + 2-digit base-36 checksum.
+// e.g. CH1 @ 540 kHz, CH7 @ 1140 kHz -> "AM1" "10UV4" "7DPTS" + checksum
+
+use crate::config::Config;
+use crate::model::Channel;
+
+/// Prefix, doubling as the format version
+pub const PLAN_CODE_PREFIX: &str = "AM1";
+
+const DIGITS: &[u8; 36] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const FREQ_DIGITS: usize = 4;  // 36^4 = 1_679_616 > MAX_FREQUENCY - MIN_FREQUENCY
+const CHECKSUM_DIGITS: usize = 2;
+const GROUP_LEN: usize = 1 + FREQ_DIGITS;
+
+/// Encode the enabled channels (id, frequency) in channel order
+pub fn encode(channels: &[Channel]) -> String {
+    let mut enabled: Vec<&Channel> = channels.iter().filter(|c| c.enabled).collect();
+    enabled.sort_by_key(|c| c.id);
+
+    let mut code = PLAN_CODE_PREFIX.to_string();
+    for channel in enabled {
+        code.push(DIGITS[channel.id as usize] as char);
+        code.push_str(&to_base36(channel.frequency.saturating_sub(Config::MIN_FREQUENCY), FREQ_DIGITS));
+    }
+    let checksum = checksum(&code);
+    code.push_str(&to_base36(checksum, CHECKSUM_DIGITS));
+    code
+}
+
+/// Decode a plan code into (channel, frequency) pairs, in channel order.
+/// Whitespace is ignored and letters may be either case.
+pub fn decode(code: &str) -> Result<Vec<(u8, u32)>, String> {
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_uppercase();
+
+    if !code.is_ascii() || !code.starts_with(PLAN_CODE_PREFIX) {
+        return Err("Not a channel plan code".to_string());
+    }
+    if code.len() < PLAN_CODE_PREFIX.len() + CHECKSUM_DIGITS
+        || !(code.len() - PLAN_CODE_PREFIX.len() - CHECKSUM_DIGITS).is_multiple_of(GROUP_LEN)
+    {
+        return Err("Plan code has the wrong length".to_string());
+    }
+
+    let (body, check) = code.split_at(code.len() - CHECKSUM_DIGITS);
+    if from_base36(check) != Some(checksum(body)) {
+        return Err("Plan code checksum mismatch (mistyped or truncated?)".to_string());
+    }
+
+    let mut plan: Vec<(u8, u32)> = Vec::new();
+    for group in body.as_bytes()[PLAN_CODE_PREFIX.len()..].chunks(GROUP_LEN) {
+        let group = std::str::from_utf8(group).map_err(|_| "Not a channel plan code".to_string())?;
+        let ch = u8::from_str_radix(&group[..1], 16)
+            .ok()
+            .filter(|c| (1..=Config::NUM_CHANNELS).contains(c))
+            .ok_or_else(|| format!("Invalid channel in plan code: {}", &group[..1]))?;
+        let freq = from_base36(&group[1..])
+            .map(|offset| offset + Config::MIN_FREQUENCY)
+            .ok_or_else(|| format!("Invalid frequency for CH{} in plan code", ch))?;
+
+        if !(Config::MIN_FREQUENCY..=Config::MAX_FREQUENCY).contains(&freq) {
+            return Err(format!("CH{}: frequency {} out of range ({}-{})",
+                ch, freq, Config::MIN_FREQUENCY, Config::MAX_FREQUENCY));
+        }
+        if plan.iter().any(|(id, _)| *id == ch) {
+            return Err(format!("Duplicate CH{} in plan code", ch));
+        }
+        plan.push((ch, freq));
+    }
+
+    Ok(plan)
+}
+
+// Position-weighted sum so swapped characters are caught as well as changed ones
+fn checksum(text: &str) -> u32 {
+    let modulus = 36u32.pow(CHECKSUM_DIGITS as u32);
+    text.bytes()
+        .enumerate()
+        .fold(0u32, |sum, (i, b)| (sum + (i as u32 + 1) * b as u32) % modulus)
+}
+
+fn to_base36(mut value: u32, width: usize) -> String {
+    let mut digits = vec![b'0'; width];
+    for digit in digits.iter_mut().rev() {
+        *digit = DIGITS[(value % 36) as usize];
+        value /= 36;
+    }
+    String::from_utf8(digits).unwrap_or_default()
+}
+
+fn from_base36(text: &str) -> Option<u32> {
+    u32::from_str_radix(text, 36).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(enabled: &[(u8, u32)]) -> Vec<Channel> {
+        (1..=12).map(|id| {
+            let freq = enabled.iter().find(|(ch, _)| *ch == id).map(|(_, f)| *f);
+            Channel { id, enabled: freq.is_some(), frequency: freq.unwrap_or(540_000), amplitude: 1.0, phase: 0.0, pilot: false }
+        }).collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for enabled in [
+            vec![],
+            vec![(1, 540_000)],
+            vec![(1, 540_000), (7, 1_140_000)],
+            vec![(12, 1_700_000), (3, 500_000), (10, 1_234_567)],
+            (1..=12).map(|ch| (ch, 500_000 + ch as u32 * 100_000)).collect(),
+        ] {
+            let code = encode(&plan(&enabled));
+            let mut expected = enabled.clone();
+            expected.sort();
+            assert_eq!(decode(&code).unwrap(), expected, "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_code_is_compact_and_forgiving() {
+        let code = encode(&plan(&[(1, 540_000), (7, 1_140_000)]));
+        assert_eq!(code.len(), 3 + 2 * 5 + 2);
+        assert!(code.starts_with("AM110UV47DPTS"));
+
+        let pasted = format!("  {} {}\n", code[..8].to_lowercase(), &code[8..]);
+        assert_eq!(decode(&pasted).unwrap(), vec![(1, 540_000), (7, 1_140_000)]);
+    }
+
+    #[test]
+    fn test_corrupted_code_rejected() {
+        let code = encode(&plan(&[(1, 540_000), (7, 1_140_000)]));
+
+        // One character changed
+        let mut changed = code.clone().into_bytes();
+        changed[5] = if changed[5] == b'Z' { b'Y' } else { b'Z' };
+        assert!(decode(&String::from_utf8(changed).unwrap()).unwrap_err().contains("checksum"));
+
+        // Two characters swapped
+        let mut swapped = code.clone().into_bytes();
+        swapped.swap(4, 5);
+        assert!(decode(&String::from_utf8(swapped).unwrap()).is_err());
+
+        assert!(decode(&code[..code.len() - 1]).unwrap_err().contains("length"));
+        assert!(decode("hello").unwrap_err().contains("Not a channel plan"));
+        assert!(decode("AM1é").is_err());
+        assert!(decode("").is_err());
+    }
+}
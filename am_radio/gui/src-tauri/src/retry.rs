@@ -1,11 +1,12 @@
 // retry.rs
 // Exponential backoff retry logic for connection handling
 
+use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use tokio::time::sleep;
 
 /// Retry configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RetryConfig {
     pub max_attempts: u32,
     pub initial_delay_ms: u64,
@@ -25,6 +26,23 @@ impl Default for RetryConfig {
 }
 
 impl RetryConfig {
+    /// Reject settings that would never retry or would shrink the backoff
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_attempts == 0 {
+            return Err("max_attempts must be at least 1".to_string());
+        }
+        if !self.multiplier.is_finite() || self.multiplier < 1.0 {
+            return Err(format!("multiplier must be >= 1.0 (got {})", self.multiplier));
+        }
+        if self.max_delay_ms < self.initial_delay_ms {
+            return Err(format!(
+                "max_delay_ms ({}) must be >= initial_delay_ms ({})",
+                self.max_delay_ms, self.initial_delay_ms
+            ));
+        }
+        Ok(())
+    }
+
     /// Calculate delay for attempt n (0-indexed)
     /// Attempt 0: 0ms (immediate)
     /// Attempt 1: 1000ms
@@ -194,4 +212,25 @@ mod additional_tests {
         assert_eq!(config.delay_for_attempt(3), Duration::from_millis(4500));
         assert_eq!(config.delay_for_attempt(4), Duration::from_millis(5000));
     }
+
+    #[test]
+    fn test_validate_accepts_default() {
+        assert!(RetryConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_config() {
+        let zero_attempts = RetryConfig { max_attempts: 0, ..RetryConfig::default() };
+        assert!(zero_attempts.validate().is_err());
+
+        let shrinking = RetryConfig { multiplier: 0.5, ..RetryConfig::default() };
+        assert!(shrinking.validate().is_err());
+
+        let inverted = RetryConfig {
+            initial_delay_ms: 5000,
+            max_delay_ms: 1000,
+            ..RetryConfig::default()
+        };
+        assert!(inverted.validate().is_err());
+    }
 }
@@ -0,0 +1,126 @@
+#![allow(dead_code)]
+// scene.rs
+// Saved channel plans ("scenes") with a versioned on-disk JSON format
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::Channel;
+use crate::state_machine::SourceMode;
+
+/// Current scene file format version
+pub const SCENE_FILE_VERSION: u32 = 1;
+
+/// A saved channel plan plus audio source
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Scene {
+    pub name: String,
+    pub source: SourceMode,
+    pub channels: Vec<Channel>,
+}
+
+/// On-disk wrapper - the version is checked before the scene is parsed
+#[derive(Serialize, Deserialize)]
+struct SceneFile {
+    version: u32,
+    scene: Scene,
+}
+
+/// Serialize a scene with the current format version
+pub fn to_json(scene: &Scene) -> Result<String, String> {
+    let file = SceneFile {
+        version: SCENE_FILE_VERSION,
+        scene: scene.clone(),
+    };
+    serde_json::to_string_pretty(&file).map_err(|e| format!("Failed to serialize scene: {}", e))
+}
+
+/// Parse a scene file, migrating older versions and rejecting unknown ones
+pub fn from_json(json: &str) -> Result<Scene, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(json).map_err(|e| format!("Invalid scene file: {}", e))?;
+
+    let version = value
+        .get("version")
+        .and_then(|v| v.as_u64())
+        .ok_or("Scene file has no version field")?;
+
+    match version {
+        1 => {
+            let file: SceneFile = serde_json::from_value(value)
+                .map_err(|e| format!("Invalid v1 scene file: {}", e))?;
+            Ok(file.scene)
+        }
+        // Future: migrate older versions here before parsing
+        v if v > SCENE_FILE_VERSION as u64 => Err(format!(
+            "Scene file version {} is newer than supported version {}",
+            v, SCENE_FILE_VERSION
+        )),
+        v => Err(format!("Unsupported scene file version {}", v)),
+    }
+}
+
+/// Write a scene to disk
+pub fn save(path: &Path, scene: &Scene) -> Result<(), String> {
+    let json = to_json(scene)?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Read a scene from disk
+pub fn load(path: &Path) -> Result<Scene, String> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    from_json(&json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_scene() -> Scene {
+        let mut channels: Vec<Channel> = (1..=12).map(Channel::new).collect();
+        channels[0].enabled = true;
+        channels[0].frequency = 540_000;
+        Scene {
+            name: "Tunnel A".to_string(),
+            source: SourceMode::Adc,
+            channels,
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_v1() {
+        let json = to_json(&sample_scene()).unwrap();
+        assert!(json.contains("\"version\": 1"));
+
+        let scene = from_json(&json).unwrap();
+        assert_eq!(scene.name, "Tunnel A");
+        assert_eq!(scene.source, SourceMode::Adc);
+        assert_eq!(scene.channels.len(), 12);
+        assert!(scene.channels[0].enabled);
+    }
+
+    #[test]
+    fn test_save_and_load_file() {
+        let path = std::env::temp_dir().join(format!("amradio_scene_{}.json", std::process::id()));
+        save(&path, &sample_scene()).unwrap();
+        let scene = load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(scene.name, "Tunnel A");
+    }
+
+    #[test]
+    fn test_future_version_rejected() {
+        let json = to_json(&sample_scene()).unwrap().replace("\"version\": 1", "\"version\": 7");
+        let err = from_json(&json).unwrap_err();
+        assert!(err.contains("newer than supported"), "{}", err);
+    }
+
+    #[test]
+    fn test_missing_version_rejected() {
+        let err = from_json(r#"{"scene": {}}"#).unwrap_err();
+        assert!(err.contains("no version"), "{}", err);
+    }
+}
@@ -0,0 +1,84 @@
+#![allow(dead_code)]
+// settings.rs
+// Small persisted user settings (JSON file in the app config dir)
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+/// Connection endpoint
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Endpoint {
+    pub ip: String,
+    pub port: u16,
+}
+
+impl Endpoint {
+    pub fn new(ip: &str, port: u16) -> Self {
+        Self { ip: ip.to_string(), port }
+    }
+}
+
+impl Default for Endpoint {
+    fn default() -> Self {
+        Self {
+            ip: Config::DEFAULT_IP.to_string(),
+            port: Config::DEFAULT_PORT,
+        }
+    }
+}
+
+/// Persisted settings - unknown/missing fields fall back to defaults
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub last_endpoint: Option<Endpoint>,
+}
+
+impl Settings {
+    /// Load settings, falling back to defaults if the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write settings, creating the parent directory if needed
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            if !dir.as_os_str().is_empty() {
+                std::fs::create_dir_all(dir)
+                    .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+            }
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_file_uses_defaults() {
+        let path = std::env::temp_dir().join("amradio_settings_does_not_exist.json");
+        let settings = Settings::load(&path);
+        assert!(settings.last_endpoint.is_none());
+        assert_eq!(Endpoint::default().ip, Config::DEFAULT_IP);
+        assert_eq!(Endpoint::default().port, Config::DEFAULT_PORT);
+    }
+
+    #[test]
+    fn test_corrupt_file_uses_defaults() {
+        let path = std::env::temp_dir().join(format!("amradio_settings_corrupt_{}.json", std::process::id()));
+        std::fs::write(&path, "{ not json").unwrap();
+        let settings = Settings::load(&path);
+        let _ = std::fs::remove_file(&path);
+        assert!(settings.last_endpoint.is_none());
+    }
+}
@@ -112,6 +112,15 @@ impl BroadcastState {
         matches!(self, BroadcastState::Arming | BroadcastState::Starting | BroadcastState::Stopping)
     }
 
+    /// State an in-flight transition is heading to (settled states map to themselves)
+    pub fn settled(&self) -> BroadcastState {
+        match self {
+            BroadcastState::Arming | BroadcastState::Stopping => BroadcastState::Idle,
+            BroadcastState::Starting => BroadcastState::Broadcasting,
+            other => *other,
+        }
+    }
+
     /// Get display string for UI
     pub fn display(&self) -> &'static str {
         match self {
@@ -126,6 +135,104 @@ impl BroadcastState {
     }
 }
 
+/// Outcome of one self-test scenario
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SelftestResult {
+    pub scenario: String,
+    pub passed: bool,
+    pub detail: Option<String>,  // First failed check
+}
+
+fn check(ok: bool, what: &str) -> Result<(), String> {
+    if ok { Ok(()) } else { Err(what.to_string()) }
+}
+
+fn expect(actual: BroadcastState, expected: BroadcastState) -> Result<(), String> {
+    check(actual == expected, &format!("expected {}, got {}", expected.display(), actual.display()))
+}
+
+const ALL_STATES: [BroadcastState; 7] = [
+    BroadcastState::Idle,
+    BroadcastState::Arming,
+    BroadcastState::Armed,
+    BroadcastState::Starting,
+    BroadcastState::Broadcasting,
+    BroadcastState::Stopping,
+    BroadcastState::Emergency,
+];
+
+type Scenario = (&'static str, fn() -> Result<(), String>);
+
+const SELFTEST_SCENARIOS: &[Scenario] = &[
+    ("Normal flow: arm, start, stop", || {
+        let state = BroadcastState::Idle.request_arm()?;
+        expect(state, BroadcastState::Arming)?;
+        let state = state.confirm_armed();
+        expect(state, BroadcastState::Armed)?;
+        let state = state.request_start()?;
+        expect(state, BroadcastState::Starting)?;
+        let state = state.confirm_broadcasting();
+        expect(state, BroadcastState::Broadcasting)?;
+        let state = state.request_stop()?;
+        expect(state, BroadcastState::Stopping)?;
+        expect(state.confirm_stopped(), BroadcastState::Idle)
+    }),
+    ("Cannot broadcast without arming", || {
+        check(BroadcastState::Idle.request_start().is_err(), "start allowed from IDLE")?;
+        check(BroadcastState::Arming.request_start().is_err(), "start allowed while ARMING")
+    }),
+    ("Cannot arm twice", || {
+        check(BroadcastState::Arming.request_arm().is_err(), "arm allowed while ARMING")?;
+        check(BroadcastState::Armed.request_arm().is_err(), "arm allowed while ARMED")
+    }),
+    ("Cannot start while stopping", || {
+        check(BroadcastState::Stopping.request_start().is_err(), "start allowed while STOPPING")
+    }),
+    ("Stop cancels arming and disarms", || {
+        expect(BroadcastState::Arming.request_stop()?, BroadcastState::Idle)?;
+        expect(BroadcastState::Armed.request_stop()?, BroadcastState::Idle)
+    }),
+    ("Emergency from any state", || {
+        for state in ALL_STATES.iter().filter(|s| **s != BroadcastState::Emergency) {
+            expect(state.request_emergency()?, BroadcastState::Emergency)?;
+        }
+        check(BroadcastState::Emergency.request_emergency().is_err(), "emergency re-entered")
+    }),
+    ("Emergency only ends via stop_emergency", || {
+        expect(BroadcastState::Emergency.request_stop_emergency()?, BroadcastState::Idle)?;
+        check(BroadcastState::Emergency.request_stop().is_err(), "normal stop ended emergency")?;
+        check(BroadcastState::Idle.request_stop_emergency().is_err(), "stop_emergency allowed from IDLE")?;
+        check(BroadcastState::Broadcasting.request_stop_emergency().is_err(),
+            "stop_emergency allowed while BROADCASTING")
+    }),
+    ("Confirmations ignored in the wrong state", || {
+        expect(BroadcastState::Idle.confirm_armed(), BroadcastState::Idle)?;
+        expect(BroadcastState::Idle.confirm_broadcasting(), BroadcastState::Idle)?;
+        expect(BroadcastState::Idle.confirm_stopped(), BroadcastState::Idle)?;
+        expect(BroadcastState::Broadcasting.confirm_armed(), BroadcastState::Broadcasting)
+    }),
+    ("Output-on states", || {
+        for state in ALL_STATES {
+            let expected = matches!(state, BroadcastState::Broadcasting | BroadcastState::Emergency);
+            check(state.is_broadcasting() == expected, &format!("{} is_broadcasting wrong", state.display()))?;
+        }
+        Ok(())
+    }),
+];
+
+/// Walk the broadcast transitions the unit tests cover, so a deployed build can
+/// confirm its safety logic without a test harness
+pub fn run_selftest() -> Vec<SelftestResult> {
+    SELFTEST_SCENARIOS.iter().map(|(scenario, run)| {
+        let outcome = run();
+        SelftestResult {
+            scenario: scenario.to_string(),
+            passed: outcome.is_ok(),
+            detail: outcome.err(),
+        }
+    }).collect()
+}
+
 /// Connection state machine
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum ConnectionState {
@@ -175,6 +282,20 @@ impl WatchdogState {
     }
 }
 
+/// Why the last broadcast went back to idle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StopReason {
+    Manual,
+    Watchdog,
+    OverTemp,       // Device dropped output while over temperature
+    Disconnect,     // Disconnect or lost connection
+    Fault,          // Fatal device fault or device reboot
+    Emergency,      // Emergency broadcast ended
+    LicenseWindow,  // Licensed hours ended mid-broadcast
+    Vswr,           // Antenna VSWR over the safety limit
+    Scheduled,      // Reached the time set by schedule_stop_at
+}
+
 /// Audio source
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum SourceMode {
@@ -284,6 +405,15 @@ mod additional_tests {
         assert!(BroadcastState::Emergency.is_broadcasting());
     }
 
+    #[test]
+    fn test_settled_collapses_transitions() {
+        assert_eq!(BroadcastState::Arming.settled().display(), "IDLE");
+        assert_eq!(BroadcastState::Starting.settled().display(), "BROADCASTING");
+        assert_eq!(BroadcastState::Stopping.settled().display(), "IDLE");
+        assert_eq!(BroadcastState::Armed.settled(), BroadcastState::Armed);
+        assert_eq!(BroadcastState::Emergency.settled(), BroadcastState::Emergency);
+    }
+
     #[test]
     fn test_is_transitioning() {
         assert!(BroadcastState::Arming.is_transitioning());
@@ -337,4 +467,14 @@ mod additional_tests {
         assert_eq!(BroadcastState::Idle.confirm_stopped(), BroadcastState::Idle);
         assert_eq!(BroadcastState::Broadcasting.confirm_armed(), BroadcastState::Broadcasting);
     }
+
+    #[test]
+    fn test_selftest_passes_on_correct_build() {
+        let results = run_selftest();
+        assert_eq!(results.len(), SELFTEST_SCENARIOS.len());
+        for result in &results {
+            assert!(result.passed, "{}: {:?}", result.scenario, result.detail);
+            assert_eq!(result.detail, None);
+        }
+    }
 }
@@ -0,0 +1,65 @@
+#![allow(dead_code)]
+// stations.rs
+// Call-sign lookup table for tuning by station: {"CALLSIGN": frequency_hz, ...}
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::config::Config;
+
+/// Parse a station table. Call signs are trimmed and upper-cased; every
+/// frequency must fall inside the AM band.
+pub fn from_json(json: &str) -> Result<HashMap<String, u32>, String> {
+    let raw: HashMap<String, u32> =
+        serde_json::from_str(json).map_err(|e| format!("Invalid station file: {}", e))?;
+
+    let mut stations = HashMap::with_capacity(raw.len());
+    for (call_sign, frequency) in raw {
+        let call_sign = normalize(&call_sign);
+        if call_sign.is_empty() {
+            return Err("Empty call sign in station file".to_string());
+        }
+        if !(Config::MIN_FREQUENCY..=Config::MAX_FREQUENCY).contains(&frequency) {
+            return Err(format!("{}: frequency {} out of range ({}-{})",
+                call_sign, frequency, Config::MIN_FREQUENCY, Config::MAX_FREQUENCY));
+        }
+        if stations.insert(call_sign.clone(), frequency).is_some() {
+            return Err(format!("Duplicate call sign {}", call_sign));
+        }
+    }
+
+    Ok(stations)
+}
+
+/// Read a station table from disk
+pub fn load(path: &Path) -> Result<HashMap<String, u32>, String> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    from_json(&json)
+}
+
+/// Lookup key for a call sign
+pub fn normalize(call_sign: &str) -> String {
+    call_sign.trim().to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_normalizes_call_signs() {
+        let stations = from_json(r#"{" tunnel1 ": 540000, "PA-2": 1000000}"#).unwrap();
+        assert_eq!(stations.len(), 2);
+        assert_eq!(stations["TUNNEL1"], 540_000);
+        assert_eq!(stations["PA-2"], 1_000_000);
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_tables() {
+        assert!(from_json(r#"{"LOW": 99000}"#).unwrap_err().contains("out of range"));
+        assert!(from_json(r#"{"  ": 540000}"#).unwrap_err().contains("Empty"));
+        assert!(from_json(r#"{"abc": 540000, "ABC": 640000}"#).unwrap_err().contains("Duplicate"));
+        assert!(from_json("[1, 2]").is_err());
+    }
+}
@@ -26,6 +26,13 @@ const Controller = {
     // Initialize View
     View.init();
 
+    // Default connection fields to the last-used endpoint
+    const endpoint = await Model.getLastEndpoint();
+    if (endpoint) {
+      document.getElementById('ipInput').value = endpoint.ip;
+      document.getElementById('portInput').value = endpoint.port;
+    }
+
     // Bind event handlers
     this.bindConnectionEvents();
     this.bindBroadcastEvents();
@@ -19,6 +19,7 @@ const Model = {
     try {
       const result = await this.invoke('connect', { ip, port: parseInt(port) });
       EventBus.publish(Events.UI_LOG, { message: `Connected to ${ip}:${port}`, type: 'success' });
+      await this.saveLastEndpoint(ip, port);
       return result;
     } catch (err) {
       EventBus.publish(Events.UI_LOG, { message: `Connection failed: ${err}`, type: 'error' });
@@ -37,6 +38,24 @@ const Model = {
     }
   },
 
+  async getLastEndpoint() {
+    try {
+      return await this.invoke('get_last_endpoint');
+    } catch (err) {
+      console.error('Failed to load last endpoint:', err);
+      return null;
+    }
+  },
+
+  async saveLastEndpoint(ip, port) {
+    try {
+      return await this.invoke('save_last_endpoint', { ip, port: parseInt(port) });
+    } catch (err) {
+      console.error('Failed to save endpoint:', err);
+      return null;
+    }
+  },
+
   // ==================== BROADCAST STATE MACHINE ====================
 
   async arm() {